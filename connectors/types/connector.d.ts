@@ -18,10 +18,28 @@ export interface ConnectorScope {
   scope: string;
   label: string;
   description: string;
+  /** JSON Schema of the data exported for this scope */
+  schema?: Record<string, unknown>;
 }
 
-/** Metadata structure for connector definition files */
+/** How a connector script is run */
+export type ConnectorRuntime = 'vanilla' | 'network-capture' | 'playwright';
+
+/**
+ * Capabilities a connector asks for beyond reading the pages it opens:
+ * - 'network-capture': page.captureNetwork() and the network-capture runtime
+ * - 'downloads': let the browser download files
+ */
+export type ConnectorPermission = 'network-capture' | 'downloads';
+
+/**
+ * Metadata structure for connector definition files.
+ * Version 2 manifests require `manifestVersion: 2`, `id`, `entry` and `runtime`;
+ * manifests without `manifestVersion` are version 1 and run `<file stem>.js`.
+ */
 export interface ConnectorMetadata {
+  /** Manifest schema version (1 when omitted) */
+  manifestVersion?: 1 | 2;
   /** Unique identifier for the connector (e.g., 'chatgpt-001') */
   id?: string;
   /** Display name of the platform */
@@ -36,8 +54,25 @@ export interface ConnectorMetadata {
   connectSelector: string;
   /** How often exports should be refreshed */
   exportFrequency?: 'daily' | 'weekly' | 'monthly';
+  /** Script to run, relative to this file (e.g. 'chatgpt-playwright.js') */
+  entry?: string;
+  /** How the script is run (default: 'vanilla') */
+  runtime?: ConnectorRuntime;
+  /** Playwright runner protocol the script targets ('playwright' runtime only) */
+  runnerProtocolVersion?: number;
   /** Data scopes this connector supports */
   scopes?: ConnectorScope[];
+  /** Capabilities the connector needs */
+  permissions?: ConnectorPermission[];
+  /**
+   * Hosts the connector may open and fetch from, e.g. ['chatgpt.com', '*.openai.com']
+   * ('*.example.com' also matches example.com). Must include the connectURL host when set.
+   * Webview connectors default to the connectURL site; Playwright runs are only confined
+   * to these hosts when the list is set.
+   */
+  allowedDomains?: string[];
+  /** Oldest DataConnect version that can run this connector (semver) */
+  minAppVersion?: string;
   /** Configuration for vectorization */
   vectorize_config?: {
     documents?: string;
@@ -1,13 +1,42 @@
 /**
  * Playwright Runner for DataConnect
  *
- * Runs as a sidecar process, receives commands via stdin, sends results via stdout.
+ * Runs as a long-lived sidecar process, receives commands via stdin, sends results via stdout.
+ * Several runs can be in flight at once; every run message carries its runId, and a
+ * { type: "done", runId } message is sent once a run's browser has been released.
+ *
+ * Every message is one JSON object per line, tagged by `type` (mirrored by
+ * src-tauri/src/commands/runner_protocol.rs). On startup the runner sends
+ * { type: "ready", protocolVersion, runnerVersion }; the app's first command is
+ * { type: "hello", protocolVersion } and the runner exits if the versions differ.
+ * Commands it can't parse are answered with { type: "protocol-error", message, runId?, requestId? }.
  *
  * Commands:
- * - { type: "run", runId, connectorPath, url, headless }
+ * - { type: "hello", protocolVersion }
+ * - { type: "run", runId, connectorPath, url, headless, headedFallback?, scopes?, timeoutMs?, simulateNoChrome?, diagnosticsDir? }
  * - { type: "stop", runId }
+ * - { type: "prompt-answer", runId, promptId, value, cancelled? } (reply to a "prompt" message)
+ * - { type: "ping", requestId } -> { type: "pong", requestId, activeRuns }
+ * - { type: "test", requestId? } -> { type: "test-result", requestId, data }
  * - { type: "quit" }
  *
+ * A run's result is sent as { type: "result-chunk", runId, seq, data } pieces of its JSON
+ * followed by { type: "result-commit", runId, chunks }, so large exports don't travel as one line.
+ *
+ * When a run fails and the run command named a diagnosticsDir, the runner saves a screenshot,
+ * the page's DOM, a HAR of recent requests and the console messages there, then sends
+ * { type: "diagnostics", runId, files }.
+ *
+ * Each time a run launches its browser the runner sends { type: "browser", runId, pid } with
+ * the PID of that Chrome, which leads its own process group. The app kills just that group
+ * when a timed-out run doesn't release its browser.
+ *
+ * Log lines on stderr that belong to a run start with "[run:<runId>] ".
+ *
+ * Connectors can ask the user a question with pageApi.askUser(); the runner sends
+ * { type: "prompt", runId, promptId, kind, message, options?, timeoutMs } and, when it times
+ * out or the run ends unanswered, { type: "prompt-closed", runId, promptId, reason }.
+ *
  * Supports two-phase connectors:
  * - Phase 1 (Browser): Login detection + credential extraction
  * - Phase 2 (Background): Direct HTTP fetch without browser
@@ -19,6 +48,19 @@ const readline = require('readline');
 const path = require('path');
 const { execSync } = require('child_process');
 
+// Bump together with RUNNER_PROTOCOL_VERSION in src-tauri/src/commands/runner_protocol.rs
+const PROTOCOL_VERSION = 5;
+
+// Results are streamed as result-chunk messages of at most this many characters
+const RESULT_CHUNK_SIZE = 512 * 1024;
+const RUNNER_VERSION = '1.0.0';
+// How much browser activity is kept per run for failure diagnostics
+const DIAGNOSTICS_CONSOLE_LIMIT = 200;
+const DIAGNOSTICS_REQUEST_LIMIT = 100;
+const DIAGNOSTICS_CAPTURE_TIMEOUT_MS = 5000;
+// Header values never written to a HAR
+const SENSITIVE_HEADERS = ['cookie', 'set-cookie', 'authorization', 'proxy-authorization', 'x-csrf-token', 'x-api-key'];
+
 // System Chrome paths by platform
 const CHROME_PATHS = {
   darwin: '/Applications/Google Chrome.app/Contents/MacOS/Google Chrome',
@@ -249,6 +291,12 @@ async function downloadChromium(sendStatus) {
 // Active browser contexts by runId
 const activeRuns = new Map();
 
+// Prompts waiting for an answer from the user, keyed by `${runId}:${promptId}`
+const pendingPrompts = new Map();
+const DEFAULT_PROMPT_TIMEOUT_MS = 5 * 60 * 1000;
+const PROMPT_KINDS = ['text', 'secret', 'code', 'choice', 'confirm'];
+let promptCounter = 0;
+
 // Send message to parent process
 function send(msg) {
   console.log(JSON.stringify(msg));
@@ -259,11 +307,16 @@ function log(...args) {
   console.error('[PlaywrightRunner]', ...args);
 }
 
+// Log for one run; the host also files `[run:<runId>]` lines under that run's log
+function runLogger(runId) {
+  return (...args) => console.error(`[run:${runId}]`, ...args);
+}
+
 // Resolve browser executable path
-function resolveBrowserPath() {
+function resolveBrowserPath(simulateNoChrome = false) {
   let browserPath = null;
 
-  if (!process.env.DATACONNECT_SIMULATE_NO_CHROME) {
+  if (!process.env.DATACONNECT_SIMULATE_NO_CHROME && !simulateNoChrome) {
     browserPath = getSystemChromePath();
   } else {
     log('DATACONNECT_SIMULATE_NO_CHROME is set, skipping system Chrome detection');
@@ -280,8 +333,26 @@ function resolveBrowserPath() {
   return browserPath;
 }
 
-// Launch a persistent browser context
-async function launchPersistentContext(userDataDir, headless, browserPath) {
+// PID of the Chrome this process launched on `userDataDir`. Playwright starts Chrome
+// detached, so it is the leader of its own process group.
+function findBrowserPid(userDataDir) {
+  if (process.platform === 'win32') return null;
+  try {
+    const out = execSync('ps -A -o pid= -o ppid= -o args=', { encoding: 'utf8', maxBuffer: 16 * 1024 * 1024 });
+    for (const line of out.split('\n')) {
+      const match = line.trim().match(/^(\d+)\s+(\d+)\s+(.*)$/);
+      if (match && Number(match[2]) === process.pid && match[3].includes(`--user-data-dir=${userDataDir}`)) {
+        return Number(match[1]);
+      }
+    }
+  } catch (e) {
+    log('Could not look up browser PID:', e.message);
+  }
+  return null;
+}
+
+// Launch a persistent browser context. With a runId, the browser's PID is reported for that run.
+async function launchPersistentContext(userDataDir, headless, browserPath, { acceptDownloads = true, runId = null } = {}) {
   // Ensure profile directory exists
   fs.mkdirSync(userDataDir, { recursive: true });
 
@@ -292,6 +363,7 @@ async function launchPersistentContext(userDataDir, headless, browserPath) {
       '--disable-features=MediaRouter,DialMediaRouteProvider',
     ],
     viewport: { width: 1280, height: 800 },
+    acceptDownloads,
     userAgent: 'Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36',
   };
 
@@ -310,11 +382,56 @@ async function launchPersistentContext(userDataDir, headless, browserPath) {
   log(`Launching ${headless ? 'headless' : 'headed'} browser with profile: ${userDataDir}`);
   const context = await chromium.launchPersistentContext(userDataDir, launchOptions);
   log('Browser launched successfully');
+  if (runId) {
+    const pid = findBrowserPid(userDataDir);
+    if (pid) send({ type: 'browser', runId, pid });
+  }
   return context;
 }
 
+// Whether `host` matches one of the patterns; '*.example.com' also matches example.com
+function domainAllowed(host, patterns) {
+  const name = String(host).toLowerCase().replace(/\.$/, '');
+  return patterns.some((pattern) => {
+    const p = String(pattern).toLowerCase();
+    if (p.startsWith('*.')) {
+      const base = p.slice(2);
+      return name === base || name.endsWith('.' + base);
+    }
+    return name === p;
+  });
+}
+
+// Throw unless an http(s) URL is on one of the run's allowed domains (about:blank etc. are fine).
+// Runs without allowedDomains aren't confined.
+function assertUrlAllowed(runState, url) {
+  if (runState.allowedDomains.length === 0) return;
+  let parsed;
+  try {
+    parsed = new URL(url);
+  } catch {
+    return;
+  }
+  if (parsed.protocol !== 'http:' && parsed.protocol !== 'https:') return;
+  if (!domainAllowed(parsed.hostname, runState.allowedDomains)) {
+    throw new Error(`${parsed.hostname} is not in this connector's allowed domains`);
+  }
+}
+
+function requirePermission(runState, permission) {
+  if (!runState.permissions.has(permission)) {
+    throw new Error(`This connector doesn't have the "${permission}" permission`);
+  }
+}
+
+// Options for every browser the connector drives
+function contextOptions(runState) {
+  return { acceptDownloads: runState.permissions.has('downloads'), runId: runState.runId };
+}
+
 // Create the page API that connectors use
 function createPageApi(runState, runId) {
+  const log = runLogger(runId);
   const networkCaptures = new Map();
   const capturedResponses = new Map();
 
@@ -355,15 +472,50 @@ function createPageApi(runState, runId) {
     });
   }
 
+  // Pages that end up off the allowed domains (links, redirects, window.location) are
+  // sent back to about:blank before the connector can read them. Only runs whose manifest
+  // lists allowedDomains are guarded; the others may follow logins to other sites.
+  function guardNavigation(page) {
+    if (runState.allowedDomains.length === 0) return;
+    page.on('framenavigated', (frame) => {
+      if (frame !== page.mainFrame()) return;
+      try {
+        assertUrlAllowed(runState, frame.url());
+      } catch (e) {
+        log(`Blocked navigation: ${e.message}`);
+        send({ type: 'log', runId, message: `Blocked navigation: ${e.message}` });
+        page.goto('about:blank').catch(() => {});
+      }
+    });
+  }
+
   // Set up network capture on initial page
   if (runState.page) {
+    guardNavigation(runState.page);
     setupNetworkCapture(runState.page);
+    recordDiagnostics(runState, runState.page);
   }
 
-  return {
+  // The connector needs the user to log in. A headless run reopens headed so they can,
+  // unless the run is in the background (headedFallback off), where it fails instead.
+  const requireLoginWindow = async (message) => {
+    send({ type: 'status', runId, status: { type: 'CONNECT_WEBSITE', message: message || 'Please log in...' } });
+    if (!runState.headless) return;
+    if (!runState.headedFallback) {
+      throw new Error('Login required. Open the connector to sign in again.');
+    }
+    const page = runState.page;
+    const currentUrl = page && !page.isClosed() ? page.url() : null;
+    log('Login required in headless mode, reopening browser headed');
+    send({ type: 'log', runId, message: 'Login required, opening a browser window...' });
+    await pageApi.showBrowser(currentUrl && currentUrl !== 'about:blank' ? currentUrl : runState.url);
+  };
+
+  const pageApi = {
     goto: async (url, options = {}) => {
       const page = requirePage();
       log(`pageApi.goto called with: ${url}`);
+      assertUrlAllowed(runState, url);
       send({ type: 'log', runId, message: `Navigating to: ${url}` });
       const { waitUntil = 'domcontentloaded', timeout } = options;
       const gotoOpts = { waitUntil };
@@ -387,8 +539,13 @@ function createPageApi(runState, runId) {
 
     sleep: (ms) => new Promise(resolve => setTimeout(resolve, ms)),
 
+    // Scopes the user selected for this connector, or null to collect everything
+    getRequestedScopes: () => (runState.scopes ? [...runState.scopes] : null),
+
     setData: async (key, value) => {
-      if (key === 'status') {
+      if (key === 'status' && isConnectWebsite(value)) {
+        await requireLoginWindow(value.message);
+      } else if (key === 'status') {
         send({ type: 'log', runId, message: value });
         log(`[status] ${value}`);
       } else if (key === 'error') {
@@ -422,7 +579,17 @@ function createPageApi(runState, runId) {
       }
     },
 
+    // Ask the user a question (2FA code, which account, ...) and wait for the answer.
+    // kind: 'text' | 'secret' | 'code' | 'choice' | 'confirm'; rejects on timeout or dismissal.
+    askUser: async (message, options = {}) => {
+      send({ type: 'status', runId, status: { type: 'WAITING_FOR_USER', message } });
+      const answer = await askUser(runId, { ...options, message });
+      send({ type: 'status', runId, status: 'RUNNING' });
+      return answer;
+    },
+
     captureNetwork: async (config) => {
+      requirePermission(runState, 'network-capture');
       networkCaptures.set(config.key, {
         urlPattern: config.urlPattern || '',
         bodyPattern: config.bodyPattern || ''
@@ -486,6 +653,7 @@ function createPageApi(runState, runId) {
     // Closes any existing browser first, then opens a new headed one.
     showBrowser: async (url) => {
       log('showBrowser requested');
+      if (url) assertUrlAllowed(runState, url);
 
       // Close existing browser if open
       if (runState.context && !runState.browserClosed) {
@@ -505,7 +673,7 @@ function createPageApi(runState, runId) {
       runState.browserClosed = false;
       runState.browserClosedByConnector = false;
       runState.headless = false;
-      const context = await launchPersistentContext(runState.userDataDir, false, runState.browserPath);
+      const context = await launchPersistentContext(runState.userDataDir, false, runState.browserPath, contextOptions(runState));
       const page = context.pages()[0] || await context.newPage();
 
       // Set up disconnect handler
@@ -515,9 +683,9 @@ function createPageApi(runState, runId) {
           runState.browserClosed = true;
           runState.context = null;
           runState.page = null;
-          activeRuns.delete(runId);
+          runState.stopped = true;
           send({ type: 'status', runId, status: 'STOPPED' });
-          process.exit(0);
+          endRun(runId, runState);
         }
       });
 
@@ -526,7 +694,9 @@ function createPageApi(runState, runId) {
       runState.page = page;
 
       // Re-setup network capture on new page
+      guardNavigation(page);
       setupNetworkCapture(page);
+      recordDiagnostics(runState, page);
 
       // Navigate to URL
       if (url) {
@@ -564,7 +734,7 @@ function createPageApi(runState, runId) {
       runState.browserClosed = false;
       runState.browserClosedByConnector = false;
       runState.headless = true;
-      const context = await launchPersistentContext(runState.userDataDir, true, runState.browserPath);
+      const context = await launchPersistentContext(runState.userDataDir, true, runState.browserPath, contextOptions(runState));
       const page = context.pages()[0] || await context.newPage();
 
       // Set up disconnect handler
@@ -574,9 +744,9 @@ function createPageApi(runState, runId) {
           runState.browserClosed = true;
           runState.context = null;
           runState.page = null;
-          activeRuns.delete(runId);
+          runState.stopped = true;
           send({ type: 'status', runId, status: 'STOPPED' });
-          process.exit(0);
+          endRun(runId, runState);
         }
       });
 
@@ -585,10 +755,12 @@ function createPageApi(runState, runId) {
       runState.page = page;
 
       // Re-setup network capture on new page
+      guardNavigation(page);
       setupNetworkCapture(page);
+      recordDiagnostics(runState, page);
 
       // Navigate to establish browser context
-      await page.goto('https://chatgpt.com/', { waitUntil: 'domcontentloaded' });
+      await page.goto(runState.url, { waitUntil: 'domcontentloaded' });
 
       send({ type: 'log', runId, message: 'Switched to headless mode for background data collection' });
       log('Switched to headless mode');
@@ -598,6 +770,7 @@ function createPageApi(runState, runId) {
     // Works after closeBrowser() for background data collection.
     // Automatically includes cookies extracted from the browser session.
     httpFetch: async (url, options = {}) => {
+      assertUrlAllowed(runState, url);
       const { timeout = 30000, ...fetchOptions } = options;
 
       // Auto-include cookies from the closed browser session
@@ -655,11 +828,239 @@ function createPageApi(runState, runId) {
       }
     },
   };
+
+  return pageApi;
+}
+
+// Stream a result to the host in chunks. Chunk boundaries never split a surrogate pair,
+// so every chunk is valid on its own.
+function sendResult(runId, data) {
+  const json = JSON.stringify(data === undefined ? null : data);
+  let seq = 0;
+  for (let start = 0; start < json.length || seq === 0; ) {
+    let end = Math.min(start + RESULT_CHUNK_SIZE, json.length);
+    const code = json.charCodeAt(end - 1);
+    if (end < json.length && code >= 0xd800 && code <= 0xdbff) end -= 1;
+    send({ type: 'result-chunk', runId, seq, data: json.slice(start, end) });
+    seq += 1;
+    start = end;
+  }
+  send({ type: 'result-commit', runId, chunks: seq });
+}
+
+// A status the connector reports when the user has to log in:
+// 'CONNECT_WEBSITE' or { type: 'CONNECT_WEBSITE', message }
+function isConnectWebsite(value) {
+  return value === 'CONNECT_WEBSITE' || (value && typeof value === 'object' && value.type === 'CONNECT_WEBSITE');
+}
+
+// Ask the user something and wait for the host to send back a prompt-answer
+function askUser(runId, { message, kind = 'text', options = null, timeoutMs = DEFAULT_PROMPT_TIMEOUT_MS } = {}) {
+  if (!message) throw new Error('askUser needs a message');
+  if (!PROMPT_KINDS.includes(kind)) throw new Error(`Unknown prompt kind: ${kind}`);
+  if (kind === 'choice' && (!Array.isArray(options) || options.length === 0)) {
+    throw new Error("A 'choice' prompt needs options");
+  }
+
+  const promptId = `prompt-${++promptCounter}`;
+  const key = `${runId}:${promptId}`;
+  return new Promise((resolve, reject) => {
+    const timer = setTimeout(() => {
+      pendingPrompts.delete(key);
+      send({ type: 'prompt-closed', runId, promptId, reason: 'timeout' });
+      reject(new Error(`No answer to "${message}" within ${Math.round(timeoutMs / 1000)}s`));
+    }, timeoutMs);
+    pendingPrompts.set(key, { resolve, reject, timer });
+    send({ type: 'prompt', runId, promptId, kind, message, options, timeoutMs });
+  });
+}
+
+function answerPrompt(cmd) {
+  const key = `${cmd.runId}:${cmd.promptId}`;
+  const pending = pendingPrompts.get(key);
+  if (!pending) {
+    send({ type: 'protocol-error', runId: cmd.runId, message: `No pending prompt ${cmd.promptId}` });
+    return;
+  }
+  pendingPrompts.delete(key);
+  clearTimeout(pending.timer);
+  if (cmd.cancelled) {
+    pending.reject(new Error('The user dismissed the prompt'));
+  } else {
+    pending.resolve(cmd.value);
+  }
+}
+
+// Fail any prompts a finished run left unanswered
+function closeRunPrompts(runId) {
+  for (const [key, pending] of pendingPrompts) {
+    if (!key.startsWith(`${runId}:`)) continue;
+    pendingPrompts.delete(key);
+    clearTimeout(pending.timer);
+    send({ type: 'prompt-closed', runId, promptId: key.slice(runId.length + 1), reason: 'run-ended' });
+    pending.reject(new Error('Run ended'));
+  }
+}
+
+function pushLimited(list, entry, limit) {
+  list.push(entry);
+  if (list.length > limit) list.shift();
+}
+
+function harHeaders(headers) {
+  return Object.entries(headers || {}).map(([name, value]) => ({
+    name,
+    value: SENSITIVE_HEADERS.includes(name.toLowerCase()) ? '[REDACTED]' : value,
+  }));
+}
+
+// Keep the page's recent console messages and requests around in case the run fails
+function recordDiagnostics(runState, page) {
+  const { console: consoleMessages, requests } = runState.diagnostics;
+
+  page.on('console', (msg) => {
+    pushLimited(consoleMessages, {
+      time: new Date().toISOString(),
+      type: msg.type(),
+      text: msg.text(),
+      location: msg.location().url || undefined,
+    }, DIAGNOSTICS_CONSOLE_LIMIT);
+  });
+  page.on('pageerror', (error) => {
+    pushLimited(consoleMessages, {
+      time: new Date().toISOString(),
+      type: 'pageerror',
+      text: error.stack || error.message,
+    }, DIAGNOSTICS_CONSOLE_LIMIT);
+  });
+
+  const recordRequest = async (request, failure) => {
+    const timing = request.timing();
+    const response = failure ? null : await request.response().catch(() => null);
+    const elapsed = timing.responseEnd > 0 ? timing.responseEnd : -1;
+    pushLimited(requests, {
+      startedDateTime: new Date(timing.startTime > 0 ? timing.startTime : Date.now()).toISOString(),
+      time: elapsed,
+      request: {
+        method: request.method(),
+        url: request.url(),
+        httpVersion: 'HTTP/1.1',
+        headers: harHeaders(request.headers()),
+        queryString: [],
+        cookies: [],
+        headersSize: -1,
+        bodySize: -1,
+      },
+      response: {
+        status: response ? response.status() : 0,
+        statusText: response ? response.statusText() : (failure || ''),
+        httpVersion: 'HTTP/1.1',
+        headers: harHeaders(response ? response.headers() : {}),
+        cookies: [],
+        content: { size: -1, mimeType: (response && response.headers()['content-type']) || '' },
+        redirectURL: '',
+        headersSize: -1,
+        bodySize: -1,
+      },
+      cache: {},
+      timings: { send: 0, wait: elapsed, receive: 0 },
+      _resourceType: request.resourceType(),
+    }, DIAGNOSTICS_REQUEST_LIMIT);
+  };
+  page.on('requestfinished', (request) => { recordRequest(request, null).catch(() => {}); });
+  page.on('requestfailed', (request) => {
+    recordRequest(request, (request.failure() && request.failure().errorText) || 'failed').catch(() => {});
+  });
+}
+
+function withTimeout(promise, ms) {
+  let timer;
+  return Promise.race([
+    promise,
+    new Promise((_, reject) => { timer = setTimeout(() => reject(new Error('timed out')), ms); }),
+  ]).finally(() => clearTimeout(timer));
+}
+
+// Save what the browser was doing when a run failed into the run's diagnostics directory
+async function saveFailureDiagnostics(runId, runState, error) {
+  const log = runLogger(runId);
+  const dir = runState.diagnosticsDir;
+  if (!dir) return;
+
+  const files = [];
+  const write = (name, contents) => {
+    try {
+      fs.writeFileSync(path.join(dir, name), contents);
+      files.push(name);
+    } catch (e) {
+      log(`Failed to save ${name} for run ${runId}:`, e.message);
+    }
+  };
+
+  try {
+    fs.mkdirSync(dir, { recursive: true });
+  } catch (e) {
+    log(`Failed to create diagnostics directory for run ${runId}:`, e.message);
+    return;
+  }
+
+  write('error.txt', `${error && (error.stack || error.message) || error}\n`);
+  write('console.jsonl', runState.diagnostics.console.map((entry) => JSON.stringify(entry)).join('\n') + '\n');
+  write('network.har', JSON.stringify({
+    log: {
+      version: '1.2',
+      creator: { name: 'dataconnect-playwright-runner', version: RUNNER_VERSION },
+      pages: [],
+      entries: runState.diagnostics.requests,
+    },
+  }, null, 2));
+
+  const page = runState.page;
+  if (page && !runState.browserClosed && !page.isClosed()) {
+    try {
+      const screenshot = await page.screenshot({ timeout: DIAGNOSTICS_CAPTURE_TIMEOUT_MS });
+      write('screenshot.png', screenshot);
+    } catch (e) {
+      log(`Failed to take screenshot for run ${runId}:`, e.message);
+    }
+    try {
+      const html = await withTimeout(page.content(), DIAGNOSTICS_CAPTURE_TIMEOUT_MS);
+      write('page.html', `<!-- ${page.url()} -->\n${html}`);
+    } catch (e) {
+      log(`Failed to capture DOM for run ${runId}:`, e.message);
+    }
+  }
+
+  if (files.length > 0) {
+    send({ type: 'diagnostics', runId, files });
+  }
+}
+
+// Forget a run and tell the host its browser profile is free again.
+// The runner itself stays alive for the next run.
+function endRun(runId, runState) {
+  if (runState.ended) return;
+  runState.ended = true;
+  activeRuns.delete(runId);
+  closeRunPrompts(runId);
+  send({ type: 'done', runId });
 }
 
 // Run a connector
-async function runConnector(runId, connectorPath, url, headless = true) {
-  log(`Starting run ${runId} with connector ${connectorPath} (headless: ${headless})`);
+// Options (from per-connector settings): scopes - requested scopes or null for all,
+// timeoutMs - fail the run if the connector hasn't finished in time
+async function runConnector(runId, connectorPath, url, headless = true, options = {}) {
+  const log = runLogger(runId);
+  const {
+    scopes = null,
+    timeoutMs = null,
+    simulateNoChrome = false,
+    headedFallback = true,
+    diagnosticsDir = null,
+    allowedDomains = [],
+    permissions = [],
+  } = options;
+  log(`Starting run ${runId} with connector ${connectorPath} (headless: ${headless}, headed fallback: ${headedFallback})`);
 
   // Derive connector ID for persistent browser profile
   const connectorFileName = path.basename(connectorPath, path.extname(connectorPath));
@@ -668,14 +1069,27 @@ async function runConnector(runId, connectorPath, url, headless = true) {
 
   // Mutable state shared with pageApi
   const runState = {
+    runId,
     context: null,
     page: null,
     browserClosed: false,
     browserClosedByConnector: false,
     connectorCompleted: false,
     headless,
+    headedFallback,
+    url,
     userDataDir,
     browserPath: null,
+    scopes,
+    // Set once the run was stopped (by the user or the host); later results are dropped
+    stopped: false,
+    ended: false,
+    diagnosticsDir,
+    // What the connector's manifest asked for and the user approved
+    allowedDomains,
+    permissions: new Set(permissions),
+    // Recent console messages and requests, saved if the run fails
+    diagnostics: { console: [], requests: [] },
   };
 
   try {
@@ -683,7 +1097,7 @@ async function runConnector(runId, connectorPath, url, headless = true) {
     const connectorCode = fs.readFileSync(connectorPath, 'utf-8');
 
     // Resolve browser executable
-    runState.browserPath = resolveBrowserPath();
+    runState.browserPath = resolveBrowserPath(simulateNoChrome);
     log(`Using browser: ${runState.browserPath}`);
 
     // On first run, we need to:
@@ -701,7 +1115,7 @@ async function runConnector(runId, connectorPath, url, headless = true) {
     }
 
     // Launch browser with persistent context (cookies already in db on first run)
-    const context = await launchPersistentContext(userDataDir, headless, runState.browserPath);
+    const context = await launchPersistentContext(userDataDir, headless, runState.browserPath, contextOptions(runState));
     const page = context.pages()[0] || await context.newPage();
 
     runState.context = context;
@@ -714,9 +1128,9 @@ async function runConnector(runId, connectorPath, url, headless = true) {
         runState.browserClosed = true;
         runState.context = null;
         runState.page = null;
-        activeRuns.delete(runId);
+        runState.stopped = true;
         send({ type: 'status', runId, status: 'STOPPED' });
-        process.exit(0);
+        endRun(runId, runState);
       }
     });
 
@@ -731,6 +1145,7 @@ async function runConnector(runId, connectorPath, url, headless = true) {
 
     // Navigate to starting URL
     log(`Navigating to initial URL: ${url}`);
+    assertUrlAllowed(runState, url);
     await page.goto(url, { waitUntil: 'domcontentloaded' });
     log('Initial navigation complete');
     send({ type: 'status', runId, status: 'RUNNING' });
@@ -761,12 +1176,29 @@ async function runConnector(runId, connectorPath, url, headless = true) {
     const runConnectorFn = new AsyncFunction('page', modifiedCode);
 
     log('Calling connector function...');
-    const result = await runConnectorFn.call(null, pageApi);
+    let timeoutId = null;
+    const result = await (timeoutMs
+      ? Promise.race([
+          runConnectorFn.call(null, pageApi),
+          new Promise((_, reject) => {
+            timeoutId = setTimeout(
+              () => reject(new Error(`Run timed out after ${Math.round(timeoutMs / 1000)}s`)),
+              timeoutMs
+            );
+          }),
+        ]).finally(() => clearTimeout(timeoutId))
+      : runConnectorFn.call(null, pageApi));
     log('Connector function completed with result:', result ? 'has result' : 'undefined');
 
+    if (runState.stopped) {
+      log(`Run ${runId} was stopped, discarding its result`);
+      endRun(runId, runState);
+      return;
+    }
+
     // Unwrap the data if connector returns { success: true, data: ... }
     const exportData = (result && result.success && result.data) ? result.data : result;
-    send({ type: 'result', runId, data: exportData });
+    sendResult(runId, exportData);
     send({ type: 'status', runId, status: 'COMPLETE' });
 
     // Mark as completed to prevent disconnect handler from sending STOPPED
@@ -782,16 +1214,16 @@ async function runConnector(runId, connectorPath, url, headless = true) {
       }
     }
 
-    activeRuns.delete(runId);
-
-    // Exit process after successful completion
-    log('Connector completed successfully, exiting');
-    process.exit(0);
+    log(`Connector completed successfully for run ${runId}`);
+    endRun(runId, runState);
 
   } catch (error) {
     log(`Error in run ${runId}:`, error.message);
-    send({ type: 'error', runId, message: error.message });
-    send({ type: 'status', runId, status: 'ERROR' });
+    if (!runState.stopped) {
+      await saveFailureDiagnostics(runId, runState, error);
+      send({ type: 'error', runId, message: error.message });
+      send({ type: 'status', runId, status: 'ERROR' });
+    }
 
     // Cleanup on error
     if (runState.context && !runState.browserClosed) {
@@ -799,31 +1231,32 @@ async function runConnector(runId, connectorPath, url, headless = true) {
         await runState.context.close();
       } catch (e) {}
     }
-    activeRuns.delete(runId);
-
-    // Exit process after error
-    log('Connector failed, exiting');
-    process.exit(1);
+    endRun(runId, runState);
   }
 }
 
 // Stop a run
 async function stopRun(runId) {
+  const log = runLogger(runId);
   const run = activeRuns.get(runId);
   if (run) {
     log(`Stopping run ${runId}`);
-    if (run.runState && run.runState.context && !run.runState.browserClosed) {
-      await run.runState.context.close().catch(() => {});
-    }
-    activeRuns.delete(runId);
+    const { runState } = run;
+    if (runState.stopped) return;
+    runState.stopped = true;
     send({ type: 'status', runId, status: 'STOPPED' });
+    if (runState.context && !runState.browserClosed) {
+      runState.browserClosedByConnector = true;
+      await runState.context.close().catch(() => {});
+    }
+    endRun(runId, runState);
   }
 }
 
 // Main loop - read commands from stdin
 async function main() {
   log('Playwright runner started');
-  send({ type: 'ready' });
+  send({ type: 'ready', protocolVersion: PROTOCOL_VERSION, runnerVersion: RUNNER_VERSION });
 
   const rl = readline.createInterface({
     input: process.stdin,
@@ -832,16 +1265,66 @@ async function main() {
   });
 
   for await (const line of rl) {
+    if (!line.trim()) continue;
+
+    let cmd;
     try {
-      const cmd = JSON.parse(line);
+      cmd = JSON.parse(line);
+    } catch (error) {
+      log(`Error parsing command: ${error.message}`);
+      send({ type: 'protocol-error', message: `Command is not JSON: ${error.message}` });
+      continue;
+    }
+    if (!cmd || typeof cmd.type !== 'string') {
+      send({ type: 'protocol-error', message: 'Command has no type' });
+      continue;
+    }
+    if (['run', 'stop', 'prompt-answer'].includes(cmd.type) && typeof cmd.runId !== 'string') {
+      send({ type: 'protocol-error', message: `'${cmd.type}' command has no runId` });
+      continue;
+    }
 
+    try {
       switch (cmd.type) {
+        case 'hello':
+          if (cmd.protocolVersion !== PROTOCOL_VERSION) {
+            const message = `App speaks protocol v${cmd.protocolVersion}, runner speaks v${PROTOCOL_VERSION}`;
+            log(message);
+            send({ type: 'protocol-error', message });
+            process.exit(1);
+          }
+          break;
+
         case 'run':
-          runConnector(cmd.runId, cmd.connectorPath, cmd.url, cmd.headless !== false);
+          if (typeof cmd.connectorPath !== 'string' || typeof cmd.url !== 'string') {
+            send({ type: 'protocol-error', runId: cmd.runId, message: "'run' command needs connectorPath and url" });
+            send({ type: 'done', runId: cmd.runId });
+            break;
+          }
+          runConnector(cmd.runId, cmd.connectorPath, cmd.url, cmd.headless !== false, {
+            scopes: Array.isArray(cmd.scopes) ? cmd.scopes : null,
+            timeoutMs: typeof cmd.timeoutMs === 'number' ? cmd.timeoutMs : null,
+            simulateNoChrome: cmd.simulateNoChrome === true,
+            headedFallback: cmd.headedFallback !== false,
+            diagnosticsDir: typeof cmd.diagnosticsDir === 'string' ? cmd.diagnosticsDir : null,
+            allowedDomains: Array.isArray(cmd.allowedDomains) ? cmd.allowedDomains : [],
+            permissions: Array.isArray(cmd.permissions) ? cmd.permissions : [],
+          });
           break;
 
         case 'stop':
-          await stopRun(cmd.runId);
+          // Not awaited: a browser that hangs on close must not stop the runner answering pings
+          stopRun(cmd.runId).catch((error) => {
+            send({ type: 'protocol-error', runId: cmd.runId, message: error.message });
+          });
+          break;
+
+        case 'prompt-answer':
+          answerPrompt(cmd);
+          break;
+
+        case 'ping':
+          send({ type: 'pong', requestId: cmd.requestId, activeRuns: activeRuns.size });
           break;
 
         case 'quit':
@@ -859,6 +1342,7 @@ async function main() {
           const os = require('os');
           send({
             type: 'test-result',
+            requestId: cmd.requestId,
             data: {
               nodejs: process.version,
               platform: process.platform,
@@ -873,13 +1357,25 @@ async function main() {
 
         default:
           log(`Unknown command: ${cmd.type}`);
+          send({
+            type: 'protocol-error',
+            runId: cmd.runId,
+            requestId: cmd.requestId,
+            message: `Unknown command: ${cmd.type}`
+          });
       }
     } catch (error) {
-      log(`Error parsing command: ${error.message}`);
+      log(`Error handling ${cmd.type} command: ${error.message}`);
+      send({ type: 'protocol-error', runId: cmd.runId, requestId: cmd.requestId, message: error.message });
     }
   }
 }
 
+// One misbehaving connector must not take down the other runs sharing this process
+process.on('unhandledRejection', (reason) => {
+  log('Unhandled rejection:', reason && reason.message ? reason.message : reason);
+});
+
 main().catch(err => {
   log('Fatal error:', err);
   process.exit(1);
@@ -0,0 +1,543 @@
+use dirs::home_dir;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+
+use super::file_ops::{lock_file, write_file_atomic_locked};
+use super::manifest::ConnectorGrant;
+use super::redact::{set_redaction_patterns, Redactor};
+use super::signing::parse_public_key;
+
+/// Current on-disk config schema version. Bump together with a new entry in `MIGRATIONS`.
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Migration steps; `MIGRATIONS[n]` upgrades a raw config object from version `n` to `n + 1`.
+const MIGRATIONS: [fn(&mut serde_json::Map<String, serde_json::Value>); CONFIG_VERSION as usize] =
+    [migrate_v0_to_v1];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageProvider {
+    #[default]
+    Local,
+    Vana,
+    Gdrive,
+    Dropbox,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ServerMode {
+    #[default]
+    Cloud,
+    SelfHosted,
+}
+
+/// Values accepted for a connector's `exportFrequency` override
+pub const EXPORT_FREQUENCIES: [&str; 4] = ["daily", "weekly", "monthly", "manual"];
+
+/// Per-connector preferences, keyed by connector (platform) id in `AppConfig.connectors`.
+/// Unset fields fall back to the connector's own metadata / app defaults.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConnectorSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    /// Run the browser headless (default) or visible
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub headless: Option<bool>,
+    /// Scopes to collect; `None` means everything the connector offers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scopes: Option<Vec<String>>,
+    /// Maximum duration of a run before it is stopped as timed out
+    #[serde(rename = "timeoutSecs", skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    /// Stop a run that sends no status/log/data for this long (not counting time spent waiting on the user)
+    #[serde(rename = "stallTimeoutSecs", skip_serializing_if = "Option::is_none")]
+    pub stall_timeout_secs: Option<u64>,
+    /// Overrides the connector's `exportFrequency` (one of `EXPORT_FREQUENCIES`)
+    #[serde(rename = "exportFrequency", skip_serializing_if = "Option::is_none")]
+    pub export_frequency: Option<String>,
+    /// Domains and capabilities the user approved for this (user-installed) connector
+    #[serde(rename = "approvedPermissions", skip_serializing_if = "Option::is_none")]
+    pub approved_permissions: Option<ConnectorGrant>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ConnectorSettings {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.unwrap_or(true)
+    }
+
+    fn validate(&self, connector_id: &str) -> Result<(), String> {
+        if self.timeout_secs == Some(0) {
+            return Err(format!("Connector {}: timeoutSecs must be greater than 0", connector_id));
+        }
+        if self.stall_timeout_secs == Some(0) {
+            return Err(format!("Connector {}: stallTimeoutSecs must be greater than 0", connector_id));
+        }
+        if let Some(frequency) = &self.export_frequency {
+            if !EXPORT_FREQUENCIES.contains(&frequency.as_str()) {
+                return Err(format!(
+                    "Connector {}: exportFrequency must be one of {:?}, got {:?}",
+                    connector_id, EXPORT_FREQUENCIES, frequency
+                ));
+            }
+        }
+        if let Some(scopes) = &self.scopes {
+            if scopes.iter().any(|s| s.trim().is_empty()) {
+                return Err(format!("Connector {}: scopes must not be empty strings", connector_id));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Global policy for scheduled background runs (`AppConfig.scheduledRuns`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScheduledRunsPolicy {
+    /// Scheduled runs are on unless explicitly disabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    /// Only start runs once the user has been inactive for `idleMinutes`
+    #[serde(rename = "onlyWhenIdle", skip_serializing_if = "Option::is_none")]
+    pub only_when_idle: Option<bool>,
+    #[serde(rename = "idleMinutes", skip_serializing_if = "Option::is_none")]
+    pub idle_minutes: Option<u64>,
+    /// Skip runs while the machine is on battery
+    #[serde(rename = "onlyOnAcPower", skip_serializing_if = "Option::is_none")]
+    pub only_on_ac_power: Option<bool>,
+}
+
+/// A connector registry to install from (`AppConfig.registries`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegistrySource {
+    /// URL of the registry JSON: http(s), or file:// for a local registry
+    pub url: String,
+    /// Minisign public key the registry and its packages are signed with;
+    /// defaults to the publisher keys pinned in the app
+    #[serde(rename = "trustKey", skip_serializing_if = "Option::is_none")]
+    pub trust_key: Option<String>,
+    /// When registries offer the same connector, the highest priority wins
+    #[serde(default)]
+    pub priority: i32,
+}
+
+impl RegistrySource {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            trust_key: None,
+            priority: 0,
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let parsed = reqwest::Url::parse(&self.url)
+            .map_err(|e| format!("Invalid registry URL {:?}: {}", self.url, e))?;
+        if !["http", "https", "file"].contains(&parsed.scheme()) {
+            return Err(format!(
+                "Registry URL must use http, https or file, got {:?}",
+                parsed.scheme()
+            ));
+        }
+        if let Some(key) = &self.trust_key {
+            parse_public_key(key).map_err(|e| format!("Registry {}: {}", self.url, e))?;
+        }
+        Ok(())
+    }
+}
+
+/// App configuration structure
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppConfig {
+    #[serde(default)]
+    pub version: u32,
+    #[serde(rename = "storageProvider", default)]
+    pub storage_provider: StorageProvider,
+    #[serde(rename = "serverMode", default)]
+    pub server_mode: ServerMode,
+    #[serde(rename = "selfHostedUrl", skip_serializing_if = "Option::is_none")]
+    pub self_hosted_url: Option<String>,
+    /// Keep export content on disk after syncing instead of archiving it
+    #[serde(rename = "keepSyncedContent", skip_serializing_if = "Option::is_none")]
+    pub keep_synced_content: Option<bool>,
+    /// Per-connector settings keyed by connector id
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub connectors: BTreeMap<String, ConnectorSettings>,
+    #[serde(rename = "scheduledRuns", default)]
+    pub scheduled_runs: ScheduledRunsPolicy,
+    /// Playwright runs allowed at once; further runs wait in the run queue
+    #[serde(rename = "maxConcurrentRuns", skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_runs: Option<u32>,
+    /// Extra regexes masked in the log file, on top of the built-in secret and PII patterns
+    #[serde(rename = "logRedactionPatterns", default, skip_serializing_if = "Vec::is_empty")]
+    pub log_redaction_patterns: Vec<String>,
+    /// Registries connectors are installed from; empty means the default registry
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub registries: Vec<RegistrySource>,
+    /// Fields this version doesn't know about (written by a newer app or by hand),
+    /// kept so saving the config doesn't drop them
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            storage_provider: StorageProvider::Local,
+            server_mode: ServerMode::Cloud,
+            self_hosted_url: None,
+            keep_synced_content: None,
+            connectors: BTreeMap::new(),
+            scheduled_runs: ScheduledRunsPolicy::default(),
+            max_concurrent_runs: None,
+            log_redaction_patterns: Vec::new(),
+            registries: Vec::new(),
+            extra: serde_json::Map::new(),
+        }
+    }
+}
+
+impl AppConfig {
+    /// Reject configs that would leave the app unable to reach its server
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(url) = &self.self_hosted_url {
+            validate_server_url(url)?;
+        }
+        if self.server_mode == ServerMode::SelfHosted && self.self_hosted_url.is_none() {
+            return Err("Self-hosted server mode requires selfHostedUrl".to_string());
+        }
+        if self.max_concurrent_runs == Some(0) {
+            return Err("maxConcurrentRuns must be at least 1".to_string());
+        }
+        for (id, settings) in &self.connectors {
+            settings.validate(id)?;
+        }
+        Redactor::new(&self.log_redaction_patterns)?;
+        for (index, registry) in self.registries.iter().enumerate() {
+            registry.validate()?;
+            if self.registries[..index].iter().any(|r| r.url == registry.url) {
+                return Err(format!("Registry {} is listed more than once", registry.url));
+            }
+        }
+        Ok(())
+    }
+
+    /// Settings for a connector, or defaults if none are stored
+    pub fn connector_settings(&self, connector_id: &str) -> ConnectorSettings {
+        self.connectors.get(connector_id).cloned().unwrap_or_default()
+    }
+}
+
+/// A self-hosted server URL must be absolute http(s) with a host
+fn validate_server_url(url: &str) -> Result<(), String> {
+    let parsed = reqwest::Url::parse(url)
+        .map_err(|e| format!("Invalid selfHostedUrl {:?}: {}", url, e))?;
+    if parsed.scheme() != "http" && parsed.scheme() != "https" {
+        return Err(format!("selfHostedUrl must use http or https, got {:?}", parsed.scheme()));
+    }
+    if parsed.host_str().map_or(true, |h| h.is_empty()) {
+        return Err(format!("selfHostedUrl has no host: {:?}", url));
+    }
+    Ok(())
+}
+
+/// v0 configs had no `version` and stored modes as free-form strings.
+/// Normalise their spelling and drop empty URLs.
+fn migrate_v0_to_v1(obj: &mut serde_json::Map<String, serde_json::Value>) {
+    for key in ["storageProvider", "serverMode"] {
+        if let Some(serde_json::Value::String(value)) = obj.get_mut(key) {
+            let normalized = value.trim().to_lowercase().replace('_', "-");
+            *value = if normalized == "selfhosted" { "self-hosted".to_string() } else { normalized };
+        }
+    }
+    if obj
+        .get("selfHostedUrl")
+        .and_then(|v| v.as_str())
+        .is_some_and(|url| url.trim().is_empty())
+    {
+        obj.remove("selfHostedUrl");
+    }
+}
+
+/// Run pending migrations on a raw config object. Returns true if anything was upgraded.
+fn migrate_config(value: &mut serde_json::Value) -> Result<bool, String> {
+    let obj = value
+        .as_object_mut()
+        .ok_or_else(|| "Config file is not a JSON object".to_string())?;
+
+    let from = obj.get("version").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+    if from > CONFIG_VERSION {
+        log::warn!(
+            "Config version {} is newer than supported version {}; loading best-effort",
+            from,
+            CONFIG_VERSION
+        );
+        return Ok(false);
+    }
+
+    for (version, migrate) in MIGRATIONS.iter().enumerate().skip(from as usize) {
+        log::info!("Migrating app config from version {} to {}", version, version + 1);
+        migrate(obj);
+    }
+    obj.insert("version".to_string(), serde_json::Value::from(CONFIG_VERSION.max(from)));
+    Ok(from < CONFIG_VERSION)
+}
+
+/// Parse a raw config, falling back to defaults for unrecognised mode values
+/// rather than failing the whole load.
+fn parse_config(mut value: serde_json::Value) -> Result<AppConfig, String> {
+    if let Some(obj) = value.as_object_mut() {
+        let invalid = |key: &str, v: &serde_json::Value| match key {
+            "storageProvider" => serde_json::from_value::<StorageProvider>(v.clone()).is_err(),
+            "serverMode" => serde_json::from_value::<ServerMode>(v.clone()).is_err(),
+            _ => false,
+        };
+        for key in ["storageProvider", "serverMode"] {
+            if obj.get(key).is_some_and(|v| invalid(key, v)) {
+                log::warn!("Ignoring invalid {} in config: {}", key, obj[key]);
+                obj.remove(key);
+            }
+        }
+    }
+    serde_json::from_value(value).map_err(|e| format!("Failed to parse config file: {}", e))
+}
+
+/// Get the path to the DataConnect config file (~/.dataconnect/config.json)
+fn get_config_path() -> Result<PathBuf, String> {
+    let home = home_dir().ok_or("Failed to get home directory")?;
+    let config_dir = home.join(".dataconnect");
+    Ok(config_dir.join("config.json"))
+}
+
+/// Get app configuration from ~/.dataconnect/config.json
+#[tauri::command]
+pub async fn get_app_config() -> Result<AppConfig, String> {
+    load_app_config()
+}
+
+/// Read the app config from disk, falling back to defaults when no file exists.
+/// Older configs are migrated and written back.
+pub(crate) fn load_app_config() -> Result<AppConfig, String> {
+    let config_path = get_config_path()?;
+
+    if !config_path.exists() {
+        // Return default config if file doesn't exist
+        return Ok(AppConfig::default());
+    }
+
+    let _lock = lock_file(&config_path)?;
+    let (config, migrated) = read_app_config_locked(&config_path)?;
+    if migrated {
+        let json = serde_json::to_string_pretty(&config)
+            .map_err(|e| format!("Failed to serialize config: {}", e))?;
+        write_file_atomic_locked(&config_path, json.as_bytes())?;
+        log::info!("Migrated app config to version {}", config.version);
+    }
+    Ok(config)
+}
+
+/// Parse and migrate the config file. The caller must hold `lock_file(config_path)`.
+/// Returns the config and whether it was migrated.
+fn read_app_config_locked(config_path: &Path) -> Result<(AppConfig, bool), String> {
+    if !config_path.exists() {
+        return Ok((AppConfig::default(), false));
+    }
+    let content = fs::read_to_string(config_path)
+        .map_err(|e| format!("Failed to read config file: {}", e))?;
+    let mut value: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse config file: {}", e))?;
+
+    let migrated = migrate_config(&mut value)?;
+    let config = parse_config(value)?;
+    if let Err(e) = config.validate() {
+        log::warn!("App config on disk is invalid: {}", e);
+    }
+    Ok((config, migrated))
+}
+
+/// Settings for one connector, falling back to defaults if the config can't be read
+pub(crate) fn load_connector_settings(connector_id: &str) -> ConnectorSettings {
+    match load_app_config() {
+        Ok(config) => config.connector_settings(connector_id),
+        Err(e) => {
+            log::warn!("Failed to load app config, using default connector settings: {}", e);
+            ConnectorSettings::default()
+        }
+    }
+}
+
+/// Update fields of the app config in ~/.dataconnect/config.json and notify listeners via
+/// `config-changed`. Top-level fields present in `patch` replace the stored ones, a `null`
+/// resets a field to its default, and omitted fields are left as they are.
+#[tauri::command]
+pub async fn set_app_config(
+    app: AppHandle,
+    patch: serde_json::Map<String, serde_json::Value>,
+) -> Result<(), String> {
+    update_app_config(&app, |config| apply_config_patch(config, patch))
+}
+
+/// Read, modify and write the app config as one step under the config lock, so concurrent
+/// updates (settings, permission approvals) don't overwrite each other.
+pub(crate) fn update_app_config<T>(
+    app: &AppHandle,
+    update: impl FnOnce(&mut AppConfig) -> Result<T, String>,
+) -> Result<T, String> {
+    let config_path = get_config_path()?;
+    // Also creates the .dataconnect directory
+    let _lock = lock_file(&config_path)?;
+
+    let (mut config, _) = read_app_config_locked(&config_path)?;
+    let result = update(&mut config)?;
+    config.validate()?;
+    config.version = config.version.max(CONFIG_VERSION);
+
+    let json = serde_json::to_string_pretty(&config)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+
+    write_file_atomic_locked(&config_path, json.as_bytes())?;
+
+    log::info!("App config saved to: {:?}", config_path);
+    set_redaction_patterns(&config.log_redaction_patterns)?;
+    let _ = app.emit("config-changed", &config);
+    Ok(result)
+}
+
+/// Merge a partial config from the frontend into `config`. The schema version is never
+/// taken from the patch.
+fn apply_config_patch(
+    config: &mut AppConfig,
+    patch: serde_json::Map<String, serde_json::Value>,
+) -> Result<(), String> {
+    let mut value = serde_json::to_value(&*config)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    let obj = value
+        .as_object_mut()
+        .ok_or_else(|| "Config is not a JSON object".to_string())?;
+    for (key, field) in patch {
+        if key == "version" {
+            continue;
+        }
+        if field.is_null() {
+            obj.remove(&key);
+        } else {
+            obj.insert(key, field);
+        }
+    }
+    *config = serde_json::from_value(value).map_err(|e| format!("Invalid config update: {}", e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrates_v0_config_and_preserves_unknown_fields() {
+        let mut raw = serde_json::json!({
+            "storageProvider": "Local",
+            "serverMode": "self_hosted",
+            "selfHostedUrl": "https://server.example.com",
+            "futureSetting": { "enabled": true }
+        });
+
+        assert!(migrate_config(&mut raw).expect("migration should succeed"));
+        let config = parse_config(raw).expect("migrated config should parse");
+
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.storage_provider, StorageProvider::Local);
+        assert_eq!(config.server_mode, ServerMode::SelfHosted);
+        assert_eq!(config.extra["futureSetting"], serde_json::json!({ "enabled": true }));
+
+        let saved = serde_json::to_value(&config).expect("config should serialize");
+        assert_eq!(saved["serverMode"], "self-hosted");
+        assert_eq!(saved["futureSetting"]["enabled"], true);
+    }
+
+    #[test]
+    fn config_patch_only_replaces_given_fields() {
+        let mut config = AppConfig {
+            keep_synced_content: Some(true),
+            max_concurrent_runs: Some(2),
+            ..AppConfig::default()
+        };
+        config.connectors.insert("chatgpt-playwright".to_string(), ConnectorSettings::default());
+
+        let patch = serde_json::json!({
+            "serverMode": "self-hosted",
+            "selfHostedUrl": "http://localhost:8080",
+            "maxConcurrentRuns": null,
+            "version": 0
+        });
+        apply_config_patch(&mut config, patch.as_object().unwrap().clone()).expect("patch should apply");
+
+        assert_eq!(config.server_mode, ServerMode::SelfHosted);
+        assert_eq!(config.max_concurrent_runs, None);
+        assert_eq!(config.keep_synced_content, Some(true));
+        assert!(config.connectors.contains_key("chatgpt-playwright"));
+        assert_eq!(config.version, CONFIG_VERSION);
+
+        let bad = serde_json::json!({ "maxConcurrentRuns": "two" });
+        assert!(apply_config_patch(&mut config, bad.as_object().unwrap().clone()).is_err());
+    }
+
+    #[test]
+    fn parse_config_falls_back_on_invalid_modes() {
+        let config = parse_config(serde_json::json!({ "version": 1, "serverMode": "clowd" }))
+            .expect("invalid mode should not fail the load");
+        assert_eq!(config.server_mode, ServerMode::Cloud);
+    }
+
+    #[test]
+    fn validate_rejects_bad_self_hosted_urls() {
+        let mut config = AppConfig {
+            server_mode: ServerMode::SelfHosted,
+            ..AppConfig::default()
+        };
+        assert!(config.validate().is_err());
+
+        config.self_hosted_url = Some("ftp://server.example.com".to_string());
+        assert!(config.validate().is_err());
+
+        config.self_hosted_url = Some("not a url".to_string());
+        assert!(config.validate().is_err());
+
+        config.self_hosted_url = Some("http://localhost:8080".to_string());
+        assert!(config.validate().is_ok());
+
+        config.connectors.insert(
+            "chatgpt-playwright".to_string(),
+            ConnectorSettings {
+                export_frequency: Some("hourly".to_string()),
+                ..ConnectorSettings::default()
+            },
+        );
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn validate_checks_registries() {
+        let mut config = AppConfig::default();
+        config.registries.push(RegistrySource::new("file:///tmp/registry/registry.json"));
+        config.registries.push(RegistrySource {
+            trust_key: Some("RWS8g44f99AbBvdTxpB+200eDRTZWTH+3CvsL3mbyd7niBWRoeuTRY32".to_string()),
+            priority: 10,
+            ..RegistrySource::new("http://localhost:8080/registry.json")
+        });
+        assert!(config.validate().is_ok());
+
+        config.registries[1].trust_key = Some("not a key".to_string());
+        assert!(config.validate().is_err());
+
+        config.registries[1] = RegistrySource::new("file:///tmp/registry/registry.json");
+        assert!(config.validate().is_err());
+
+        config.registries[1] = RegistrySource::new("ftp://registry.example.com/registry.json");
+        assert!(config.validate().is_err());
+    }
+}
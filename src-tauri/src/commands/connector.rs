@@ -2,9 +2,24 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
 
+use super::config::{load_app_config, load_connector_settings, update_app_config, AppConfig, ConnectorSettings};
+use super::diagnostics::run_diagnostics_dir;
+use super::file_ops::export_run_dir;
+use super::manifest::{
+    domain_allowed, parse_manifest, ConnectorGrant, ConnectorManifest, ConnectorPermission, ConnectorRuntime,
+    ConnectorScope,
+};
+use super::result_transfer::{
+    abort_result_transfer, append_result_chunk, emit_export_complete, persist_transferred_result,
+};
+use super::run_log::{emit_connector_log, register_run_log, RunLogLevel};
+use super::run_queue::{acquire_run_slot, cancel_queued_run};
+use super::runner::{runner_request, start_runner_run, stop_runner_run, RunContext, RunLimits};
+use super::runner_protocol::{RunCommand, RunnerCommand, RunnerMessage};
+
 // Chromium download constants
 const CHROMIUM_REVISION: &str = "1200";
 const CHROMIUM_CDN_MIRRORS: &[&str] = &[
@@ -12,23 +27,6 @@ const CHROMIUM_CDN_MIRRORS: &[&str] = &[
     "https://playwright.download.prss.microsoft.com/dbazure/download/playwright",
 ];
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct ConnectorMetadata {
-    pub id: Option<String>,
-    pub name: String,
-    pub company: Option<String>,
-    pub description: String,
-    #[serde(rename = "connectURL")]
-    pub connect_url: String,
-    #[serde(rename = "connectSelector")]
-    pub connect_selector: String,
-    #[serde(rename = "exportFrequency")]
-    pub export_frequency: Option<String>,
-    pub vectorize_config: Option<serde_json::Value>,
-    /// Runtime type: "vanilla" (default) or "network-capture" (uses network interception)
-    pub runtime: Option<String>,
-}
-
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Platform {
     pub id: String,
@@ -49,8 +47,39 @@ pub struct Platform {
     #[serde(rename = "exportFrequency")]
     pub export_frequency: Option<String>,
     pub vectorize_config: Option<serde_json::Value>,
-    /// Runtime type: "vanilla" (default) or "network-capture" (uses network interception)
-    pub runtime: Option<String>,
+    /// How the connector runs; `None` means vanilla
+    pub runtime: Option<ConnectorRuntime>,
+    /// Script file, relative to the connector's company directory
+    pub entry: String,
+    #[serde(rename = "manifestVersion")]
+    pub manifest_version: u32,
+    pub scopes: Vec<ConnectorScope>,
+    /// Capabilities the connector asks for (see `ConnectorManifest::requested_grant`)
+    pub permissions: Vec<ConnectorPermission>,
+    /// Hosts the connector may open and fetch from
+    #[serde(rename = "allowedDomains")]
+    pub allowed_domains: Vec<String>,
+    /// Hosts Playwright runs are confined to; empty unless the manifest lists `allowedDomains`
+    #[serde(rename = "navigationDomains", default)]
+    pub navigation_domains: Vec<String>,
+    #[serde(rename = "minAppVersion")]
+    pub min_app_version: Option<String>,
+    pub source: ConnectorSource,
+    /// What a user-installed connector asks for that the user hasn't approved yet;
+    /// the connector can't run until they do
+    #[serde(rename = "pendingPermissions")]
+    pub pending_permissions: Option<ConnectorGrant>,
+    /// False when the user disabled this connector in settings
+    pub enabled: bool,
+    /// User overrides from `AppConfig.connectors`, if any
+    pub settings: Option<ConnectorSettings>,
+}
+
+impl Platform {
+    /// The domains and capabilities this connector runs with
+    pub fn grant(&self) -> ConnectorGrant {
+        ConnectorGrant::new(self.allowed_domains.clone(), self.permissions.clone())
+    }
 }
 
 /// Get the user connectors directory (~/.dataconnect/connectors/)
@@ -68,7 +97,7 @@ fn get_user_connectors_dir() -> Option<PathBuf> {
 }
 
 /// Get the bundled connectors directory path
-fn get_connectors_dir(app: &AppHandle) -> PathBuf {
+pub(crate) fn get_connectors_dir(app: &AppHandle) -> PathBuf {
     // In development, look in the project root directory
     // The project root is 3 levels up from src-tauri/target/debug/
 
@@ -177,13 +206,84 @@ pub async fn debug_connector_paths(app: AppHandle) -> Result<serde_json::Value,
     Ok(serde_json::Value::Object(results))
 }
 
-/// Load platforms from a single directory
-fn load_platforms_from_dir(dir: &PathBuf) -> Vec<Platform> {
+/// Which connectors directory a manifest was loaded from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConnectorSource {
+    /// Shipped with the app
+    Bundled,
+    /// Installed in ~/.dataconnect/connectors
+    User,
+}
+
+/// A connector manifest that was skipped because it couldn't be read or failed validation
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConnectorLoadError {
+    pub path: String,
+    pub error: String,
+    pub source: ConnectorSource,
+}
+
+/// Everything `get_platforms` found: the loaded connectors and the manifests that failed
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PlatformsLoad {
+    pub platforms: Vec<Platform>,
+    pub diagnostics: Vec<ConnectorLoadError>,
+}
+
+/// Build a platform from a validated manifest at `path` (`<dir>/<company>/<filename>.json`)
+fn platform_from_manifest(manifest: ConnectorManifest, path: &Path, source: ConnectorSource) -> Platform {
+    let filename = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    // Get company from parent directory
+    let company = path
+        .parent()
+        .and_then(|p| p.file_name())
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "Unknown".to_string());
+    let entry = manifest.entry_or_default(&filename);
+    let grant = manifest.requested_grant();
+    let navigation_domains = manifest.navigation_domains();
+
+    Platform {
+        id: manifest.platform_id(&filename),
+        company: manifest.company.unwrap_or(company),
+        name: manifest.name,
+        filename: filename.clone(),
+        description: manifest.description,
+        is_updated: false,
+        logo_url: filename,
+        needs_connection: true,
+        connect_url: Some(manifest.connect_url),
+        connect_selector: Some(manifest.connect_selector),
+        export_frequency: manifest.export_frequency,
+        vectorize_config: manifest.vectorize_config,
+        runtime: manifest.runtime,
+        entry,
+        manifest_version: manifest.manifest_version,
+        scopes: manifest.scopes,
+        permissions: grant.permissions,
+        allowed_domains: grant.allowed_domains,
+        navigation_domains,
+        min_app_version: manifest.min_app_version,
+        source,
+        pending_permissions: None,
+        enabled: true,
+        settings: None,
+    }
+}
+
+/// Load platforms from a single directory, along with the manifests that failed to load
+fn load_platforms_from_dir(
+    dir: &PathBuf,
+    source: ConnectorSource,
+    app_version: &semver::Version,
+) -> (Vec<Platform>, Vec<ConnectorLoadError>) {
     let mut platforms = Vec::new();
+    let mut errors = Vec::new();
 
     if !dir.exists() {
         log::warn!("Connectors directory not found: {:?}", dir);
-        return platforms;
+        return (platforms, errors);
     }
 
     // Walk through the connectors directory
@@ -199,7 +299,7 @@ fn load_platforms_from_dir(dir: &PathBuf) -> Vec<Platform> {
         let path = entry.path();
 
         // Look for JSON metadata files
-        if path.extension().map_or(false, |ext| ext == "json") {
+        if path.extension().is_some_and(|ext| ext == "json") {
             let filename = path.file_stem().unwrap_or_default().to_string_lossy();
 
             // Skip type definition files and schema files
@@ -216,62 +316,39 @@ fn load_platforms_from_dir(dir: &PathBuf) -> Vec<Platform> {
                 continue;
             }
 
-            // Read and parse the metadata
-            match fs::read_to_string(path) {
-                Ok(content) => {
-                    match serde_json::from_str::<ConnectorMetadata>(&content) {
-                        Ok(metadata) => {
-                            // Get company from parent directory
-                            let company = path
-                                .parent()
-                                .and_then(|p| p.file_name())
-                                .map(|n| n.to_string_lossy().to_string())
-                                .unwrap_or_else(|| "Unknown".to_string());
-
-                            platforms.push(Platform {
-                                id: metadata
-                                    .id
-                                    .unwrap_or_else(|| format!("{}-001", filename)),
-                                company: metadata.company.unwrap_or(company),
-                                name: metadata.name.clone(),
-                                filename: filename.to_string(),
-                                description: metadata.description,
-                                is_updated: false,
-                                logo_url: filename.to_string(),
-                                needs_connection: true,
-                                connect_url: Some(metadata.connect_url),
-                                connect_selector: Some(metadata.connect_selector),
-                                export_frequency: metadata.export_frequency,
-                                vectorize_config: metadata.vectorize_config,
-                                runtime: metadata.runtime,
-                            });
-                        }
-                        Err(e) => {
-                            log::error!("Failed to parse metadata {:?}: {}", path, e);
-                        }
-                    }
-                }
-                Err(e) => {
-                    log::error!("Failed to read metadata {:?}: {}", path, e);
+            // Read, parse and validate the manifest
+            let manifest = fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read manifest: {}", e))
+                .and_then(|content| parse_manifest(&content, path, app_version));
+            match manifest {
+                Ok(manifest) => platforms.push(platform_from_manifest(manifest, path, source)),
+                Err(error) => {
+                    log::error!("Skipping connector {:?}: {}", path, error);
+                    errors.push(ConnectorLoadError {
+                        path: path.to_string_lossy().to_string(),
+                        error,
+                        source,
+                    });
                 }
             }
         }
     }
 
-    platforms
+    (platforms, errors)
 }
 
 /// Load all platform connectors from both user and bundled connectors directories
 /// User connectors take precedence over bundled ones
-#[tauri::command]
-pub async fn get_platforms(app: AppHandle) -> Result<Vec<Platform>, String> {
+pub(crate) fn load_platforms(app: &AppHandle) -> PlatformsLoad {
     let mut platforms = Vec::new();
     let mut seen_ids = std::collections::HashSet::new();
+    let app_version = app.package_info().version.clone();
 
     // First, load from bundled directory
-    let bundled_dir = get_connectors_dir(&app);
+    let bundled_dir = get_connectors_dir(app);
     log::info!("Loading bundled connectors from: {:?}", bundled_dir);
-    for platform in load_platforms_from_dir(&bundled_dir) {
+    let (bundled, mut diagnostics) = load_platforms_from_dir(&bundled_dir, ConnectorSource::Bundled, &app_version);
+    for platform in bundled {
         seen_ids.insert(platform.id.clone());
         platforms.push(platform);
     }
@@ -279,7 +356,9 @@ pub async fn get_platforms(app: AppHandle) -> Result<Vec<Platform>, String> {
     // Then, load from user directory (overrides bundled)
     if let Some(user_dir) = get_user_connectors_dir() {
         log::info!("Loading user connectors from: {:?}", user_dir);
-        for platform in load_platforms_from_dir(&user_dir) {
+        let (user, user_errors) = load_platforms_from_dir(&user_dir, ConnectorSource::User, &app_version);
+        diagnostics.extend(user_errors);
+        for platform in user {
             if seen_ids.contains(&platform.id) {
                 // Remove the bundled version
                 platforms.retain(|p| p.id != platform.id);
@@ -289,58 +368,178 @@ pub async fn get_platforms(app: AppHandle) -> Result<Vec<Platform>, String> {
         }
     }
 
-    log::info!("Loaded {} total platforms", platforms.len());
-    Ok(platforms)
+    // Apply per-connector settings from the app config
+    let config = load_app_config().unwrap_or_else(|e| {
+        log::warn!("Failed to load connector settings: {}", e);
+        AppConfig::default()
+    });
+    for platform in platforms.iter_mut() {
+        if let Some(settings) = config.connectors.get(&platform.id) {
+            platform.enabled = settings.is_enabled();
+            if settings.export_frequency.is_some() {
+                platform.export_frequency = settings.export_frequency.clone();
+            }
+            platform.settings = Some(settings.clone());
+        }
+        // Bundled connectors ship with the app; user-installed ones need the user's approval
+        if platform.source == ConnectorSource::User {
+            let approved = platform
+                .settings
+                .as_ref()
+                .and_then(|settings| settings.approved_permissions.clone())
+                .unwrap_or_default();
+            let missing = platform.grant().not_covered_by(&approved);
+            platform.pending_permissions = (!missing.is_empty()).then_some(missing);
+        }
+    }
+
+    log::info!("Loaded {} total platforms ({} failed to load)", platforms.len(), diagnostics.len());
+    PlatformsLoad { platforms, diagnostics }
 }
 
-/// Active connector windows
+/// Load all platform connectors, with the reasons any connector manifests were skipped
+#[tauri::command]
+pub async fn get_platforms(app: AppHandle) -> Result<PlatformsLoad, String> {
+    Ok(load_platforms(&app))
+}
+
+/// The installed connector `platform_id`, ready to run with `Platform::grant`. Fails if a
+/// user-installed connector asks for anything the user hasn't approved.
+pub(crate) fn connector_for_run(app: &AppHandle, platform_id: &str) -> Result<Platform, String> {
+    let platform = load_platforms(app)
+        .platforms
+        .into_iter()
+        .find(|platform| platform.id == platform_id)
+        .ok_or_else(|| format!("Connector {} is not installed", platform_id))?;
+    if let Some(pending) = &platform.pending_permissions {
+        return Err(format!(
+            "{} needs your approval before it can run ({})",
+            platform.name,
+            pending.summary()
+        ));
+    }
+    Ok(platform)
+}
+
+/// Approve everything a user-installed connector currently asks for. Returns what was approved.
+#[tauri::command]
+pub async fn approve_connector_permissions(app: AppHandle, platform_id: String) -> Result<ConnectorGrant, String> {
+    let platform = load_platforms(&app)
+        .platforms
+        .into_iter()
+        .find(|platform| platform.id == platform_id)
+        .ok_or_else(|| format!("Connector {} is not installed", platform_id))?;
+    let grant = platform.grant();
+
+    update_app_config(&app, |config| {
+        config.connectors.entry(platform_id.clone()).or_default().approved_permissions = Some(grant.clone());
+        Ok(())
+    })?;
+    log::info!("Approved permissions for {}: {}", platform_id, grant.summary());
+    Ok(grant)
+}
+
+/// Result of checking a connector manifest with `validate_connector`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConnectorValidation {
+    /// The manifest that was checked
+    pub path: String,
+    pub valid: bool,
+    pub errors: Vec<String>,
+    /// How the app would list the connector, when the manifest parses
+    pub platform: Option<Platform>,
+}
+
+/// Check a connector manifest the way `get_platforms` would, for connector authors.
+/// `path` is the manifest (`.json`) or its entry script (`.js`, manifest next to it).
+#[tauri::command]
+pub async fn validate_connector(app: AppHandle, path: String) -> Result<ConnectorValidation, String> {
+    let mut manifest_path = PathBuf::from(&path);
+    if manifest_path.extension().is_some_and(|ext| ext == "js") {
+        manifest_path.set_extension("json");
+    }
+    let content = fs::read_to_string(&manifest_path)
+        .map_err(|e| format!("Failed to read {:?}: {}", manifest_path, e))?;
+
+    let (errors, platform) = match serde_json::from_str::<ConnectorManifest>(&content) {
+        Ok(manifest) => {
+            let errors = manifest.problems(&manifest_path, &app.package_info().version);
+            (errors, Some(platform_from_manifest(manifest, &manifest_path, ConnectorSource::User)))
+        }
+        Err(e) => (vec![format!("Invalid manifest: {}", e)], None),
+    };
+
+    Ok(ConnectorValidation {
+        path: manifest_path.to_string_lossy().to_string(),
+        valid: errors.is_empty(),
+        errors,
+        platform,
+    })
+}
+
+/// An active connector window and the run it reports for
+#[derive(Debug, Clone)]
+struct ConnectorWindow {
+    label: String,
+    platform_id: String,
+    company: String,
+    name: String,
+    /// Only pages on these hosts may report for the run
+    allowed_domains: Vec<String>,
+}
+
+/// Active connector windows by run id
 static CONNECTOR_WINDOWS: std::sync::LazyLock<
-    std::sync::Mutex<HashMap<String, String>>,
+    std::sync::Mutex<HashMap<String, ConnectorWindow>>,
 > = std::sync::LazyLock::new(|| std::sync::Mutex::new(HashMap::new()));
 
-/// Load connector script from the connectors directory
-/// Checks user directory first, then bundled directory
-fn load_connector_script(app: &AppHandle, company: &str, filename: &str) -> Option<String> {
+/// Path of a connector's script: the manifest's `entry` (`<filename>.js` by default) in the
+/// user directory if it exists there, otherwise in the bundled directory
+fn connector_script_path(app: &AppHandle, company: &str, filename: &str) -> PathBuf {
     let company_lower = company.to_lowercase();
+    let script_in = |dir: &Path| {
+        let company_dir = dir.join(&company_lower);
+        let manifest_path = company_dir.join(format!("{}.json", filename));
+        fs::read_to_string(&manifest_path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<ConnectorManifest>(&content).ok())
+            .and_then(|manifest| manifest.entry_path(&manifest_path))
+            .unwrap_or_else(|| company_dir.join(format!("{}.js", filename)))
+    };
 
     // First check user directory
     if let Some(user_dir) = get_user_connectors_dir() {
-        let js_path = user_dir.join(&company_lower).join(format!("{}.js", filename));
-        log::info!("Looking for connector script in user dir: {:?}", js_path);
-
-        if js_path.exists() {
-            match fs::read_to_string(&js_path) {
-                Ok(content) => {
-                    log::info!("Loaded connector script from user dir: {:?}", js_path);
-                    return Some(content);
-                }
-                Err(e) => {
-                    log::error!("Failed to read connector script {:?}: {}", js_path, e);
-                }
-            }
+        let user_path = script_in(&user_dir);
+        if user_path.exists() {
+            log::info!("Found connector in user directory: {:?}", user_path);
+            return user_path;
         }
     }
 
     // Fall back to bundled directory
-    let connectors_dir = get_connectors_dir(app);
-    let js_path = connectors_dir.join(&company_lower).join(format!("{}.js", filename));
-    let ts_path = connectors_dir.join(&company_lower).join(format!("{}.ts", filename));
+    let bundled_path = script_in(&get_connectors_dir(app));
+    log::info!("Looking for connector in bundled directory: {:?}", bundled_path);
+    bundled_path
+}
 
-    log::info!("Looking for connector script in bundled dir: {:?}", js_path);
+/// Load connector script from the connectors directory
+/// Checks user directory first, then bundled directory
+fn load_connector_script(app: &AppHandle, company: &str, filename: &str) -> Option<String> {
+    let js_path = connector_script_path(app, company, filename);
 
     if js_path.exists() {
         match fs::read_to_string(&js_path) {
             Ok(content) => {
-                log::info!("Loaded connector script from bundled dir: {:?}", js_path);
+                log::info!("Loaded connector script: {:?}", js_path);
                 return Some(content);
             }
             Err(e) => {
                 log::error!("Failed to read connector script {:?}: {}", js_path, e);
             }
         }
-    } else if ts_path.exists() {
+    } else if js_path.with_extension("ts").exists() {
         // TypeScript file exists but we can't run it directly
-        log::warn!("Found TypeScript connector at {:?}, but JS version is required", ts_path);
+        log::warn!("Found TypeScript connector at {:?}, but JS version is required", js_path.with_extension("ts"));
     } else {
         log::warn!("No connector script found for {}/{}", company, filename);
     }
@@ -373,150 +572,28 @@ fn load_library_script(app: &AppHandle, script_name: &str) -> Option<String> {
     None
 }
 
-/// Active Playwright processes by runId
-static PLAYWRIGHT_PROCESSES: std::sync::LazyLock<
-    std::sync::Mutex<HashMap<String, std::process::Child>>,
-> = std::sync::LazyLock::new(|| std::sync::Mutex::new(HashMap::new()));
-
-/// Kill all active Playwright processes on app exit (sync, best-effort).
-/// Uses process group kills so spawned Chrome children are also reaped.
-pub fn cleanup_playwright_processes() {
-    if let Ok(mut guard) = PLAYWRIGHT_PROCESSES.lock() {
-        if guard.is_empty() {
-            return;
-        }
-        log::info!("Cleaning up {} Playwright process(es) on app exit...", guard.len());
-        for (run_id, mut child) in guard.drain() {
-            #[cfg(unix)]
-            {
-                use crate::commands::server::kill_process_group;
-                kill_process_group(child.id(), libc::SIGTERM);
-            }
-            // Brief wait then force kill
-            let mut exited = false;
-            for _ in 0..10 {
-                if let Ok(Some(_)) = child.try_wait() {
-                    exited = true;
-                    break;
-                }
-                std::thread::sleep(std::time::Duration::from_millis(100));
-            }
-            if !exited {
-                #[cfg(unix)]
-                {
-                    use crate::commands::server::kill_process_group;
-                    kill_process_group(child.id(), libc::SIGKILL);
-                }
-                #[cfg(not(unix))]
-                {
-                    let _ = child.kill();
-                }
-                let _ = child.wait();
-            }
-            log::info!("Playwright process for run {} cleaned up", run_id);
-        }
-    }
+/// Per-run options for `start_playwright_run`, on top of the connector's settings
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PlaywrightRunOptions {
+    pub simulate_no_chrome: bool,
+    /// Overrides `ConnectorSettings.headless` for this run
+    pub headless: Option<bool>,
+    /// Reopen the browser headed when a headless run reports CONNECT_WEBSITE
+    pub headed_fallback: bool,
 }
 
-/// Get the path to the bundled Playwright runner binary (production) or None (dev)
-fn get_bundled_playwright_runner(app: &AppHandle) -> Option<(PathBuf, Option<PathBuf>)> {
-    // In production, the binary is in the resources directory
-    let resource_dir = app.path().resource_dir().ok()?;
-
-    #[cfg(target_os = "macos")]
-    let binary_name = "playwright-runner";
-    #[cfg(target_os = "windows")]
-    let binary_name = "playwright-runner.exe";
-    #[cfg(target_os = "linux")]
-    let binary_name = "playwright-runner";
-
-    log::info!("=== Looking for Playwright runner ===");
-    log::info!("Resource directory: {:?}", resource_dir);
-    log::info!("Binary name: {}", binary_name);
-
-    // List contents of resource directory for debugging
-    if let Ok(entries) = std::fs::read_dir(&resource_dir) {
-        let contents: Vec<_> = entries
-            .filter_map(|e| e.ok())
-            .map(|e| e.file_name().to_string_lossy().to_string())
-            .collect();
-        log::info!("Resource dir contents: {:?}", contents);
-    }
-
-    // Try playwright-runner/dist path (matches tauri.conf.json resources config)
-    let dist_path = resource_dir.join("playwright-runner").join("dist");
-    let dist_binary = dist_path.join(binary_name);
-    let dist_browsers = dist_path.join("browsers");
-
-    log::info!("Checking path 1: {:?} (exists: {})", dist_binary, dist_binary.exists());
-    if dist_path.exists() {
-        if let Ok(entries) = std::fs::read_dir(&dist_path) {
-            let contents: Vec<_> = entries
-                .filter_map(|e| e.ok())
-                .map(|e| e.file_name().to_string_lossy().to_string())
-                .collect();
-            log::info!("  dist path contents: {:?}", contents);
+impl Default for PlaywrightRunOptions {
+    fn default() -> Self {
+        Self {
+            simulate_no_chrome: false,
+            headless: None,
+            headed_fallback: true,
         }
     }
-
-    if dist_binary.exists() {
-        log::info!("Found bundled Playwright runner at {:?}", dist_binary);
-        let browsers = if dist_browsers.exists() {
-            Some(dist_browsers)
-        } else {
-            None
-        };
-        return Some((dist_binary, browsers));
-    }
-
-    // Try binaries/ path (alternative CI builds layout)
-    let binary_path = resource_dir.join("binaries").join(binary_name);
-    let browsers_path = resource_dir.join("binaries").join("browsers");
-
-    log::info!("Checking path 2: {:?} (exists: {})", binary_path, binary_path.exists());
-
-    if binary_path.exists() {
-        log::info!("Found bundled Playwright runner at {:?}", binary_path);
-        let browsers = if browsers_path.exists() {
-            Some(browsers_path)
-        } else {
-            None
-        };
-        return Some((binary_path, browsers));
-    }
-
-    // Try _up_/playwright-runner/dist path (local builds with "../playwright-runner/dist" resource)
-    let up_path = resource_dir.join("_up_").join("playwright-runner").join("dist");
-    let up_binary = up_path.join(binary_name);
-    let up_browsers = up_path.join("browsers");
-
-    log::info!("Checking path 3: {:?} (exists: {})", up_binary, up_binary.exists());
-    if up_path.exists() {
-        if let Ok(entries) = std::fs::read_dir(&up_path) {
-            let contents: Vec<_> = entries
-                .filter_map(|e| e.ok())
-                .map(|e| e.file_name().to_string_lossy().to_string())
-                .collect();
-            log::info!("  _up_ path contents: {:?}", contents);
-        }
-    }
-
-    if up_binary.exists() {
-        log::info!("Found bundled Playwright runner at {:?}", up_binary);
-        let browsers = if up_browsers.exists() {
-            Some(up_browsers)
-        } else {
-            None
-        };
-        return Some((up_binary, browsers));
-    }
-
-    log::error!("=== Playwright runner NOT FOUND in any location ===");
-    None
 }
 
 /// Start a connector run using Playwright sidecar
-async fn start_playwright_run(
+pub(crate) async fn start_playwright_run(
     app: AppHandle,
     run_id: String,
     platform_id: String,
@@ -524,13 +601,20 @@ async fn start_playwright_run(
     company: String,
     name: String,
     connect_url: String,
-    simulate_no_chrome: Option<bool>,
+    options: PlaywrightRunOptions,
+    settings: ConnectorSettings,
 ) -> Result<(), String> {
-    use std::io::{BufRead, BufReader, Write};
-    use std::process::{Command, Stdio};
-
     log::info!("Starting Playwright run for {} (platform: {}, company: {}, filename: {})",
         run_id, platform_id, company, filename);
+    let platform = connector_for_run(&app, &platform_id)?;
+    let grant = platform.grant();
+
+    // Phase 0: Wait for a free slot; runs of the same connector share a browser profile
+    let Some(slot) = acquire_run_slot(&app, &run_id, &platform_id, &filename).await? else {
+        return Ok(());
+    };
+    let run_dir = export_run_dir(&app, &company, &name, &run_id)?;
+    register_run_log(&run_id, &run_dir);
 
     // Phase 1: Check browser availability
     let _ = app.emit("connector-status", serde_json::json!({
@@ -539,7 +623,7 @@ async fn start_playwright_run(
         "timestamp": chrono_timestamp()
     }));
 
-    let browser_status = check_browser_available(simulate_no_chrome).await?;
+    let browser_status = check_browser_available(Some(options.simulate_no_chrome)).await?;
     if !browser_status.available {
         log::info!("No browser available, downloading Chromium...");
         let _ = app.emit("connector-status", serde_json::json!({
@@ -547,50 +631,23 @@ async fn start_playwright_run(
             "status": { "type": "STARTED", "message": "Downloading browser (~170 MB)..." },
             "timestamp": chrono_timestamp()
         }));
-        let _ = app.emit("connector-log", serde_json::json!({
-            "runId": run_id,
-            "message": "No browser found. Downloading Chromium (~170MB)...",
-            "timestamp": chrono_timestamp()
-        }));
+        emit_connector_log(&app, &run_id, RunLogLevel::Info, "No browser found. Downloading Chromium (~170MB)...");
 
         // Download using Rust (with progress events)
         download_chromium_rust(app.clone()).await?;
 
         log::info!("Chromium download complete, continuing with connector");
-        let _ = app.emit("connector-log", serde_json::json!({
-            "runId": run_id,
-            "message": "Browser download complete. Starting connector...",
-            "timestamp": chrono_timestamp()
-        }));
+        emit_connector_log(&app, &run_id, RunLogLevel::Info, "Browser download complete. Starting connector...");
     }
 
     // Phase 2: Find the connector script (check user dir first, then bundled)
-    let company_lower = company.to_lowercase();
-    let connector_path = if let Some(user_dir) = get_user_connectors_dir() {
-        let user_path = user_dir.join(&company_lower).join(format!("{}.js", filename));
-        if user_path.exists() {
-            log::info!("Found connector in user directory: {:?}", user_path);
-            user_path
-        } else {
-            let bundled_dir = get_connectors_dir(&app);
-            let bundled_path = bundled_dir.join(&company_lower).join(format!("{}.js", filename));
-            log::info!("Looking for connector in bundled directory: {:?}", bundled_path);
-            bundled_path
-        }
-    } else {
-        let connectors_dir = get_connectors_dir(&app);
-        connectors_dir.join(&company_lower).join(format!("{}.js", filename))
-    };
+    let connector_path = connector_script_path(&app, &company, &filename);
 
     if !connector_path.exists() {
         let err = format!("Connector script not found: {:?}", connector_path);
         log::error!("{}", err);
         // Emit error to UI
-        let _ = app.emit("connector-log", serde_json::json!({
-            "runId": run_id,
-            "message": format!("Error: {}", err),
-            "timestamp": chrono_timestamp()
-        }));
+        emit_connector_log(&app, &run_id, RunLogLevel::Error, &format!("Error: {}", err));
         return Err(err);
     }
 
@@ -603,103 +660,36 @@ async fn start_playwright_run(
         "timestamp": chrono_timestamp()
     }));
 
-    // In debug mode, always use node directly (avoids macOS code signing issues with copied binaries)
-    // In release mode, use the bundled binary
-    #[cfg(debug_assertions)]
-    let use_bundled_binary = false;
-    #[cfg(not(debug_assertions))]
-    let use_bundled_binary = true;
-
-    let mut child = if use_bundled_binary {
-        if let Some((binary_path, browsers_path)) = get_bundled_playwright_runner(&app) {
-            log::info!("Found bundled Playwright runner at: {:?}", binary_path);
-
-            // Production mode: use bundled binary
-            let mut cmd = Command::new(&binary_path);
-            cmd.stdin(Stdio::piped())
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped());
-
-            // Create a new process group so we can kill all children (Chrome) at once
-            #[cfg(unix)]
-            {
-                use std::os::unix::process::CommandExt;
-                cmd.process_group(0);
-            }
-
-            // Set browser path if bundled
-            if let Some(ref browsers) = browsers_path {
-                log::info!("Setting PLAYWRIGHT_BROWSERS_PATH to: {:?}", browsers);
-                cmd.env("PLAYWRIGHT_BROWSERS_PATH", browsers);
-            }
-
-            // Set simulate no chrome flag for testing
-            if simulate_no_chrome.unwrap_or(false) {
-                log::info!("Setting DATACONNECT_SIMULATE_NO_CHROME=1");
-                cmd.env("DATACONNECT_SIMULATE_NO_CHROME", "1");
-            }
-
-            log::info!("Spawning Playwright runner...");
-            match cmd.spawn() {
-                Ok(child) => child,
-                Err(e) => {
-                    let err = format!("Failed to spawn bundled Playwright runner: {}", e);
-                    log::error!("{}", err);
-                    let _ = app.emit("connector-log", serde_json::json!({
-                        "runId": run_id,
-                        "message": format!("Error: {}", err),
-                        "timestamp": chrono_timestamp()
-                    }));
-                    return Err(err);
-                }
-            }
-        } else {
-            return Err("Bundled Playwright runner not found".to_string());
-        }
-    } else {
-        // Dev mode: use node index.cjs directly (avoids code signing issues)
-        log::info!("Dev mode: using node index.cjs directly");
-        let connectors_dir = get_connectors_dir(&app);
-        let runner_dir = connectors_dir.parent()
-            .and_then(|p| Some(p.join("playwright-runner")))
-            .ok_or("Could not find playwright-runner directory")?;
-
-        if !runner_dir.exists() {
-            return Err(format!("Playwright runner not found: {:?}. Run 'npm install' in playwright-runner directory.", runner_dir));
-        }
-
-        let mut cmd = Command::new("node");
-        cmd.arg("index.cjs")
-            .current_dir(&runner_dir)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
-
-        #[cfg(unix)]
-        {
-            use std::os::unix::process::CommandExt;
-            cmd.process_group(0);
-        }
-
-        // Set simulate no chrome flag for testing
-        if simulate_no_chrome.unwrap_or(false) {
-            log::info!("Setting DATACONNECT_SIMULATE_NO_CHROME=1 (dev mode)");
-            cmd.env("DATACONNECT_SIMULATE_NO_CHROME", "1");
-        }
-
-        cmd.spawn()
-            .map_err(|e| format!("Failed to spawn Playwright runner: {}", e))?
+    let run_cmd = RunCommand {
+        run_id: run_id.clone(),
+        connector_path: connector_path.to_string_lossy().to_string(),
+        url: connect_url,
+        headless: options.headless.or(settings.headless).unwrap_or(true),
+        headed_fallback: options.headed_fallback,
+        scopes: settings.scopes.clone(),
+        timeout_ms: settings.timeout_secs.map(|secs| secs * 1000),
+        simulate_no_chrome: options.simulate_no_chrome,
+        diagnostics_dir: Some(run_diagnostics_dir(&run_dir).to_string_lossy().to_string()),
+        allowed_domains: platform.navigation_domains,
+        permissions: grant.permissions,
     };
 
-    log::info!("Playwright runner spawned successfully");
-
-    // Get handles to stdin/stdout
-    let stdin = child.stdin.take().ok_or("Failed to get stdin")?;
-    let stdout = child.stdout.take().ok_or("Failed to get stdout")?;
-    let stderr = child.stderr.take().ok_or("Failed to get stderr")?;
-
-    // Store process for cleanup
-    PLAYWRIGHT_PROCESSES.lock().unwrap().insert(run_id.clone(), child);
+    // Runs share one long-lived runner process; messages come back tagged with the runId
+    if let Err(err) = start_runner_run(
+        &app,
+        RunContext::new(
+            platform_id.clone(),
+            company.clone(),
+            name.clone(),
+            slot,
+            RunLimits::from_settings(&settings),
+        ),
+        run_cmd,
+    ) {
+        log::error!("{}", err);
+        emit_connector_log(&app, &run_id, RunLogLevel::Error, &format!("Error: {}", err));
+        return Err(err);
+    }
 
     // Emit that the run has started
     app.emit("run-started", serde_json::json!({
@@ -710,131 +700,11 @@ async fn start_playwright_run(
         "runtime": "playwright"
     })).map_err(|e| format!("Failed to emit event: {}", e))?;
 
-    // Spawn thread to read stderr (for debug logs)
-    let run_id_for_stderr = run_id.clone();
-    std::thread::spawn(move || {
-        let reader = BufReader::new(stderr);
-        for line in reader.lines().flatten() {
-            log::info!("[Playwright:{}] {}", run_id_for_stderr, line);
-        }
-    });
-
-    // Spawn thread to read stdout and forward events
-    let app_clone = app.clone();
-    let run_id_for_stdout = run_id.clone();
-    let platform_id_clone = platform_id.clone();
-    let company_clone = company.clone();
-    let name_clone = name.clone();
-
-    // Build the run command JSON before moving stdin
-    let run_cmd = serde_json::json!({
-        "type": "run",
+    let _ = app.emit("connector-status", serde_json::json!({
         "runId": run_id,
-        "connectorPath": connector_path.to_string_lossy(),
-        "url": connect_url,
-        "headless": true
-    });
-
-    std::thread::spawn(move || {
-        // Move stdin into this thread to keep it alive
-        let mut stdin = stdin;
-
-        // Send the run command
-        if let Err(e) = writeln!(stdin, "{}", run_cmd.to_string()) {
-            log::error!("Failed to send run command: {}", e);
-        }
-        // Flush stdin to ensure command is sent immediately
-        let _ = stdin.flush();
-
-        // Now read stdout in the same thread to keep stdin alive
-        let reader = BufReader::new(stdout);
-        for line in reader.lines().flatten() {
-            if let Ok(msg) = serde_json::from_str::<serde_json::Value>(&line) {
-                let msg_type = msg.get("type").and_then(|v| v.as_str()).unwrap_or("");
-
-                match msg_type {
-                    "ready" => {
-                        log::info!("Playwright runner ready for {}", run_id_for_stdout);
-                        let _ = app_clone.emit("connector-status", serde_json::json!({
-                            "runId": run_id_for_stdout,
-                            "status": { "type": "STARTED", "message": "Authorizing..." },
-                            "timestamp": chrono_timestamp()
-                        }));
-                    }
-                    "log" => {
-                        if let Some(message) = msg.get("message").and_then(|v| v.as_str()) {
-                            let _ = app_clone.emit("connector-log", serde_json::json!({
-                                "runId": run_id_for_stdout,
-                                "message": message,
-                                "timestamp": chrono_timestamp()
-                            }));
-                        }
-                    }
-                    "status" => {
-                        if let Some(status_str) = msg.get("status").and_then(|v| v.as_str()) {
-                            // Simple string status (e.g., "RUNNING", "COMPLETE")
-                            let _ = app_clone.emit("connector-status", serde_json::json!({
-                                "runId": run_id_for_stdout,
-                                "status": { "type": status_str },
-                                "timestamp": chrono_timestamp()
-                            }));
-                        } else if let Some(status_obj) = msg.get("status") {
-                            // Structured status object (e.g., { type: "COLLECTING", message, phase, count })
-                            let _ = app_clone.emit("connector-status", serde_json::json!({
-                                "runId": run_id_for_stdout,
-                                "status": status_obj,
-                                "timestamp": chrono_timestamp()
-                            }));
-                        }
-                    }
-                    "result" => {
-                        if let Some(data) = msg.get("data") {
-                            let _ = app_clone.emit("export-complete", serde_json::json!({
-                                "runId": run_id_for_stdout,
-                                "platformId": platform_id_clone,
-                                "company": company_clone,
-                                "name": name_clone,
-                                "data": data,
-                                "timestamp": chrono_timestamp()
-                            }));
-                        }
-                    }
-                    "error" => {
-                        if let Some(message) = msg.get("message").and_then(|v| v.as_str()) {
-                            let _ = app_clone.emit("connector-log", serde_json::json!({
-                                "runId": run_id_for_stdout,
-                                "message": format!("Error: {}", message),
-                                "timestamp": chrono_timestamp()
-                            }));
-                        }
-                    }
-                    "data" => {
-                        // Forward connector data events to frontend
-                        let key = msg.get("key").and_then(|v| v.as_str()).unwrap_or("");
-                        let value = msg.get("value");
-                        let _ = app_clone.emit("connector-data", serde_json::json!({
-                            "runId": run_id_for_stdout,
-                            "key": key,
-                            "value": value,
-                            "timestamp": chrono_timestamp()
-                        }));
-                    }
-                    _ => {}
-                }
-            }
-        }
-
-        // Process ended, cleanup and notify UI
-        PLAYWRIGHT_PROCESSES.lock().unwrap().remove(&run_id_for_stdout);
-        log::info!("Playwright runner ended for {}", run_id_for_stdout);
-
-        // Emit stopped status so UI updates (in case process exited without COMPLETE/ERROR)
-        let _ = app_clone.emit("connector-status", serde_json::json!({
-            "runId": run_id_for_stdout,
-            "status": { "type": "STOPPED", "message": "Process ended" },
-            "timestamp": chrono_timestamp()
-        }));
-    });
+        "status": { "type": "STARTED", "message": "Authorizing..." },
+        "timestamp": chrono_timestamp()
+    }));
 
     Ok(())
 }
@@ -849,18 +719,34 @@ pub async fn start_connector_run(
     company: String,
     name: String,
     connect_url: String,
-    runtime: Option<String>,
+    runtime: Option<ConnectorRuntime>,
     simulate_no_chrome: Option<bool>,
+    headless: Option<bool>,
 ) -> Result<(), String> {
+    let settings = load_connector_settings(&platform_id);
+    if !settings.is_enabled() {
+        return Err(format!("Connector {} is disabled in settings", platform_id));
+    }
+
     // Check if this is a Playwright runtime connector
-    if runtime.as_deref() == Some("playwright") {
+    if runtime == Some(ConnectorRuntime::Playwright) {
+        // `headless` applies to this run only; the connector's setting is the default
+        let options = PlaywrightRunOptions {
+            simulate_no_chrome: simulate_no_chrome.unwrap_or(false),
+            headless,
+            ..Default::default()
+        };
         return start_playwright_run(
-            app, run_id, platform_id, filename, company, name, connect_url, simulate_no_chrome
+            app, run_id, platform_id, filename, company, name, connect_url, options, settings
         ).await;
     }
 
     let window_label = format!("connector-{}", run_id);
-    let use_network_capture = runtime.as_deref() == Some("network-capture");
+    let use_network_capture = runtime == Some(ConnectorRuntime::NetworkCapture);
+    let grant = connector_for_run(&app, &platform_id)?.grant();
+    if use_network_capture && !grant.has(ConnectorPermission::NetworkCapture) {
+        return Err(format!("Connector {} doesn't have the network-capture permission", platform_id));
+    }
 
     // Load the connector script
     let connector_script = load_connector_script(&app, &company, &filename)
@@ -896,6 +782,33 @@ pub async fn start_connector_run(
         const __FILENAME__ = "{}";
         const __COMPANY__ = "{}";
         const __NAME__ = "{}";
+        const __SCOPES__ = {};
+
+        // Deliver a log/status/result message to the app over the connector_message command
+        const __dataconnectSend = function(message) {{
+            const invoke = window.__TAURI__ && window.__TAURI__.core && window.__TAURI__.core.invoke;
+            if (!invoke) {{
+                console.warn('[DataConnect] IPC unavailable, dropping', message.type);
+                return Promise.resolve();
+            }}
+            return invoke('connector_message', {{ runId: __RUN_ID__, message: message }})
+                .catch(function(e) {{ console.error('[DataConnect] Failed to deliver ' + message.type + ':', e); }});
+        }};
+
+        // Results go over in ordered chunks (never splitting a surrogate pair), then a commit
+        const __dataconnectSendResult = async function(result) {{
+            const json = JSON.stringify(result);
+            const chunkSize = 1024 * 1024;
+            let seq = 0;
+            for (let start = 0; start < json.length; seq++) {{
+                let end = Math.min(start + chunkSize, json.length);
+                const code = json.charCodeAt(end - 1);
+                if (end < json.length && code >= 0xd800 && code <= 0xdbff) end -= 1;
+                await __dataconnectSend({{ type: 'result-chunk', seq: seq, data: json.slice(start, end) }});
+                start = end;
+            }}
+            await __dataconnectSend({{ type: 'result-commit', chunks: seq }});
+        }};
 
         // Define log function first so other functions can reference it
         const __dataconnectLog = function(...args) {{
@@ -903,15 +816,36 @@ pub async fn start_connector_run(
                 typeof arg === 'object' ? JSON.stringify(arg) : String(arg)
             );
             console.log('[DataConnect]', ...stringArgs);
-            if (window.__TAURI__ && window.__TAURI__.event) {{
-                window.__TAURI__.event.emit('connector-log', {{
-                    runId: __RUN_ID__,
-                    message: stringArgs.join(' '),
-                    timestamp: Date.now()
-                }});
-            }}
+            __dataconnectSend({{ type: 'log', message: stringArgs.join(' ') }});
         }};
 
+        // Connectors report by assigning window.__DATACONNECT_STATUS__ / __DATACONNECT_RESULT__;
+        // each assignment is delivered right away (repeated identical statuses are skipped)
+        let __dataconnectStatus = undefined;
+        let __dataconnectStatusKey = null;
+        let __dataconnectResult = undefined;
+        Object.defineProperty(window, '__DATACONNECT_STATUS__', {{
+            configurable: true,
+            get: function() {{ return __dataconnectStatus; }},
+            set: function(status) {{
+                __dataconnectStatus = status;
+                if (!status || !status.type) return;
+                const key = status.type + '_' + (status.message || '');
+                if (key === __dataconnectStatusKey) return;
+                __dataconnectStatusKey = key;
+                __dataconnectSend({{ type: 'status', status: status }});
+            }}
+        }});
+        Object.defineProperty(window, '__DATACONNECT_RESULT__', {{
+            configurable: true,
+            get: function() {{ return __dataconnectResult; }},
+            set: function(result) {{
+                if (__dataconnectResult || !result) return;
+                __dataconnectResult = result;
+                __dataconnectSendResult(result);
+            }}
+        }});
+
         window.__DATACONNECT_API__ = {{
             log: __dataconnectLog,
             waitForElement: function(selector, elementName, multipleElements = false, timeout = 10000) {{
@@ -944,13 +878,7 @@ pub async fn start_connector_run(
             }},
             sendStatus: function(status) {{
                 __dataconnectLog('Status: ' + (typeof status === 'string' ? status : JSON.stringify(status)));
-                if (window.__TAURI__ && window.__TAURI__.event) {{
-                    window.__TAURI__.event.emit('connector-status', {{
-                        runId: __RUN_ID__,
-                        status: status,
-                        timestamp: Date.now()
-                    }});
-                }}
+                __dataconnectSend({{ type: 'status', status: status }});
             }},
             navigate: function(url) {{
                 __dataconnectLog('Navigating to: ' + url);
@@ -961,7 +889,8 @@ pub async fn start_connector_run(
                 return {{
                     id: __PLATFORM_ID__,
                     name: __NAME__,
-                    company: __COMPANY__
+                    company: __COMPANY__,
+                    scopes: __SCOPES__
                 }};
             }}
         }};
@@ -974,7 +903,31 @@ pub async fn start_connector_run(
 
         console.log('[DataConnect] API initialized for run:', __RUN_ID__);
         "#,
-        run_id, platform_id, filename, company, name
+        run_id, platform_id, filename, company, name,
+        serde_json::to_string(&settings.scopes).unwrap_or_else(|_| "null".to_string())
+    );
+
+    // Pages outside the connector's allowed domains get neither the API nor the connector;
+    // throwing here stops the rest of the initialization script
+    let domain_guard = format!(
+        r#"
+        (function() {{
+            const allowed = {};
+            const host = window.location.hostname.toLowerCase();
+            const ok = allowed.some(function(pattern) {{
+                if (pattern.indexOf('*.') === 0) {{
+                    const base = pattern.slice(2);
+                    return host === base || host.endsWith('.' + base);
+                }}
+                return host === pattern;
+            }});
+            if (!ok) {{
+                console.warn('[DataConnect] ' + host + ' is not in the connector\'s allowed domains, not running it here');
+                throw new Error('DataConnect: domain not allowed');
+            }}
+        }})();
+        "#,
+        serde_json::to_string(&grant.allowed_domains).unwrap_or_else(|_| "[]".to_string())
     );
 
     // Combine the API script with the connector script
@@ -984,6 +937,8 @@ pub async fn start_connector_run(
         r#"
         {}
 
+        {}
+
         // Network capture library (network-capture runtime only)
         {}
 
@@ -1015,22 +970,52 @@ pub async fn start_connector_run(
             }}, 2000);
         }}
         "#,
-        api_script, network_capture_script, page_api_script, connector_script, connector_script
+        domain_guard, api_script, network_capture_script, page_api_script, connector_script, connector_script
     );
 
     // Create the webview window
+    let allow_downloads = grant.has(ConnectorPermission::Downloads);
+    let run_id_for_download = run_id.clone();
     let webview = WebviewWindowBuilder::new(&app, &window_label, WebviewUrl::External(connect_url.parse().map_err(|e| format!("Invalid URL: {}", e))?))
         .title(format!("DataConnect - {}", name))
         .inner_size(1024.0, 768.0)
         .initialization_script(&full_script)
+        .on_download(move |_, event| {
+            if let tauri::webview::DownloadEvent::Requested { url, .. } = event {
+                if !allow_downloads {
+                    log::warn!("Blocked download of {} for run {} (no downloads permission)", url, run_id_for_download);
+                    return false;
+                }
+            }
+            true
+        })
         .build()
         .map_err(|e| format!("Failed to create window: {}", e))?;
 
-    // Store the window reference
+    // Register the window; connector_message only accepts messages from registered windows
+    if let Ok(run_dir) = export_run_dir(&app, &company, &name, &run_id) {
+        register_run_log(&run_id, &run_dir);
+    }
     CONNECTOR_WINDOWS
         .lock()
         .unwrap()
-        .insert(run_id.clone(), window_label.clone());
+        .insert(run_id.clone(), ConnectorWindow {
+            label: window_label.clone(),
+            platform_id: platform_id.clone(),
+            company: company.clone(),
+            name: name.clone(),
+            allowed_domains: grant.allowed_domains.clone(),
+        });
+
+    let run_id_for_close = run_id.clone();
+    webview.on_window_event(move |event| {
+        if let tauri::WindowEvent::Destroyed = event {
+            if CONNECTOR_WINDOWS.lock().unwrap().remove(&run_id_for_close).is_some() {
+                log::info!("Connector window closed for run {}", run_id_for_close);
+                abort_result_transfer(&run_id_for_close);
+            }
+        }
+    });
 
     // Emit that the run has started
     app.emit("run-started", serde_json::json!({
@@ -1041,195 +1026,126 @@ pub async fn start_connector_run(
     }))
     .map_err(|e| format!("Failed to emit event: {}", e))?;
 
-    // Start polling for results in a background task
-    let app_clone = app.clone();
-    let run_id_clone = run_id.clone();
-    let platform_id_clone = platform_id.clone();
-    let company_clone = company.clone();
-    let name_clone = name.clone();
-
-    tokio::spawn(async move {
-        poll_connector_result(
-            app_clone,
-            webview,
-            run_id_clone,
-            platform_id_clone,
-            company_clone,
-            name_clone,
-        ).await;
-    });
-
     Ok(())
 }
 
-/// Poll the webview for connector results using URL hash as communication channel
-async fn poll_connector_result(
+/// Commands a connector window may invoke. Connector windows load third-party pages,
+/// so everything else is refused for them (see the invoke handler in lib.rs).
+const CONNECTOR_WINDOW_COMMANDS: &[&str] = &["connector_message"];
+
+/// Whether the window/webview `label` may invoke `command`
+pub fn connector_window_allows(label: &str, command: &str) -> bool {
+    !label.starts_with("connector-") || CONNECTOR_WINDOW_COMMANDS.contains(&command)
+}
+
+/// A message from the connector script running in a connector window
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum ConnectorWindowMessage {
+    Log { message: String },
+    Status { status: serde_json::Value },
+    /// One piece of the JSON-serialized result, numbered from 0
+    ResultChunk { seq: u64, data: String },
+    /// All `chunks` result chunks have been sent
+    ResultCommit { chunks: u64 },
+}
+
+/// Status and result delivery from webview connectors. Only the run's own window may report
+/// for it, and nothing is accepted after its result.
+#[tauri::command]
+pub async fn connector_message(
     app: AppHandle,
-    webview: tauri::WebviewWindow,
+    webview: tauri::Webview,
     run_id: String,
-    platform_id: String,
-    company: String,
-    name: String,
-) {
-    let mut last_status_hash = String::new();
-    let max_polls = 300; // Poll for up to 5 minutes
-    let mut poll_count = 0;
+    message: ConnectorWindowMessage,
+) -> Result<(), String> {
+    let expected_label = format!("connector-{}", run_id);
+    let window = {
+        let windows = CONNECTOR_WINDOWS.lock().unwrap();
+        match windows.get(&run_id) {
+            Some(window) if webview.label() == expected_label && window.label == expected_label => window.clone(),
+            _ => {
+                log::warn!("Rejected connector message for run {} from window {}", run_id, webview.label());
+                return Err(format!("Window {} cannot report for run {}", webview.label(), run_id));
+            }
+        }
+    };
+    let host = webview.url().ok().and_then(|url| url.host_str().map(str::to_string)).unwrap_or_default();
+    if !domain_allowed(&host, &window.allowed_domains) {
+        log::warn!("Rejected connector message for run {} from {:?} (not an allowed domain)", run_id, host);
+        return Err(format!("{} is not in the connector's allowed domains", host));
+    }
 
-    log::info!("Starting polling for run {}", run_id);
+    match message {
+        ConnectorWindowMessage::Log { message } => {
+            emit_connector_log(&app, &run_id, RunLogLevel::Info, &message);
+        }
+        ConnectorWindowMessage::Status { mut status } => {
+            let status_type = status
+                .as_str()
+                .or_else(|| status.get("type").and_then(|v| v.as_str()))
+                .unwrap_or("")
+                .to_string();
+            // The export only travels as result chunks; never echo a COMPLETE payload to the UI
+            if status_type == "COMPLETE" {
+                if let Some(status) = status.as_object_mut() {
+                    status.remove("data");
+                }
+            }
+            let status_msg = status.get("message").and_then(|v| v.as_str());
+
+            log::info!("Got STATUS from webview: {} - {:?}", status_type, status_msg);
 
-    loop {
-        poll_count += 1;
-        if poll_count > max_polls {
-            log::warn!("Polling timeout for run {}", run_id);
             let _ = app.emit("connector-status", serde_json::json!({
                 "runId": run_id,
-                "status": { "type": "ERROR", "message": "Polling timeout" },
+                "status": status,
                 "timestamp": chrono_timestamp()
             }));
-            break;
-        }
 
-        // Wait before polling
-        tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
-
-        // Check if window still exists
-        if webview.is_closable().is_err() {
-            log::info!("Webview closed for run {}", run_id);
-            break;
+            // Emit log
+            if let Some(msg) = status_msg {
+                emit_connector_log(&app, &run_id, RunLogLevel::Info, msg);
+            }
         }
-
-        // Inject script to encode status/result in URL hash
-        // We use base64 encoding to handle JSON safely in URLs
-        let check_script = r#"
-            (function() {
-                try {
-                    var result = window.__DATACONNECT_RESULT__;
-                    var status = window.__DATACONNECT_STATUS__;
-
-                    if (result && !window.__DATACONNECT_HASH_SET__) {
-                        window.__DATACONNECT_HASH_SET__ = true;
-                        var encoded = btoa(unescape(encodeURIComponent(JSON.stringify({
-                            type: 'RESULT',
-                            data: result
-                        }))));
-                        window.location.hash = 'DATACONNECT_' + encoded;
-                        return;
-                    }
-
-                    if (status && status.type && !window.__DATACONNECT_HASH_SET__) {
-                        var statusKey = status.type + '_' + (status.message || '');
-                        if (window.__DATACONNECT_LAST_STATUS_KEY__ !== statusKey) {
-                            window.__DATACONNECT_LAST_STATUS_KEY__ = statusKey;
-                            var encoded = btoa(unescape(encodeURIComponent(JSON.stringify({
-                                type: 'STATUS',
-                                data: status
-                            }))));
-                            window.location.hash = 'DATACONNECT_' + encoded;
-                        }
-                    }
-                } catch(e) {
-                    console.error('[DataConnect Poll] Error:', e);
+        ConnectorWindowMessage::ResultChunk { seq, data } => {
+            let run_dir = export_run_dir(&app, &window.company, &window.name, &run_id)?;
+            append_result_chunk(&run_dir, &run_id, seq, &data)?;
+        }
+        ConnectorWindowMessage::ResultCommit { chunks } => {
+            let saved = match persist_transferred_result(
+                &app, &run_id, &window.platform_id, &window.company, &window.name, chunks,
+            ) {
+                Ok(saved) => saved,
+                Err(e) => {
+                    log::error!("{}", e);
+                    let _ = app.emit("connector-status", serde_json::json!({
+                        "runId": run_id,
+                        "status": { "type": "ERROR", "message": "Failed to save the export" },
+                        "timestamp": chrono_timestamp()
+                    }));
+                    return Err(e);
                 }
-            })();
-        "#;
-
-        let _ = webview.eval(check_script);
+            };
+            log::info!("Got RESULT from webview for run {}", run_id);
+            // The run is done; later messages from the page are refused
+            CONNECTOR_WINDOWS.lock().unwrap().remove(&run_id);
 
-        // Small delay for script to execute
-        tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+            // Emit completion status
+            let _ = app.emit("connector-status", serde_json::json!({
+                "runId": run_id,
+                "status": { "type": "COMPLETE", "message": "Export complete" },
+                "timestamp": chrono_timestamp()
+            }));
 
-        // Read the URL to check the hash
-        if let Ok(url) = webview.url() {
-            let url_str = url.to_string();
-            if let Some(hash_pos) = url_str.find("#DATACONNECT_") {
-                let hash = &url_str[hash_pos + 12..]; // Skip "#DATACONNECT_"
+            emit_export_complete(&app, &run_id, &window.platform_id, &window.company, &window.name, &saved);
 
-                // Skip if we've already processed this hash
-                if hash == last_status_hash {
-                    continue;
-                }
-                last_status_hash = hash.to_string();
-
-                // Decode base64
-                if let Ok(decoded_bytes) = base64_decode(hash) {
-                    if let Ok(decoded_str) = String::from_utf8(decoded_bytes) {
-                        if let Ok(payload) = serde_json::from_str::<serde_json::Value>(&decoded_str) {
-                            let msg_type = payload.get("type").and_then(|v| v.as_str()).unwrap_or("");
-                            let data = payload.get("data").cloned().unwrap_or(serde_json::Value::Null);
-
-                            if msg_type == "RESULT" {
-                                log::info!("Got RESULT from webview for run {}", run_id);
-
-                                // Emit completion status
-                                let total = data.get("totalConversations").and_then(|v| v.as_i64()).unwrap_or(0);
-                                let _ = app.emit("connector-status", serde_json::json!({
-                                    "runId": run_id,
-                                    "status": {
-                                        "type": "COMPLETE",
-                                        "message": format!("Exported {} conversations", total),
-                                        "data": data
-                                    },
-                                    "timestamp": chrono_timestamp()
-                                }));
-
-                                // Emit export complete
-                                let _ = app.emit("export-complete", serde_json::json!({
-                                    "runId": run_id,
-                                    "platformId": platform_id,
-                                    "company": company,
-                                    "name": name,
-                                    "data": data,
-                                    "timestamp": chrono_timestamp()
-                                }));
-
-                                log::info!("Export complete for run {}, emitted events", run_id);
-
-                                // Clear the hash
-                                let _ = webview.eval("window.location.hash = '';");
-                                break;
-                            } else if msg_type == "STATUS" {
-                                let status_type = data.get("type").and_then(|v| v.as_str()).unwrap_or("");
-                                let status_msg = data.get("message").and_then(|v| v.as_str());
-
-                                log::info!("Got STATUS from webview: {} - {:?}", status_type, status_msg);
-
-                                let _ = app.emit("connector-status", serde_json::json!({
-                                    "runId": run_id,
-                                    "status": data,
-                                    "timestamp": chrono_timestamp()
-                                }));
-
-                                // Emit log
-                                if let Some(msg) = status_msg {
-                                    let _ = app.emit("connector-log", serde_json::json!({
-                                        "runId": run_id,
-                                        "message": msg,
-                                        "timestamp": chrono_timestamp()
-                                    }));
-                                }
-
-                                // Clear the hash so we can detect next status
-                                let _ = webview.eval("window.location.hash = '';");
-                            }
-                        }
-                    }
-                }
-            }
+            log::info!("Export complete for run {}, emitted events", run_id);
         }
     }
-
-    // Remove from active windows
-    CONNECTOR_WINDOWS.lock().unwrap().remove(&run_id);
-    log::info!("Polling ended for run {}", run_id);
-}
-
-/// Decode base64 string using the base64 crate
-fn base64_decode(input: &str) -> Result<Vec<u8>, String> {
-    use base64::{Engine as _, engine::general_purpose::STANDARD};
-    STANDARD.decode(input).map_err(|e| format!("Base64 decode error: {}", e))
+    Ok(())
 }
 
-fn chrono_timestamp() -> i64 {
+pub(crate) fn chrono_timestamp() -> i64 {
     std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap()
@@ -1239,27 +1155,18 @@ fn chrono_timestamp() -> i64 {
 /// Stop a connector run by closing its webview or killing the browser process
 #[tauri::command]
 pub async fn stop_connector_run(app: AppHandle, run_id: String) -> Result<(), String> {
-    // Try to stop Playwright process first
-    if let Some(mut process) = PLAYWRIGHT_PROCESSES.lock().unwrap().remove(&run_id) {
-        log::info!("Killing Playwright process for run {}", run_id);
-        #[cfg(unix)]
-        {
-            use crate::commands::server::kill_process_group;
-            kill_process_group(process.id(), libc::SIGTERM);
-            // Brief wait for graceful shutdown, then force kill
-            for _ in 0..20 {
-                if let Ok(Some(_)) = process.try_wait() {
-                    return Ok(());
-                }
-                std::thread::sleep(std::time::Duration::from_millis(100));
-            }
-            kill_process_group(process.id(), libc::SIGKILL);
-        }
-        #[cfg(not(unix))]
-        {
-            let _ = process.kill();
-        }
-        let _ = process.wait();
+    // A run still waiting in the queue just leaves it
+    if cancel_queued_run(&app, &run_id) {
+        let _ = app.emit("connector-status", serde_json::json!({
+            "runId": run_id,
+            "status": { "type": "STOPPED", "message": "Cancelled before it started" },
+            "timestamp": chrono_timestamp()
+        }));
+        return Ok(());
+    }
+
+    // Try to stop a Playwright run first
+    if stop_runner_run(&run_id)? {
         return Ok(());
     }
 
@@ -1270,7 +1177,7 @@ pub async fn stop_connector_run(app: AppHandle, run_id: String) -> Result<(), St
         .remove(&run_id);
 
     // If we have a window label, try to close the window
-    if let Some(label) = window_label {
+    if let Some(ConnectorWindow { label, .. }) = window_label {
         if let Some(window) = app.get_webview_window(&label) {
             let _ = window.close(); // Ignore close errors
         }
@@ -1295,7 +1202,7 @@ pub async fn check_connected_platforms(
         .join("exported_data");
 
     // Build a map from platform ID to company name so we can check both paths
-    let all_platforms = get_platforms(app).await.unwrap_or_default();
+    let all_platforms = load_platforms(&app).platforms;
     let id_to_company: HashMap<String, String> = all_platforms
         .into_iter()
         .map(|p| (p.id.clone(), p.company))
@@ -1426,39 +1333,12 @@ pub async fn download_browser(app: AppHandle) -> Result<(), String> {
 /// Test that Node.js runtime is working
 #[tauri::command]
 pub async fn test_nodejs(app: AppHandle) -> Result<serde_json::Value, String> {
-    use std::io::{BufRead, BufReader, Write};
-    use std::process::{Command, Stdio};
-
-    // Get the playwright runner binary
-    let (binary_path, _) = get_bundled_playwright_runner(&app)
-        .ok_or("Playwright runner not found")?;
-
-    let mut child = Command::new(&binary_path)
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-        .map_err(|e| format!("Failed to spawn playwright runner: {}", e))?;
-
-    let mut stdin = child.stdin.take().ok_or("Failed to get stdin")?;
-    let stdout = child.stdout.take().ok_or("Failed to get stdout")?;
-
-    // Send test command
-    writeln!(stdin, r#"{{"type":"test"}}"#)
-        .map_err(|e| format!("Failed to send command: {}", e))?;
-
-    // Read response
-    let reader = BufReader::new(stdout);
-    for line in reader.lines().flatten() {
-        if let Ok(msg) = serde_json::from_str::<serde_json::Value>(&line) {
-            if msg.get("type").and_then(|v| v.as_str()) == Some("test-result") {
-                let _ = child.kill();
-                return Ok(msg.get("data").cloned().unwrap_or(serde_json::Value::Null));
-            }
-        }
+    // Reuses the long-lived runner, starting it if needed
+    let test = |request_id| RunnerCommand::Test { request_id };
+    match runner_request(&app, test, std::time::Duration::from_secs(30)).await? {
+        RunnerMessage::TestResult { data, .. } => Ok(data),
+        other => Err(format!("Unexpected reply from Playwright runner: {:?}", other)),
     }
-
-    Err("No response from Node.js runtime".to_string())
 }
 
 /// Get downloaded Chromium path from ~/.dataconnect/browsers
@@ -1850,3 +1730,42 @@ pub async fn download_chromium_rust(app: AppHandle) -> Result<String, String> {
 
     Ok(final_exe_path.to_string_lossy().to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connector_windows_only_reach_their_channel() {
+        assert!(connector_window_allows("connector-run-1", "connector_message"));
+        assert!(!connector_window_allows("connector-run-1", "write_export_data"));
+        assert!(!connector_window_allows("connector-run-1", "set_app_config"));
+        assert!(connector_window_allows("main", "write_export_data"));
+    }
+
+    #[test]
+    fn loads_valid_manifests_and_reports_broken_ones() {
+        let nanos = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_nanos();
+        let dir = std::env::temp_dir().join(format!("connectors_{}", nanos));
+        let company_dir = dir.join("openai");
+        fs::create_dir_all(&company_dir).unwrap();
+        fs::write(company_dir.join("chatgpt.js"), "").unwrap();
+        fs::write(
+            company_dir.join("chatgpt.json"),
+            r#"{"name":"ChatGPT","description":"d","connectURL":"https://chatgpt.com","connectSelector":"nav","runtime":"playwright"}"#,
+        )
+        .unwrap();
+        fs::write(company_dir.join("broken.json"), r#"{"name":"Broken""#).unwrap();
+
+        let (platforms, errors) = load_platforms_from_dir(&dir, ConnectorSource::User, &semver::Version::new(1, 0, 0));
+        assert_eq!(platforms.len(), 1);
+        assert_eq!(platforms[0].id, "chatgpt-001");
+        assert_eq!(platforms[0].company, "openai");
+        assert_eq!(platforms[0].entry, "chatgpt.js");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].path.ends_with("broken.json"));
+        assert!(errors[0].error.starts_with("Invalid manifest"));
+        assert_eq!(errors[0].source, ConnectorSource::User);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
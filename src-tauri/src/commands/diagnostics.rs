@@ -0,0 +1,241 @@
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use super::config::load_app_config;
+use super::connector::{check_browser_available, debug_connector_paths, load_platforms};
+use super::redact::redact;
+use super::run_log::RUN_LOG_FILE_NAME;
+use super::runner_protocol::RUNNER_PROTOCOL_VERSION;
+use super::server::get_personal_server_status;
+use super::updates::get_installed_connectors;
+
+/// Folder inside a run directory where the runner saves its failure bundle
+const DIAGNOSTICS_DIR_NAME: &str = "diagnostics";
+
+/// Where a failed run's screenshot, DOM snapshot, HAR and console messages are saved
+pub(crate) fn run_diagnostics_dir(run_dir: &Path) -> PathBuf {
+    run_dir.join(DIAGNOSTICS_DIR_NAME)
+}
+
+/// Find a run's directory (`exported_data/<company>/<name>/<run_id>`) from its id alone
+pub(crate) fn find_run_dir(app: &AppHandle, run_id: &str) -> Result<PathBuf, String> {
+    let exported_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?
+        .join("exported_data");
+
+    walkdir::WalkDir::new(&exported_dir)
+        .min_depth(3)
+        .max_depth(3)
+        .into_iter()
+        .flatten()
+        .find(|entry| entry.file_type().is_dir() && entry.file_name() == run_id)
+        .map(|entry| entry.into_path())
+        .ok_or_else(|| format!("No run directory found for run {}", run_id))
+}
+
+/// The app log lines mentioning a run, across all log files
+fn collect_run_log(app: &AppHandle, run_id: &str) -> String {
+    let Ok(log_dir) = app.path().app_log_dir() else {
+        return String::new();
+    };
+    let mut log_files: Vec<PathBuf> = fs::read_dir(&log_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "log"))
+                .collect()
+        })
+        .unwrap_or_default();
+    log_files.sort();
+
+    let mut lines = String::new();
+    for path in log_files {
+        let Ok(bytes) = fs::read(&path) else { continue };
+        for line in String::from_utf8_lossy(&bytes).lines().filter(|line| line.contains(run_id)) {
+            lines.push_str(&redact(line));
+            lines.push('\n');
+        }
+    }
+    lines
+}
+
+/// Redact text line by line (lossily decoding anything that isn't UTF-8)
+fn redact_text(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).lines().map(|line| redact(line) + "\n").collect()
+}
+
+/// Write `(name, contents)` entries to a new zip file
+fn write_zip(zip_path: &Path, entries: &[(String, Vec<u8>)]) -> Result<(), String> {
+    let file = File::create(zip_path).map_err(|e| format!("Failed to create {:?}: {}", zip_path, e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    for (name, contents) in entries {
+        zip.start_file(name.as_str(), options).map_err(|e| e.to_string())?;
+        zip.write_all(contents).map_err(|e| e.to_string())?;
+    }
+    zip.finish().map_err(|e| format!("Failed to write {:?}: {}", zip_path, e))?;
+    Ok(())
+}
+
+/// Write the run log and everything in the diagnostics folder to a zip, returning the entry names
+fn write_diagnostics_zip(zip_path: &Path, diagnostics_dir: &Path, run_log: &str) -> Result<Vec<String>, String> {
+    let mut entries = vec![("run.log".to_string(), run_log.as_bytes().to_vec())];
+
+    let mut files: Vec<PathBuf> = fs::read_dir(diagnostics_dir)
+        .map(|entries| entries.flatten().map(|entry| entry.path()).filter(|path| path.is_file()).collect())
+        .unwrap_or_default();
+    files.sort();
+
+    for path in files {
+        let name = format!("{}/{}", DIAGNOSTICS_DIR_NAME, path.file_name().unwrap_or_default().to_string_lossy());
+        let contents = fs::read(&path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        // Console output, error text, requests and the DOM can all carry secrets or personal data
+        let is_text = path
+            .extension()
+            .is_some_and(|ext| ext == "txt" || ext == "jsonl" || ext == "har" || ext == "html");
+        let contents = if is_text { redact_text(&contents).into_bytes() } else { contents };
+        entries.push((name, contents));
+    }
+
+    write_zip(zip_path, &entries)?;
+    Ok(entries.into_iter().map(|(name, _)| name).collect())
+}
+
+/// Zip a run's failure diagnostics with its (redacted) log lines for a bug report.
+/// Returns the path of the zip, saved in the run directory.
+#[tauri::command]
+pub async fn export_run_diagnostics(app: AppHandle, run_id: String) -> Result<String, String> {
+    let run_dir = find_run_dir(&app, &run_id)?;
+    let diagnostics_dir = run_diagnostics_dir(&run_dir);
+    // Prefer the run's own log; runs from before per-run logs only have the global one
+    let run_log_path = run_dir.join(RUN_LOG_FILE_NAME);
+    let run_log = match fs::read(&run_log_path) {
+        Ok(bytes) => redact_text(&bytes),
+        Err(_) => collect_run_log(&app, &run_id),
+    };
+    if run_log.is_empty() && !diagnostics_dir.is_dir() {
+        return Err(format!("No diagnostics recorded for run {}", run_id));
+    }
+
+    let zip_path = run_dir.join(format!("{}-diagnostics.zip", run_id));
+    let entries = write_diagnostics_zip(&zip_path, &diagnostics_dir, &run_log)?;
+    log::info!("Exported diagnostics for run {} to {:?} ({} files)", run_id, zip_path, entries.len());
+
+    Ok(zip_path.to_string_lossy().to_string())
+}
+
+/// Pretty JSON for a bundle entry, or the error that prevented collecting it
+fn json_entry<T: Serialize>(result: Result<T, String>) -> Vec<u8> {
+    let value = result
+        .and_then(|value| serde_json::to_value(value).map_err(|e| e.to_string()))
+        .unwrap_or_else(|e| serde_json::json!({ "error": e }));
+    let json = serde_json::to_string_pretty(&value).unwrap_or_default();
+    redact(&json).into_bytes()
+}
+
+/// Zip what support usually asks for: redacted logs, connector paths and versions, browser and
+/// personal server status, OS info and the app config. Returns the path of the zip, saved
+/// under `<app_data_dir>/diagnostics`.
+#[tauri::command]
+pub async fn create_diagnostics_bundle(app: AppHandle) -> Result<String, String> {
+    let system = serde_json::json!({
+        "appVersion": app.package_info().version.to_string(),
+        "tauriVersion": tauri::VERSION,
+        "os": std::env::consts::OS,
+        "osFamily": std::env::consts::FAMILY,
+        "arch": std::env::consts::ARCH,
+        "runnerProtocolVersion": RUNNER_PROTOCOL_VERSION,
+        "createdAt": chrono::Utc::now().to_rfc3339(),
+    });
+
+    let mut entries = vec![
+        ("system.json".to_string(), json_entry(Ok(system))),
+        ("config.json".to_string(), json_entry(load_app_config())),
+        ("connector-paths.json".to_string(), json_entry(debug_connector_paths(app.clone()).await)),
+        ("installed-connectors.json".to_string(), json_entry(get_installed_connectors(app.clone()).await)),
+        ("connector-load-errors.json".to_string(), json_entry(Ok(load_platforms(&app).diagnostics))),
+        ("browser-status.json".to_string(), json_entry(check_browser_available(None).await)),
+        ("personal-server-status.json".to_string(), json_entry(get_personal_server_status())),
+    ];
+
+    if let Ok(log_dir) = app.path().app_log_dir() {
+        let mut log_files: Vec<PathBuf> = fs::read_dir(&log_dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "log"))
+                    .collect()
+            })
+            .unwrap_or_default();
+        log_files.sort();
+        for path in log_files {
+            let Ok(bytes) = fs::read(&path) else { continue };
+            let name = format!("logs/{}", path.file_name().unwrap_or_default().to_string_lossy());
+            entries.push((name, redact_text(&bytes).into_bytes()));
+        }
+    }
+
+    let bundle_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?
+        .join("diagnostics");
+    fs::create_dir_all(&bundle_dir).map_err(|e| format!("Failed to create diagnostics directory: {}", e))?;
+    let zip_path = bundle_dir.join(format!(
+        "dataconnect-diagnostics-{}.zip",
+        chrono::Utc::now().format("%Y%m%d-%H%M%S")
+    ));
+    write_zip(&zip_path, &entries)?;
+    log::info!("Created diagnostics bundle at {:?} ({} files)", zip_path, entries.len());
+
+    Ok(zip_path.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn zips_log_and_diagnostics() {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        let run_dir = std::env::temp_dir().join(format!("diagnostics_zip_{}", nanos));
+        let diagnostics_dir = run_diagnostics_dir(&run_dir);
+        fs::create_dir_all(&diagnostics_dir).unwrap();
+        fs::write(diagnostics_dir.join("error.txt"), "Error: 401 with Bearer secret\n").unwrap();
+        fs::write(diagnostics_dir.join("screenshot.png"), [0x89, b'P', b'N', b'G']).unwrap();
+
+        let zip_path = run_dir.join("bundle.zip");
+        let entries = write_diagnostics_zip(&zip_path, &diagnostics_dir, "[Playwright:r1] failed\n").unwrap();
+        assert_eq!(entries, ["run.log", "diagnostics/error.txt", "diagnostics/screenshot.png"]);
+
+        let mut archive = zip::ZipArchive::new(File::open(&zip_path).unwrap()).unwrap();
+        let mut error = String::new();
+        archive.by_name("diagnostics/error.txt").unwrap().read_to_string(&mut error).unwrap();
+        assert_eq!(error, "Error: 401 with Bearer [REDACTED]\n");
+        let _ = fs::remove_dir_all(&run_dir);
+    }
+
+    #[test]
+    fn bundle_entries_are_redacted_or_report_errors() {
+        let config = String::from_utf8(json_entry(Ok(serde_json::json!({
+            "selfHostedUrl": "https://server.example.com",
+            "owner": "jane@example.com"
+        }))))
+        .unwrap();
+        assert!(config.contains("https://server.example.com"));
+        assert!(config.contains("[EMAIL]") && !config.contains("jane@"));
+
+        let failed: serde_json::Value =
+            serde_json::from_slice(&json_entry::<()>(Err("no browser".to_string()))).unwrap();
+        assert_eq!(failed, serde_json::json!({ "error": "no browser" }));
+    }
+}
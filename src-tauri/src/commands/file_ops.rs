@@ -1,9 +1,12 @@
+use fs2::FileExt;
 use serde::{Deserialize, Serialize};
-use std::fs::{self, File};
-use std::io::Read;
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 use tauri::{AppHandle, Manager};
-use dirs::home_dir;
+
+use super::config::load_app_config;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FileInfo {
@@ -35,6 +38,91 @@ pub struct SourceExportPreview {
     pub exported_at: String,
 }
 
+/// Build a sibling path named `<prefix><file name><suffix>` (e.g. `run.json` -> `.run.json.42.tmp`).
+fn sibling_path(path: &Path, prefix: &str, suffix: &str) -> PathBuf {
+    let mut name = OsString::from(prefix);
+    name.push(path.file_name().unwrap_or_default());
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// Exclusive advisory lock on a directory's `.lock` file, released when dropped.
+pub(crate) struct FileLock {
+    file: File,
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = FileExt::unlock(&self.file);
+    }
+}
+
+/// Acquire an exclusive lock guarding reads and writes of `path`.
+/// Blocks until any other holder (in this or another process) releases it.
+/// Files in one directory share a single `.lock`, so a holder must not lock a sibling again.
+pub(crate) fn lock_file(path: &Path) -> Result<FileLock, String> {
+    let parent = path.parent().unwrap_or_else(|| Path::new("."));
+    fs::create_dir_all(parent)
+        .map_err(|e| format!("Failed to create directory {:?}: {}", parent, e))?;
+    let lock_path = parent.join(".lock");
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .map_err(|e| format!("Failed to open lock file {:?}: {}", lock_path, e))?;
+    file.lock_exclusive()
+        .map_err(|e| format!("Failed to lock {:?}: {}", lock_path, e))?;
+    Ok(FileLock { file })
+}
+
+/// Write `contents` to `path` via temp file + fsync + rename. Caller must hold `lock_file(path)`.
+/// Readers never observe a partially written file: they see either the old or the new contents.
+pub(crate) fn write_file_atomic_locked(path: &Path, contents: &[u8]) -> Result<(), String> {
+    let tmp_path = sibling_path(path, ".", &format!(".{}.tmp", std::process::id()));
+
+    let result = (|| {
+        let mut tmp = File::create(&tmp_path)
+            .map_err(|e| format!("Failed to create temp file {:?}: {}", tmp_path, e))?;
+        tmp.write_all(contents)
+            .map_err(|e| format!("Failed to write temp file {:?}: {}", tmp_path, e))?;
+        tmp.sync_all()
+            .map_err(|e| format!("Failed to sync temp file {:?}: {}", tmp_path, e))?;
+        drop(tmp);
+        fs::rename(&tmp_path, path)
+            .map_err(|e| format!("Failed to move {:?} into place: {}", path, e))
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+        return result;
+    }
+
+    // Persist the rename itself (directory entry) on filesystems that need it
+    #[cfg(unix)]
+    if let Some(parent) = path.parent() {
+        if let Ok(dir) = File::open(parent) {
+            let _ = dir.sync_all();
+        }
+    }
+
+    Ok(())
+}
+
+/// Atomically replace `path` with `contents` while holding its file lock.
+pub(crate) fn write_file_atomic(path: &Path, contents: &[u8]) -> Result<(), String> {
+    let _lock = lock_file(path)?;
+    write_file_atomic_locked(path, contents)
+}
+
+/// Read and parse an export JSON file, reporting truncated or corrupt files as errors.
+pub(crate) fn read_export_json(path: &Path) -> Result<serde_json::Value, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read export file: {}", e))?;
+    serde_json::from_str::<serde_json::Value>(&content)
+        .map_err(|e| format!("Corrupt export file: {}", e))
+}
+
 fn parse_export_timestamp(path: &Path) -> Option<u64> {
     let filename = path.file_stem()?.to_string_lossy();
     let ts_str = filename.split('_').last()?;
@@ -52,14 +140,9 @@ fn find_latest_export_json(platform_dir: &Path) -> Result<Option<(PathBuf, u64)>
         if !run_entry.path().is_dir() {
             continue;
         }
-        for file_entry in fs::read_dir(run_entry.path()).map_err(|e| e.to_string())?.flatten() {
-            let path = file_entry.path();
-            if path.extension().map_or(false, |ext| ext == "json") {
-                if let Some(ts) = parse_export_timestamp(&path) {
-                    if latest_json.as_ref().map_or(true, |(_, prev_ts)| ts > *prev_ts) {
-                        latest_json = Some((path, ts));
-                    }
-                }
+        if let Some((path, ts)) = latest_run_export_json(&run_entry.path())? {
+            if latest_json.as_ref().map_or(true, |(_, prev_ts)| ts > *prev_ts) {
+                latest_json = Some((path, ts));
             }
         }
     }
@@ -67,11 +150,25 @@ fn find_latest_export_json(platform_dir: &Path) -> Result<Option<(PathBuf, u64)>
     Ok(latest_json)
 }
 
+/// The newest export in a run directory, picked by the timestamp in its
+/// `platformId_timestamp.json` filename. Other files are ignored.
+fn latest_run_export_json(run_dir: &Path) -> Result<Option<(PathBuf, u64)>, String> {
+    let mut latest_json: Option<(PathBuf, u64)> = None;
+    for file_entry in fs::read_dir(run_dir).map_err(|e| e.to_string())?.flatten() {
+        let path = file_entry.path();
+        if path.extension().map_or(false, |ext| ext == "json") {
+            if let Some(ts) = parse_export_timestamp(&path) {
+                if latest_json.as_ref().map_or(true, |(_, prev_ts)| ts > *prev_ts) {
+                    latest_json = Some((path, ts));
+                }
+            }
+        }
+    }
+    Ok(latest_json)
+}
+
 fn read_export_content(path: &Path) -> Result<serde_json::Value, String> {
-    let content = fs::read_to_string(path)
-        .map_err(|e| format!("Failed to read export file: {}", e))?;
-    let data = serde_json::from_str::<serde_json::Value>(&content)
-        .map_err(|e| format!("Failed to parse export file: {}", e))?;
+    let data = read_export_json(path)?;
     match data.get("content") {
         Some(value) if !value.is_null() => Ok(value.clone()),
         _ => Ok(data),
@@ -179,11 +276,6 @@ pub async fn write_export_data(
     name: Option<String>, // Optional display name from frontend
     data: String, // JSON string from frontend
 ) -> Result<String, String> {
-    let timestamp = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
-
     // Parse the JSON string to get the content
     let content: serde_json::Value = serde_json::from_str(&data)
         .map_err(|e| format!("Failed to parse data: {}", e))?;
@@ -197,24 +289,45 @@ pub async fn write_export_data(
             .to_string()
     });
 
-    let data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?
-        .join("exported_data")
-        .join(&company)
-        .join(&name)
-        .join(&run_id);
+    let saved = save_run_export(&app, &run_id, &platform_id, &company, &name, content)?;
+    Ok(saved.path.to_string_lossy().to_string())
+}
+
+/// A run export written to `exported_data`
+#[derive(Debug, Clone)]
+pub(crate) struct SavedExport {
+    pub path: PathBuf,
+    pub bytes: u64,
+    pub items_exported: Option<i64>,
+    pub item_label: Option<String>,
+}
+
+/// Write a run's export content as `<platform_id>_<timestamp>.json` in its run directory
+pub(crate) fn save_run_export(
+    app: &AppHandle,
+    run_id: &str,
+    platform_id: &str,
+    company: &str,
+    name: &str,
+    content: serde_json::Value,
+) -> Result<SavedExport, String> {
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    let data_dir = export_run_dir(app, company, name, run_id)?;
 
     fs::create_dir_all(&data_dir)
         .map_err(|e| format!("Failed to create export directory: {}", e))?;
 
     let file_path = data_dir.join(format!("{}_{}.json", platform_id, timestamp));
+    let (items_exported, item_label) = summarize_content(Some(&content));
 
     let export_data = RunData {
-        company,
-        name,
-        run_id,
+        company: company.to_string(),
+        name: name.to_string(),
+        run_id: run_id.to_string(),
         timestamp,
         content,
     };
@@ -222,11 +335,50 @@ pub async fn write_export_data(
     let json = serde_json::to_string_pretty(&export_data)
         .map_err(|e| format!("Failed to serialize data: {}", e))?;
 
-    fs::write(&file_path, &json).map_err(|e| format!("Failed to write file: {}", e))?;
+    write_file_atomic(&file_path, json.as_bytes())?;
 
     log::info!("Export data saved to: {:?}", file_path);
 
-    Ok(file_path.to_string_lossy().to_string())
+    Ok(SavedExport {
+        path: file_path,
+        bytes: json.len() as u64,
+        items_exported,
+        item_label,
+    })
+}
+
+/// Persist a result received from a connector (see `result_transfer`) as the run's export.
+/// The raw result file is left for the caller to remove.
+pub(crate) fn persist_run_result(
+    app: &AppHandle,
+    run_id: &str,
+    platform_id: &str,
+    company: &str,
+    name: &str,
+    result_path: &Path,
+) -> Result<SavedExport, String> {
+    let reader = std::io::BufReader::new(
+        File::open(result_path).map_err(|e| format!("Failed to open {:?}: {}", result_path, e))?,
+    );
+    let content: serde_json::Value = serde_json::from_reader(reader)
+        .map_err(|e| format!("Failed to parse result for run {}: {}", run_id, e))?;
+    if !content.is_object() {
+        return Err(format!("Result for run {} is not a JSON object", run_id));
+    }
+
+    save_run_export(app, run_id, platform_id, company, name, content)
+}
+
+/// Directory holding a run's export: `exported_data/<company>/<name>/<run_id>`
+pub(crate) fn export_run_dir(app: &AppHandle, company: &str, name: &str, run_id: &str) -> Result<PathBuf, String> {
+    Ok(app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?
+        .join("exported_data")
+        .join(company)
+        .join(name)
+        .join(run_id))
 }
 
 /// Saved run info for loading history
@@ -251,6 +403,74 @@ pub struct SavedRun {
     pub item_label: Option<String>,
     #[serde(rename = "syncedToPersonalServer")]
     pub synced_to_personal_server: Option<bool>,
+    /// Set when the export file exists but could not be read or parsed
+    #[serde(rename = "loadError")]
+    pub load_error: Option<String>,
+}
+
+/// Convert an export timestamp (seconds) to the ISO date string used in run history
+fn format_run_timestamp(timestamp: u64) -> String {
+    chrono::DateTime::from_timestamp(timestamp as i64, 0)
+        .map(|dt| dt.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string())
+        .unwrap_or_else(|| chrono::Utc::now().to_rfc3339())
+}
+
+/// Extract the item count and label from an unsynced export's `content`.
+/// Handles both direct content and nested `content.data` structures.
+pub(crate) fn summarize_export_content(data: &serde_json::Value) -> (Option<i64>, Option<String>) {
+    summarize_content(data.get("content"))
+}
+
+/// Extract the item count and label from export content itself
+fn summarize_content(content: Option<&serde_json::Value>) -> (Option<i64>, Option<String>) {
+    let content_data = content.and_then(|c| c.get("data"));
+
+    // Try exportSummary at content.exportSummary or content.data.exportSummary
+    let export_summary = content.and_then(|c| c.get("exportSummary"))
+        .or_else(|| content_data.and_then(|d| d.get("exportSummary")));
+
+    let items = export_summary
+        .and_then(|s| s.get("count").and_then(|v| v.as_i64()))
+        .or_else(|| {
+            let sources = [content, content_data];
+            for src in sources.iter().flatten() {
+                let count = src.get("totalConversations").and_then(|v| v.as_i64())
+                    .or_else(|| src.get("totalPosts").and_then(|v| v.as_i64()))
+                    .or_else(|| src.get("conversations").and_then(|v| v.as_array()).map(|a| a.len() as i64))
+                    .or_else(|| src.get("posts").and_then(|v| v.as_array()).map(|a| a.len() as i64))
+                    .or_else(|| src.get("memories").and_then(|v| v.as_array()).map(|a| a.len() as i64))
+                    .or_else(|| src.get("media_count").and_then(|v| v.as_i64()))
+                    .or_else(|| ["experience", "education", "skills"].iter().find_map(|key| {
+                        src.get(*key).and_then(|v| v.as_array()).filter(|a| !a.is_empty()).map(|a| a.len() as i64)
+                    }));
+                if count.is_some() {
+                    return count;
+                }
+            }
+            None
+        });
+
+    let label = export_summary
+        .and_then(|s| s.get("label").and_then(|v| v.as_str()).map(|s| s.to_string()))
+        .or_else(|| {
+            let sources = [content, content_data];
+            for src in sources.iter().flatten() {
+                if src.get("posts").is_some() || src.get("media_count").is_some() {
+                    return Some("posts".to_string());
+                } else if src.get("conversations").is_some() {
+                    return Some("conversations".to_string());
+                } else if src.get("memories").is_some() {
+                    return Some("memories".to_string());
+                } else if let Some(key) = ["experience", "education", "skills"].iter().find(|key| {
+                    src.get(**key).and_then(|v| v.as_array()).is_some_and(|a| !a.is_empty())
+                }) {
+                    return Some(key.to_string());
+                }
+            }
+            None
+        });
+
+    (items, label)
 }
 
 /// Load all runs from the exported_data directory
@@ -290,113 +510,73 @@ pub async fn load_runs(app: AppHandle) -> Result<Vec<SavedRun>, String> {
                 let run_id = run_entry.file_name().to_string_lossy().to_string();
                 let run_path = run_entry.path();
 
-                // Find JSON files in the run directory
-                let mut latest_json: Option<(PathBuf, u64)> = None;
-                for file_entry in fs::read_dir(&run_path).map_err(|e| e.to_string())?.flatten() {
-                    let path = file_entry.path();
-                    if path.extension().map_or(false, |ext| ext == "json") {
-                        // Extract timestamp from filename (format: platformId_timestamp.json)
-                        let filename = path.file_stem().unwrap_or_default().to_string_lossy();
-                        if let Some(ts_str) = filename.split('_').last() {
-                            if let Ok(ts) = ts_str.parse::<u64>() {
-                                if latest_json.as_ref().map_or(true, |(_, prev_ts)| ts > *prev_ts) {
-                                    latest_json = Some((path.clone(), ts));
-                                }
-                            }
-                        }
-                    }
-                }
-
-                if let Some((json_path, timestamp)) = latest_json {
-                    // Read the JSON file to get more details
-                    if let Ok(content) = fs::read_to_string(&json_path) {
-                        if let Ok(data) = serde_json::from_str::<serde_json::Value>(&content) {
-                            // Check if this export was synced to personal server
-                            let synced = data.get("syncedToPersonalServer")
-                                .and_then(|v| v.as_bool())
-                                .unwrap_or(false);
-
-                            // For synced runs, read item metadata from top-level fields
-                            // (content was stripped during sync). For unsynced, parse from content.
-                            let (items_exported, item_label) = if synced {
-                                let items = data.get("itemsExported").and_then(|v| v.as_i64());
-                                let label = data.get("itemLabel").and_then(|v| v.as_str()).map(|s| s.to_string());
-                                (items, label)
-                            } else {
-                                // Extract items count and label from the content
-                                // Handle both direct content and nested content.data structures
-                                let content = data.get("content");
-                                let content_data = content.and_then(|c| c.get("data"));
-
-                                // Try exportSummary at content.exportSummary or content.data.exportSummary
-                                let export_summary = content.and_then(|c| c.get("exportSummary"))
-                                    .or_else(|| content_data.and_then(|d| d.get("exportSummary")));
-
-                                let items = export_summary
-                                    .and_then(|s| s.get("count").and_then(|v| v.as_i64()))
-                                    .or_else(|| {
-                                        let sources = [content, content_data];
-                                        for src in sources.iter().flatten() {
-                                            let count = src.get("totalConversations").and_then(|v| v.as_i64())
-                                                .or_else(|| src.get("totalPosts").and_then(|v| v.as_i64()))
-                                                .or_else(|| src.get("conversations").and_then(|v| v.as_array()).map(|a| a.len() as i64))
-                                                .or_else(|| src.get("posts").and_then(|v| v.as_array()).map(|a| a.len() as i64))
-                                                .or_else(|| src.get("memories").and_then(|v| v.as_array()).map(|a| a.len() as i64))
-                                                .or_else(|| src.get("media_count").and_then(|v| v.as_i64()));
-                                            if count.is_some() {
-                                                return count;
-                                            }
-                                        }
-                                        None
-                                    });
-
-                                let label = export_summary
-                                    .and_then(|s| s.get("label").and_then(|v| v.as_str()).map(|s| s.to_string()))
-                                    .or_else(|| {
-                                        let sources = [content, content_data];
-                                        for src in sources.iter().flatten() {
-                                            if src.get("posts").is_some() || src.get("media_count").is_some() {
-                                                return Some("posts".to_string());
-                                            } else if src.get("conversations").is_some() {
-                                                return Some("conversations".to_string());
-                                            } else if src.get("memories").is_some() {
-                                                return Some("memories".to_string());
-                                            }
-                                        }
-                                        None
-                                    });
-
-                                (items, label)
-                            };
-
-                            // Extract display name from JSON, fallback to directory name
-                            let display_name = data
-                                .get("name")
-                                .and_then(|v| v.as_str())
-                                .map(|s| s.to_string())
-                                .unwrap_or_else(|| platform_name.clone());
-
-                            // Convert timestamp to ISO date string
-                            let start_date = chrono::DateTime::from_timestamp(timestamp as i64, 0)
-                                .map(|dt| dt.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string())
-                                .unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
-
+                if let Some((json_path, timestamp)) = latest_run_export_json(&run_path)? {
+                    // Read the JSON file to get more details. A truncated or corrupt file is
+                    // surfaced as an errored run instead of silently disappearing from history.
+                    let data = match read_export_json(&json_path) {
+                        Ok(data) => data,
+                        Err(e) => {
+                            log::error!("Failed to load export {:?}: {}", json_path, e);
+                            let start_date = format_run_timestamp(timestamp);
                             runs.push(SavedRun {
                                 id: run_id.clone(),
                                 platform_id: platform_name.clone(),
                                 filename: platform_name.clone(),
                                 company: company.clone(),
-                                name: display_name,
+                                name: platform_name.clone(),
                                 start_date: start_date.clone(),
                                 end_date: Some(start_date),
-                                status: "success".to_string(),
+                                status: "error".to_string(),
                                 export_path: Some(run_path.to_string_lossy().to_string()),
-                                items_exported,
-                                item_label,
-                                synced_to_personal_server: if synced { Some(true) } else { None },
+                                items_exported: None,
+                                item_label: None,
+                                synced_to_personal_server: None,
+                                load_error: Some(format!("{}: {}", json_path.display(), e)),
                             });
+                            continue;
                         }
-                    }
+                    };
+
+                    // Check if this export was synced to personal server
+                    let synced = data.get("syncedToPersonalServer")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(false);
+
+                    // For synced runs, read item metadata from top-level fields
+                    // (content was stripped during sync). For unsynced, parse from content.
+                    let (items_exported, item_label) = if synced {
+                        let items = data.get("itemsExported").and_then(|v| v.as_i64());
+                        let label = data.get("itemLabel").and_then(|v| v.as_str()).map(|s| s.to_string());
+                        (items, label)
+                    } else {
+                        summarize_export_content(&data)
+                    };
+
+                    // Extract display name from JSON, fallback to directory name
+                    let display_name = data
+                        .get("name")
+                        .and_then(|v| v.as_str())
+                        .map(|s| s.to_string())
+                        .unwrap_or_else(|| platform_name.clone());
+
+                    // Convert timestamp to ISO date string
+                    let start_date = format_run_timestamp(timestamp);
+
+                    runs.push(SavedRun {
+                        id: run_id.clone(),
+                        platform_id: platform_name.clone(),
+                        filename: platform_name.clone(),
+                        company: company.clone(),
+                        name: display_name,
+                        start_date: start_date.clone(),
+                        end_date: Some(start_date),
+                        status: "success".to_string(),
+                        export_path: Some(run_path.to_string_lossy().to_string()),
+                        items_exported,
+                        item_label,
+                        synced_to_personal_server: if synced { Some(true) } else { None },
+                        load_error: None,
+                    });
                 }
             }
         }
@@ -440,13 +620,10 @@ pub async fn load_run_export_data(_run_id: String, export_path: String) -> Resul
         }
     }
 
-    if let Some(json_path) = latest_json {
-        if let Ok(data) = read_export_content(&json_path) {
-            return Ok(data);
-        }
+    match latest_json {
+        Some(json_path) => read_export_content(&json_path),
+        None => Err("Failed to load export data".to_string()),
     }
-
-    Err("Failed to load export data".to_string())
 }
 
 /// Load a truncated JSON preview for the latest source export.
@@ -533,20 +710,12 @@ pub async fn open_platform_export_folder(
     super::download::open_folder(data_dir.to_string_lossy().to_string()).await
 }
 
-/// Trim an export JSON after successful delivery to the personal server.
-/// Strips the large `content` payload, preserving metadata so `load_runs` still works.
-#[tauri::command]
-pub async fn mark_export_synced(
-    app: AppHandle,
-    run_id: String,
-    export_path: String,
-    items_exported: Option<i64>,
-    item_label: Option<String>,
-    scope: Option<String>,
-) -> Result<(), String> {
+/// Resolve an export path (run directory or `.json` file) to the export JSON inside it,
+/// refusing anything outside `exported_data`. Returns `None` if the run directory is gone.
+pub(crate) fn resolve_run_export_json(app: &AppHandle, export_path: &str) -> Result<Option<PathBuf>, String> {
     // Resolve directory: export_path may be a file (.json) or directory
     let dir_path = {
-        let p = PathBuf::from(&export_path);
+        let p = PathBuf::from(export_path);
         if p.extension().map_or(false, |ext| ext == "json") {
             p.parent()
                 .ok_or_else(|| "Invalid export path".to_string())?
@@ -557,8 +726,7 @@ pub async fn mark_export_synced(
     };
 
     if !dir_path.exists() {
-        log::info!("Export path already removed: {:?}", dir_path);
-        return Ok(());
+        return Ok(None);
     }
 
     // Safety: verify inside exported_data
@@ -572,28 +740,86 @@ pub async fn mark_export_synced(
         return Err(format!("Refusing to modify path outside exported_data: {}", export_path));
     }
 
-    // Find the JSON file in the run directory
-    let mut json_path: Option<PathBuf> = None;
-    for entry in fs::read_dir(&dir_path).map_err(|e| e.to_string())?.flatten() {
-        let path = entry.path();
-        if path.extension().map_or(false, |ext| ext == "json") {
-            json_path = Some(path);
-            break;
-        }
+    match latest_run_export_json(&dir_path)? {
+        Some((path, _)) => Ok(Some(path)),
+        None => Err("No JSON file found in export directory".to_string()),
     }
+}
 
-    let json_path = json_path.ok_or_else(|| "No JSON file found in export directory".to_string())?;
+/// Archive file holding the gzipped `content` of a synced export (`<stem>.content.json.gz`)
+pub(crate) fn synced_content_archive_path(json_path: &Path) -> PathBuf {
+    let stem = json_path.file_stem().unwrap_or_default().to_string_lossy();
+    json_path.with_file_name(format!("{}.content.json.gz", stem))
+}
 
-    // Read existing JSON, strip content, add synced metadata
-    let raw = fs::read_to_string(&json_path)
-        .map_err(|e| format!("Failed to read export file: {}", e))?;
-    let mut data: serde_json::Value = serde_json::from_str(&raw)
-        .map_err(|e| format!("Failed to parse export file: {}", e))?;
+fn compress_json(value: &serde_json::Value) -> Result<Vec<u8>, String> {
+    use flate2::{write::GzEncoder, Compression};
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    serde_json::to_writer(&mut encoder, value)
+        .map_err(|e| format!("Failed to compress export content: {}", e))?;
+    encoder
+        .finish()
+        .map_err(|e| format!("Failed to compress export content: {}", e))
+}
+
+fn decompress_json(path: &Path) -> Result<serde_json::Value, String> {
+    let file = File::open(path)
+        .map_err(|e| format!("Failed to open archived content {:?}: {}", path, e))?;
+    serde_json::from_reader(flate2::read::GzDecoder::new(file))
+        .map_err(|e| format!("Corrupt archived content {:?}: {}", path, e))
+}
+
+/// Trim an export JSON after successful delivery to the personal server.
+/// Moves the large `content` payload into a gzipped archive next to the export (restorable via
+/// `restore_synced_export`), preserving metadata so `load_runs` still works. When the config
+/// sets `keepSyncedContent`, the content stays in place and only the synced metadata is added.
+#[tauri::command]
+pub async fn mark_export_synced(
+    app: AppHandle,
+    run_id: String,
+    export_path: String,
+    items_exported: Option<i64>,
+    item_label: Option<String>,
+    scope: Option<String>,
+) -> Result<(), String> {
+    mark_run_export_synced(&app, &run_id, &export_path, items_exported, item_label, scope)
+}
+
+/// Shared by the `mark_export_synced` command and the Rust-side sync queue.
+pub(crate) fn mark_run_export_synced(
+    app: &AppHandle,
+    run_id: &str,
+    export_path: &str,
+    items_exported: Option<i64>,
+    item_label: Option<String>,
+    scope: Option<String>,
+) -> Result<(), String> {
+    let Some(json_path) = resolve_run_export_json(app, export_path)? else {
+        log::info!("Export path already removed: {:?}", export_path);
+        return Ok(());
+    };
+
+    let keep_content = load_app_config()?.keep_synced_content.unwrap_or(false);
+
+    // Hold the lock across read-modify-write so concurrent writers can't interleave
+    let _lock = lock_file(&json_path)?;
+
+    // Read existing JSON, archive content, add synced metadata
+    let mut data = read_export_json(&json_path)?;
 
     let synced_at = chrono::Utc::now().to_rfc3339();
 
-    // Strip the large content payload
-    data["content"] = serde_json::Value::Null;
+    let content = data.get("content").cloned().unwrap_or(serde_json::Value::Null);
+    if !keep_content && !content.is_null() {
+        // Archive first: if this fails the export is left untouched
+        let archive_path = synced_content_archive_path(&json_path);
+        write_file_atomic_locked(&archive_path, &compress_json(&content)?)?;
+        data["contentArchive"] = serde_json::Value::String(
+            archive_path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+        );
+        data["content"] = serde_json::Value::Null;
+    }
     // Add synced metadata at top level
     data["syncedToPersonalServer"] = serde_json::Value::Bool(true);
     data["syncedAt"] = serde_json::Value::String(synced_at);
@@ -609,76 +835,58 @@ pub async fn mark_export_synced(
 
     let trimmed = serde_json::to_string_pretty(&data)
         .map_err(|e| format!("Failed to serialize trimmed export: {}", e))?;
-    fs::write(&json_path, &trimmed)
-        .map_err(|e| format!("Failed to write trimmed export: {}", e))?;
+    write_file_atomic_locked(&json_path, trimmed.as_bytes())?;
 
-    log::info!("Marked export as synced for run {} (trimmed {})", run_id, json_path.display());
-    Ok(())
-}
-
-/// App configuration structure
-#[derive(Debug, Serialize, Deserialize)]
-pub struct AppConfig {
-    #[serde(rename = "storageProvider")]
-    pub storage_provider: Option<String>,
-    #[serde(rename = "serverMode")]
-    pub server_mode: Option<String>,
-    #[serde(rename = "selfHostedUrl")]
-    pub self_hosted_url: Option<String>,
-}
-
-impl Default for AppConfig {
-    fn default() -> Self {
-        Self {
-            storage_provider: Some("local".to_string()),
-            server_mode: Some("cloud".to_string()),
-            self_hosted_url: None,
-        }
+    if keep_content {
+        log::info!("Marked export as synced for run {} (kept content in {})", run_id, json_path.display());
+    } else {
+        log::info!("Marked export as synced for run {} (archived content of {})", run_id, json_path.display());
     }
+    Ok(())
 }
 
-/// Get the path to the DataConnect config file (~/.dataconnect/config.json)
-fn get_config_path() -> Result<PathBuf, String> {
-    let home = home_dir().ok_or("Failed to get home directory")?;
-    let config_dir = home.join(".dataconnect");
-    Ok(config_dir.join("config.json"))
-}
-
-/// Get app configuration from ~/.dataconnect/config.json
+/// Restore the archived `content` of a synced export back into its JSON file.
+/// The run stays marked as synced; only the local copy of the payload comes back.
 #[tauri::command]
-pub async fn get_app_config() -> Result<AppConfig, String> {
-    let config_path = get_config_path()?;
-
-    if !config_path.exists() {
-        // Return default config if file doesn't exist
-        return Ok(AppConfig::default());
-    }
+pub async fn restore_synced_export(app: AppHandle, export_path: String) -> Result<(), String> {
+    let json_path = resolve_run_export_json(&app, &export_path)?
+        .ok_or_else(|| format!("Export path does not exist: {}", export_path))?;
 
-    let content = fs::read_to_string(&config_path)
-        .map_err(|e| format!("Failed to read config file: {}", e))?;
+    let _lock = lock_file(&json_path)?;
+    let mut data = read_export_json(&json_path)?;
 
-    serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse config file: {}", e))
-}
+    if data.get("content").is_some_and(|c| !c.is_null()) {
+        log::info!("Export {} already has local content, nothing to restore", json_path.display());
+        return Ok(());
+    }
 
-/// Set app configuration to ~/.dataconnect/config.json
-#[tauri::command]
-pub async fn set_app_config(config: AppConfig) -> Result<(), String> {
-    let config_path = get_config_path()?;
+    let archive_path = data
+        .get("contentArchive")
+        .and_then(|v| v.as_str())
+        .map(|name| json_path.with_file_name(name))
+        .unwrap_or_else(|| synced_content_archive_path(&json_path));
+    if !archive_path.exists() {
+        return Err(format!(
+            "No archived content for this export; it was trimmed before archiving was available ({})",
+            json_path.display()
+        ));
+    }
 
-    // Ensure the .dataconnect directory exists
-    if let Some(parent) = config_path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    data["content"] = decompress_json(&archive_path)?;
+    if let Some(obj) = data.as_object_mut() {
+        obj.remove("contentArchive");
     }
 
-    let json = serde_json::to_string_pretty(&config)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    let restored = serde_json::to_string_pretty(&data)
+        .map_err(|e| format!("Failed to serialize restored export: {}", e))?;
+    write_file_atomic_locked(&json_path, restored.as_bytes())?;
 
-    fs::write(&config_path, json)
-        .map_err(|e| format!("Failed to write config file: {}", e))?;
+    // Only drop the archive once the restored export is safely on disk
+    if let Err(e) = fs::remove_file(&archive_path) {
+        log::warn!("Failed to remove archive {:?}: {}", archive_path, e);
+    }
 
-    log::info!("App config saved to: {:?}", config_path);
+    log::info!("Restored synced export content for {}", json_path.display());
     Ok(())
 }
 
@@ -702,7 +910,13 @@ pub async fn get_log_path(app: AppHandle) -> Result<String, String> {
 #[cfg(test)]
 mod tests {
     use super::build_source_export_preview;
+    use super::compress_json;
+    use super::decompress_json;
     use super::read_export_content;
+    use super::read_export_json;
+    use super::summarize_content;
+    use super::summarize_export_content;
+    use super::write_file_atomic;
     use serde_json::json;
     use std::fs;
     use std::path::PathBuf;
@@ -779,4 +993,73 @@ mod tests {
         assert_eq!(content["content"], serde_json::Value::Null);
         assert_eq!(content["syncedToPersonalServer"], serde_json::Value::Bool(true));
     }
+
+    #[test]
+    fn summarize_content_counts_profile_sections() {
+        let content = json!({
+            "platform": "linkedin",
+            "company": "LinkedIn",
+            "experience": [],
+            "education": [{ "school": "A" }, { "school": "B" }]
+        });
+        assert_eq!(summarize_content(Some(&content)), (Some(2), Some("education".to_string())));
+
+        let wrapped = json!({ "content": { "data": { "exportSummary": { "count": 7, "label": "posts" } } } });
+        assert_eq!(summarize_export_content(&wrapped), (Some(7), Some("posts".to_string())));
+    }
+
+    #[test]
+    fn write_file_atomic_replaces_contents_without_leaving_temp_files() {
+        let dir = unique_temp_file("atomic_write_dir");
+        fs::create_dir_all(&dir).expect("should create temp dir");
+        let path = dir.join("export_1.json");
+
+        write_file_atomic(&path, b"{\"v\":1}").expect("first write should succeed");
+        write_file_atomic(&path, b"{\"v\":2}").expect("second write should succeed");
+        write_file_atomic(&dir.join("export_2.json"), b"{}").expect("sibling write should succeed");
+
+        let contents = fs::read_to_string(&path).expect("should read written file");
+        let mut names: Vec<_> = fs::read_dir(&dir)
+            .expect("should list temp dir")
+            .flatten()
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+
+        fs::remove_dir_all(&dir).expect("should clean up temp dir");
+
+        assert_eq!(contents, "{\"v\":2}");
+        // No temp files, and one shared lock file rather than one per export
+        assert_eq!(names, [".lock", "export_1.json", "export_2.json"]);
+    }
+
+    #[test]
+    fn read_export_json_reports_truncated_file() {
+        let path = unique_temp_file("truncated_export");
+        fs::write(&path, "{\"company\":\"OpenAI\",\"content\":{\"conv").expect("should write temp file");
+
+        let result = read_export_json(&path);
+
+        fs::remove_file(&path).expect("should clean up temp file");
+
+        let err = result.expect_err("truncated JSON must not parse");
+        assert!(err.starts_with("Corrupt export file"), "unexpected error: {}", err);
+    }
+
+    #[test]
+    fn archived_content_round_trips_through_gzip() {
+        let path = unique_temp_file("synced_content_archive");
+        let content = json!({
+            "conversations": [{ "title": "hello", "messages": ["a", "b"] }],
+            "exportSummary": { "count": 1, "label": "conversations" }
+        });
+        fs::write(&path, compress_json(&content).expect("should compress"))
+            .expect("should write archive");
+
+        let result = decompress_json(&path);
+
+        fs::remove_file(&path).expect("should clean up temp file");
+
+        assert_eq!(result.expect("should decompress archive"), content);
+    }
 }
@@ -0,0 +1,197 @@
+use log::LevelFilter;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Manager};
+
+/// Size at which the active log file is archived (see `RotationStrategy::KeepAll`)
+pub const LOG_MAX_FILE_SIZE: u128 = 5 * 1024 * 1024;
+/// Archived log files older than this are deleted
+const LOG_MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+/// Archived log files are deleted, oldest first, until all logs fit in this many bytes
+const LOG_MAX_TOTAL_SIZE: u64 = 50 * 1024 * 1024;
+const LOG_PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Names `set_log_level` expands to several modules. Connector runs are spread over
+/// the connector, runner, run queue and result transfer modules.
+const MODULE_GROUPS: [(&str, &[&str]); 1] =
+    [("runs", &["connector", "runner", "run_queue", "result_transfer", "run_log"])];
+
+/// Log levels that can be changed while the app runs
+struct LogLevels {
+    default: LevelFilter,
+    /// By module name (`connector`, `server`, ...) or full target path
+    modules: BTreeMap<String, LevelFilter>,
+}
+
+impl LogLevels {
+    fn level_for(&self, target: &str) -> LevelFilter {
+        self.modules
+            .get(target)
+            .or_else(|| target.rsplit("::").find_map(|segment| self.modules.get(segment)))
+            .copied()
+            .unwrap_or(self.default)
+    }
+
+    fn max_level(&self) -> LevelFilter {
+        self.modules.values().copied().fold(self.default, |max, level| max.max(level))
+    }
+}
+
+static LOG_LEVELS: RwLock<LogLevels> = RwLock::new(LogLevels {
+    default: LevelFilter::Info,
+    modules: BTreeMap::new(),
+});
+
+/// Log filter for tauri-plugin-log; the plugin itself is configured to let everything through
+pub fn log_enabled(metadata: &log::Metadata) -> bool {
+    LOG_LEVELS
+        .read()
+        .map(|levels| metadata.level() <= levels.level_for(metadata.target()))
+        .unwrap_or(true)
+}
+
+/// Apply the current levels to the `log` crate, so disabled levels cost nothing
+pub fn apply_log_levels() {
+    if let Ok(levels) = LOG_LEVELS.read() {
+        log::set_max_level(levels.max_level());
+    }
+}
+
+/// Change the log level of one module, or of everything else when `module` is empty or `*`.
+/// Modules are named after the files in `commands/`: `connector` (connector install and
+/// manifests), `runner` (the Playwright runner process), `run_queue`, `result_transfer`,
+/// `scheduler`, `sync_queue`, `server`, `updates`, and so on. `runs` covers every module
+/// involved in a connector run. Levels reset when the app restarts.
+#[tauri::command]
+pub async fn set_log_level(module: Option<String>, level: String) -> Result<(), String> {
+    let level: LevelFilter = level
+        .parse()
+        .map_err(|_| format!("Invalid log level {:?} (expected off, error, warn, info, debug or trace)", level))?;
+    let module = module.filter(|module| !module.is_empty() && module != "*");
+    {
+        let mut levels = LOG_LEVELS.write().map_err(|e| e.to_string())?;
+        match &module {
+            Some(module) => {
+                for name in module_names(module) {
+                    levels.modules.insert(name.to_string(), level);
+                }
+            }
+            None => levels.default = level,
+        }
+    }
+    apply_log_levels();
+    log::info!("Log level for {} set to {}", module.as_deref().unwrap_or("all modules"), level);
+    Ok(())
+}
+
+/// The modules a `set_log_level` name refers to: a group's members, or the name itself
+fn module_names(module: &str) -> Vec<&str> {
+    MODULE_GROUPS
+        .iter()
+        .find(|(group, _)| *group == module)
+        .map_or_else(|| vec![module], |(_, members)| members.to_vec())
+}
+
+/// Delete archived log files that are too old, then the oldest ones until the directory
+/// fits the size cap. `active` (the file being written) is never deleted.
+fn prune_log_dir(log_dir: &Path, active: Option<&Path>, max_age: Duration, max_total: u64) -> Vec<PathBuf> {
+    let now = SystemTime::now();
+    let mut files: Vec<(PathBuf, SystemTime, u64)> = fs::read_dir(log_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| {
+                    let path = entry.path();
+                    if !path.extension().is_some_and(|ext| ext == "log") {
+                        return None;
+                    }
+                    let metadata = entry.metadata().ok()?;
+                    Some((path, metadata.modified().unwrap_or(now), metadata.len()))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    // Oldest first
+    files.sort_by_key(|(_, modified, _)| *modified);
+
+    let mut total: u64 = files.iter().map(|(_, _, size)| size).sum();
+    let mut removed = Vec::new();
+    for (path, modified, size) in &files {
+        if Some(path.as_path()) == active {
+            continue;
+        }
+        let too_old = now.duration_since(*modified).unwrap_or_default() > max_age;
+        if (too_old || total > max_total) && fs::remove_file(path).is_ok() {
+            total -= size;
+            removed.push(path.clone());
+        }
+    }
+    removed
+}
+
+/// Prune archived logs now and then every hour
+pub fn start_log_pruning(app: AppHandle) {
+    let Ok(log_dir) = app.path().app_log_dir() else {
+        return;
+    };
+    std::thread::spawn(move || loop {
+        // The active file is the most recently written one
+        let active = fs::read_dir(&log_dir).ok().and_then(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "log"))
+                .max_by_key(|entry| entry.metadata().and_then(|m| m.modified()).ok())
+                .map(|entry| entry.path())
+        });
+        let removed = prune_log_dir(&log_dir, active.as_deref(), LOG_MAX_AGE, LOG_MAX_TOTAL_SIZE);
+        if !removed.is_empty() {
+            log::info!("Removed {} old log file(s)", removed.len());
+        }
+        std::thread::sleep(LOG_PRUNE_INTERVAL);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    #[test]
+    fn module_levels_override_the_default() {
+        let mut levels = LogLevels { default: LevelFilter::Info, modules: BTreeMap::new() };
+        levels.modules.insert("connector".to_string(), LevelFilter::Debug);
+        assert_eq!(levels.level_for("dataconnect_lib::commands::connector"), LevelFilter::Debug);
+        assert_eq!(levels.level_for("dataconnect_lib::commands::server"), LevelFilter::Info);
+        assert_eq!(levels.max_level(), LevelFilter::Debug);
+
+        assert_eq!(module_names("server"), vec!["server"]);
+        assert!(module_names("runs").contains(&"runner"));
+    }
+
+    #[test]
+    fn prunes_old_and_oversized_archives() {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        let dir = std::env::temp_dir().join(format!("log_prune_{}", nanos));
+        fs::create_dir_all(&dir).unwrap();
+        let write = |name: &str, size: usize, age_secs: u64| {
+            let path = dir.join(name);
+            fs::write(&path, vec![b'x'; size]).unwrap();
+            let modified = SystemTime::now() - Duration::from_secs(age_secs);
+            fs::File::options().write(true).open(&path).unwrap().set_modified(modified).unwrap();
+            path
+        };
+        let active = write("app.log", 40, 0);
+        let recent = write("app_2026-10-18.log", 40, 60);
+        let older = write("app_2026-10-17.log", 40, 120);
+        let ancient = write("app_2026-09-01.log", 10, 3600);
+        write("notes.txt", 500, 3600);
+
+        let removed = prune_log_dir(&dir, Some(&active), Duration::from_secs(1800), 100);
+        assert_eq!(removed, [ancient, older]);
+        assert!(active.exists() && recent.exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,455 @@
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+
+use super::runner_protocol::RUNNER_PROTOCOL_VERSION;
+
+/// Newest connector manifest schema this app understands
+pub const CONNECTOR_MANIFEST_VERSION: u32 = 2;
+
+/// How a connector script is run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConnectorRuntime {
+    /// Injected into a webview window
+    Vanilla,
+    /// Webview window with network interception (`lib/network-capture.js`)
+    NetworkCapture,
+    /// Driven by the Playwright runner
+    Playwright,
+}
+
+/// Capabilities a connector asks for beyond reading the pages it opens
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConnectorPermission {
+    /// Read responses of the page's requests (`page.captureNetwork`, network-capture runtime)
+    NetworkCapture,
+    /// Let the browser download files
+    Downloads,
+}
+
+impl ConnectorPermission {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::NetworkCapture => "network-capture",
+            Self::Downloads => "downloads",
+        }
+    }
+}
+
+/// Whether `host` matches one of `patterns`; `*.example.com` also matches `example.com`
+pub fn domain_allowed(host: &str, patterns: &[String]) -> bool {
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    patterns.iter().any(|pattern| {
+        let pattern = pattern.to_ascii_lowercase();
+        match pattern.strip_prefix("*.") {
+            Some(base) => host == base || host.ends_with(&format!(".{}", base)),
+            None => host == pattern,
+        }
+    })
+}
+
+fn is_valid_domain_pattern(pattern: &str) -> bool {
+    let host = pattern.strip_prefix("*.").unwrap_or(pattern);
+    !host.is_empty()
+        && host.split('.').all(|label| {
+            !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+/// Domains and capabilities a connector asks for, or that the user approved
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConnectorGrant {
+    #[serde(rename = "allowedDomains", default)]
+    pub allowed_domains: Vec<String>,
+    #[serde(default)]
+    pub permissions: Vec<ConnectorPermission>,
+}
+
+impl ConnectorGrant {
+    pub fn new(mut allowed_domains: Vec<String>, mut permissions: Vec<ConnectorPermission>) -> Self {
+        allowed_domains.sort();
+        allowed_domains.dedup();
+        permissions.sort();
+        permissions.dedup();
+        Self { allowed_domains, permissions }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.allowed_domains.is_empty() && self.permissions.is_empty()
+    }
+
+    pub fn has(&self, permission: ConnectorPermission) -> bool {
+        self.permissions.contains(&permission)
+    }
+
+    /// What this grant asks for that `approved` doesn't cover
+    pub fn not_covered_by(&self, approved: &ConnectorGrant) -> ConnectorGrant {
+        ConnectorGrant {
+            allowed_domains: self
+                .allowed_domains
+                .iter()
+                .filter(|domain| !approved.allowed_domains.contains(domain))
+                .cloned()
+                .collect(),
+            permissions: self
+                .permissions
+                .iter()
+                .filter(|permission| !approved.has(**permission))
+                .copied()
+                .collect(),
+        }
+    }
+
+    /// e.g. `domains *.chatgpt.com; capabilities network-capture, downloads`
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.allowed_domains.is_empty() {
+            parts.push(format!("domains {}", self.allowed_domains.join(", ")));
+        }
+        if !self.permissions.is_empty() {
+            let permissions: Vec<&str> = self.permissions.iter().map(|p| p.as_str()).collect();
+            parts.push(format!("capabilities {}", permissions.join(", ")));
+        }
+        parts.join("; ")
+    }
+}
+
+/// A data scope a connector can export, e.g. `chatgpt.conversations`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConnectorScope {
+    pub scope: String,
+    pub label: String,
+    #[serde(default)]
+    pub description: String,
+    /// JSON Schema of the data exported for this scope
+    pub schema: Option<serde_json::Value>,
+}
+
+fn legacy_manifest_version() -> u32 {
+    1
+}
+
+/// A connector's `<name>.json` manifest. Version 1 manifests (no `manifestVersion`) are
+/// still accepted; their script is `<name>.js` next to the manifest.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConnectorManifest {
+    #[serde(rename = "manifestVersion", default = "legacy_manifest_version")]
+    pub manifest_version: u32,
+    pub id: Option<String>,
+    pub name: String,
+    pub company: Option<String>,
+    pub description: String,
+    #[serde(rename = "connectURL")]
+    pub connect_url: String,
+    #[serde(rename = "connectSelector")]
+    pub connect_selector: String,
+    #[serde(rename = "exportFrequency")]
+    pub export_frequency: Option<String>,
+    pub vectorize_config: Option<serde_json::Value>,
+    /// Script to run, relative to the manifest
+    pub entry: Option<String>,
+    /// Defaults to `vanilla` in version 1 manifests
+    pub runtime: Option<ConnectorRuntime>,
+    /// Playwright runner protocol the script was written against
+    #[serde(rename = "runnerProtocolVersion")]
+    pub runner_protocol_version: Option<u32>,
+    #[serde(default)]
+    pub scopes: Vec<ConnectorScope>,
+    #[serde(default)]
+    pub permissions: Vec<ConnectorPermission>,
+    /// Hosts the connector may open and fetch from; defaults to the `connectURL` site for
+    /// webview injection, and only confines Playwright runs when listed
+    #[serde(rename = "allowedDomains", default)]
+    pub allowed_domains: Vec<String>,
+    /// Oldest app version that can run this connector
+    #[serde(rename = "minAppVersion")]
+    pub min_app_version: Option<String>,
+}
+
+impl ConnectorManifest {
+    /// The script file name, relative to the manifest; `stem` is the manifest's file stem
+    pub fn entry_or_default(&self, stem: &str) -> String {
+        self.entry.clone().unwrap_or_else(|| format!("{}.js", stem))
+    }
+
+    /// The platform id: `id`, or `<stem>-001` for manifests without one
+    pub fn platform_id(&self, stem: &str) -> String {
+        self.id.clone().unwrap_or_else(|| format!("{}-001", stem))
+    }
+
+    /// The domains and capabilities the connector needs. Manifests without `allowedDomains`
+    /// get the `connectURL` site; version 1 manifests also get what their runtime used to allow.
+    pub fn requested_grant(&self) -> ConnectorGrant {
+        let mut allowed_domains = self.allowed_domains.clone();
+        if allowed_domains.is_empty() {
+            if let Some(host) = reqwest::Url::parse(&self.connect_url).ok().and_then(|url| url.host_str().map(str::to_string)) {
+                allowed_domains.push(format!("*.{}", host.strip_prefix("www.").unwrap_or(&host)));
+            }
+        }
+        let mut permissions = self.permissions.clone();
+        if self.manifest_version < 2 {
+            match self.runtime {
+                Some(ConnectorRuntime::NetworkCapture) => permissions.push(ConnectorPermission::NetworkCapture),
+                Some(ConnectorRuntime::Playwright) => {
+                    permissions.extend([ConnectorPermission::NetworkCapture, ConnectorPermission::Downloads])
+                }
+                _ => {}
+            }
+        }
+        ConnectorGrant::new(allowed_domains, permissions)
+    }
+
+    /// Hosts a Playwright run may navigate to; empty means it isn't confined. Only version 2
+    /// manifests that list `allowedDomains` are: the `connectURL` default would block logins
+    /// that leave the site (auth.openai.com, accounts.spotify.com, Google or Apple sign-in).
+    pub fn navigation_domains(&self) -> Vec<String> {
+        if self.manifest_version >= 2 && !self.allowed_domains.is_empty() {
+            self.requested_grant().allowed_domains
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Where the entry script of the manifest at `manifest_path` is, or `None` if `entry`
+    /// points outside the manifest's directory
+    pub fn entry_path(&self, manifest_path: &Path) -> Option<PathBuf> {
+        let stem = manifest_path.file_stem().unwrap_or_default().to_string_lossy();
+        let entry = PathBuf::from(self.entry_or_default(&stem));
+        if !entry.components().all(|c| matches!(c, Component::Normal(_))) {
+            return None;
+        }
+        Some(manifest_path.parent()?.join(entry))
+    }
+
+    /// Check the manifest at `manifest_path` against this app, returning every problem found
+    pub fn problems(&self, manifest_path: &Path, app_version: &Version) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if self.manifest_version == 0 || self.manifest_version > CONNECTOR_MANIFEST_VERSION {
+            problems.push(format!(
+                "manifestVersion {} is not supported (this app reads up to {})",
+                self.manifest_version, CONNECTOR_MANIFEST_VERSION
+            ));
+        }
+        if self.manifest_version >= 2 {
+            for (field, missing) in [
+                ("id", self.id.is_none()),
+                ("entry", self.entry.is_none()),
+                ("runtime", self.runtime.is_none()),
+            ] {
+                if missing {
+                    problems.push(format!("\"{}\" is required in manifestVersion 2", field));
+                }
+            }
+        }
+        if self.name.trim().is_empty() {
+            problems.push("\"name\" is empty".to_string());
+        }
+        if !self.connect_url.starts_with("https://") && !self.connect_url.starts_with("http://") {
+            problems.push(format!("\"connectURL\" {:?} is not an http(s) URL", self.connect_url));
+        }
+
+        let stem = manifest_path.file_stem().unwrap_or_default().to_string_lossy();
+        let entry = self.entry_or_default(&stem);
+        let entry_path = Path::new(&entry);
+        if !entry_path.extension().is_some_and(|ext| ext == "js") {
+            problems.push(format!("\"entry\" {:?} is not a .js file", entry));
+        } else if !entry_path.components().all(|c| matches!(c, Component::Normal(_))) {
+            problems.push(format!("\"entry\" {:?} must be a relative path inside the connector directory", entry));
+        } else if let Some(dir) = manifest_path.parent() {
+            if !dir.join(entry_path).is_file() {
+                problems.push(format!("entry script {:?} not found next to the manifest", entry));
+            }
+        }
+
+        if let Some(version) = self.runner_protocol_version {
+            if self.runtime != Some(ConnectorRuntime::Playwright) {
+                problems.push("\"runnerProtocolVersion\" only applies to the playwright runtime".to_string());
+            } else if version > RUNNER_PROTOCOL_VERSION {
+                problems.push(format!(
+                    "needs Playwright runner protocol v{}, but this app speaks v{}",
+                    version, RUNNER_PROTOCOL_VERSION
+                ));
+            }
+        }
+
+        if self.manifest_version >= 2
+            && self.runtime == Some(ConnectorRuntime::NetworkCapture)
+            && !self.permissions.contains(&ConnectorPermission::NetworkCapture)
+        {
+            problems.push("the network-capture runtime needs the \"network-capture\" permission".to_string());
+        }
+        for domain in &self.allowed_domains {
+            if !is_valid_domain_pattern(domain) {
+                problems.push(format!(
+                    "allowed domain {:?} must be a host name such as \"example.com\" or \"*.example.com\"",
+                    domain
+                ));
+            }
+        }
+        let connect_host = reqwest::Url::parse(&self.connect_url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string));
+        if let Some(host) = connect_host {
+            if !self.allowed_domains.is_empty() && !domain_allowed(&host, &self.allowed_domains) {
+                problems.push(format!("\"allowedDomains\" must include the connectURL host {}", host));
+            }
+        }
+
+        let mut seen_scopes = HashSet::new();
+        for scope in &self.scopes {
+            if scope.scope.trim().is_empty() {
+                problems.push("a scope has an empty \"scope\"".to_string());
+            } else if !seen_scopes.insert(scope.scope.as_str()) {
+                problems.push(format!("scope {:?} is declared twice", scope.scope));
+            }
+            if scope.schema.as_ref().is_some_and(|schema| !schema.is_object()) {
+                problems.push(format!("schema of scope {:?} is not a JSON Schema object", scope.scope));
+            }
+        }
+
+        if let Some(min_version) = &self.min_app_version {
+            match Version::parse(min_version) {
+                Ok(min) if min > *app_version => problems.push(format!(
+                    "requires DataConnect {} or newer (this is {})",
+                    min, app_version
+                )),
+                Ok(_) => {}
+                Err(e) => problems.push(format!("\"minAppVersion\" {:?} is not a valid version: {}", min_version, e)),
+            }
+        }
+
+        problems
+    }
+}
+
+/// Parse and validate a manifest file's contents
+pub fn parse_manifest(content: &str, manifest_path: &Path, app_version: &Version) -> Result<ConnectorManifest, String> {
+    let manifest: ConnectorManifest =
+        serde_json::from_str(content).map_err(|e| format!("Invalid manifest: {}", e))?;
+    let problems = manifest.problems(manifest_path, app_version);
+    if !problems.is_empty() {
+        return Err(problems.join("; "));
+    }
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn connector_dir() -> std::path::PathBuf {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        let dir = std::env::temp_dir().join(format!("manifest_{}", nanos)).join("openai");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("chatgpt.js"), "").unwrap();
+        dir
+    }
+
+    #[test]
+    fn accepts_legacy_and_v2_manifests() {
+        let dir = connector_dir();
+        let app_version = Version::new(1, 2, 0);
+
+        let legacy = parse_manifest(
+            r#"{"name":"ChatGPT","description":"d","connectURL":"https://chatgpt.com","connectSelector":"nav"}"#,
+            &dir.join("chatgpt.json"),
+            &app_version,
+        )
+        .unwrap();
+        assert_eq!(legacy.manifest_version, 1);
+        assert_eq!(legacy.entry_or_default("chatgpt"), "chatgpt.js");
+
+        let v2 = parse_manifest(
+            r#"{"manifestVersion":2,"id":"chatgpt-playwright","name":"ChatGPT","description":"d",
+                "connectURL":"https://chatgpt.com","connectSelector":"nav","entry":"chatgpt.js",
+                "runtime":"playwright","runnerProtocolVersion":3,"minAppVersion":"1.0.0",
+                "permissions":["downloads"],
+                "scopes":[{"scope":"chatgpt.conversations","label":"Conversations","schema":{"type":"array"}}]}"#,
+            &dir.join("chatgpt-playwright.json"),
+            &app_version,
+        )
+        .unwrap();
+        assert_eq!(v2.runtime, Some(ConnectorRuntime::Playwright));
+        assert_eq!(v2.permissions, [ConnectorPermission::Downloads]);
+        let _ = fs::remove_dir_all(dir.parent().unwrap());
+    }
+
+    #[test]
+    fn reports_every_problem() {
+        let dir = connector_dir();
+        let error = parse_manifest(
+            r#"{"manifestVersion":2,"name":"ChatGPT","description":"d","connectURL":"chatgpt.com",
+                "connectSelector":"nav","entry":"../other.js","runtime":"vanilla","runnerProtocolVersion":3,
+                "minAppVersion":"9.0.0","scopes":[{"scope":"a","label":"A"},{"scope":"a","label":"A","schema":1}]}"#,
+            &dir.join("chatgpt.json"),
+            &Version::new(1, 2, 0),
+        )
+        .unwrap_err();
+        for expected in [
+            "\"id\" is required",
+            "not an http(s) URL",
+            "must be a relative path",
+            "only applies to the playwright runtime",
+            "scope \"a\" is declared twice",
+            "not a JSON Schema object",
+            "requires DataConnect 9.0.0 or newer",
+        ] {
+            assert!(error.contains(expected), "{:?} missing from {:?}", expected, error);
+        }
+
+        let error = parse_manifest(r#"{"name":"x","runtime":"deno"}"#, &dir.join("x.json"), &Version::new(1, 0, 0))
+            .unwrap_err();
+        assert!(error.starts_with("Invalid manifest"));
+        let _ = fs::remove_dir_all(dir.parent().unwrap());
+    }
+
+    #[test]
+    fn grants_cover_declared_and_legacy_permissions() {
+        let manifest: ConnectorManifest = serde_json::from_str(
+            r#"{"name":"ChatGPT","description":"d","connectURL":"https://www.chatgpt.com/auth","connectSelector":"nav","runtime":"playwright"}"#,
+        )
+        .unwrap();
+        let requested = manifest.requested_grant();
+        assert_eq!(requested.allowed_domains, ["*.chatgpt.com"]);
+        assert_eq!(requested.permissions, [ConnectorPermission::NetworkCapture, ConnectorPermission::Downloads]);
+        assert!(domain_allowed("chatgpt.com", &requested.allowed_domains));
+        assert!(domain_allowed("auth.chatgpt.com", &requested.allowed_domains));
+        assert!(!domain_allowed("evilchatgpt.com", &requested.allowed_domains));
+
+        let approved = ConnectorGrant::new(vec!["*.chatgpt.com".to_string()], vec![ConnectorPermission::Downloads]);
+        let missing = requested.not_covered_by(&approved);
+        assert_eq!(missing, ConnectorGrant::new(vec![], vec![ConnectorPermission::NetworkCapture]));
+        assert_eq!(missing.summary(), "capabilities network-capture");
+        assert!(requested.not_covered_by(&requested).is_empty());
+
+        assert!(is_valid_domain_pattern("*.openai.com"));
+        assert!(!is_valid_domain_pattern("https://openai.com"));
+        assert!(!is_valid_domain_pattern("*.com/"));
+    }
+
+    #[test]
+    fn legacy_manifests_can_navigate_to_other_login_domains() {
+        let legacy: ConnectorManifest = serde_json::from_str(
+            r#"{"name":"ChatGPT","description":"d","connectURL":"https://chatgpt.com/","connectSelector":"nav","runtime":"playwright"}"#,
+        )
+        .unwrap();
+        // Sign-in happens on auth.openai.com, outside the connectURL site
+        assert!(!domain_allowed("auth.openai.com", &legacy.requested_grant().allowed_domains));
+        assert!(legacy.navigation_domains().is_empty());
+
+        let declared: ConnectorManifest = serde_json::from_str(
+            r#"{"manifestVersion":2,"id":"chatgpt-playwright","name":"ChatGPT","description":"d","connectURL":"https://chatgpt.com/",
+                "connectSelector":"nav","runtime":"playwright","allowedDomains":["*.chatgpt.com","auth.openai.com"]}"#,
+        )
+        .unwrap();
+        let domains = declared.navigation_domains();
+        assert!(domain_allowed("auth.openai.com", &domains));
+        assert!(!domain_allowed("evil.example", &domains));
+    }
+}
@@ -1,11 +1,32 @@
+pub mod config;
 pub mod connector;
+pub mod diagnostics;
 pub mod download;
 pub mod file_ops;
+pub mod logging;
+pub mod manifest;
+pub mod redact;
+pub mod result_transfer;
+pub mod run_log;
+pub mod run_queue;
+pub mod runner;
+pub mod runner_protocol;
+pub mod scheduler;
 pub mod server;
+pub mod signing;
+pub mod sync_queue;
 pub mod updates;
 
+pub use config::*;
 pub use connector::*;
+pub use diagnostics::*;
 pub use download::*;
 pub use file_ops::*;
+pub use logging::*;
+pub use run_log::*;
+pub use run_queue::*;
+pub use runner::*;
+pub use scheduler::*;
 pub use server::*;
+pub use sync_queue::*;
 pub use updates::*;
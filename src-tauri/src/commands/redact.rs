@@ -0,0 +1,125 @@
+use regex::Regex;
+use std::borrow::Cow;
+use std::sync::RwLock;
+
+/// What a masked secret is replaced with
+const REDACTED: &str = "[REDACTED]";
+
+/// Built-in patterns and their replacements; `${1}` keeps a matched prefix such as a header name
+const DEFAULT_PATTERNS: &[(&str, &str)] = &[
+    // Cookie and authorization headers run to the end of the line
+    (r"(?i)\b((?:set-)?cookie\s*[:=]\s*)[^\r\n]+", "${1}[REDACTED]"),
+    (r#"(?i)("(?:set-)?cookie"\s*:\s*")[^"]*"#, "${1}[REDACTED]"),
+    (r"(?i)\b((?:proxy-)?authorization\s*:\s*)[^\r\n]+", "${1}[REDACTED]"),
+    (r"(?i)\b(bearer\s+)[A-Za-z0-9\-._~+/]+=*", "${1}[REDACTED]"),
+    (r#"(VANA_MASTER_KEY_SIGNATURE["']?\s*[:=]\s*["']?)[^\s"',}]+"#, "${1}[REDACTED]"),
+    // Signatures and private keys, then wallet addresses
+    (r"\b0x[0-9a-fA-F]{64,}\b", "[REDACTED]"),
+    (r"\b0x[0-9a-fA-F]{40}\b", "[WALLET]"),
+    (r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}", "[EMAIL]"),
+];
+
+/// Masks secrets and personal data in text bound for logs and bug reports
+pub(crate) struct Redactor {
+    rules: Vec<(Regex, String)>,
+}
+
+impl Redactor {
+    /// The built-in rules plus `extra_patterns`, whose whole matches are masked
+    pub(crate) fn new(extra_patterns: &[String]) -> Result<Self, String> {
+        let mut rules = DEFAULT_PATTERNS
+            .iter()
+            .map(|(pattern, replacement)| {
+                Regex::new(pattern)
+                    .map(|re| (re, replacement.to_string()))
+                    .map_err(|e| format!("Invalid built-in redaction pattern {:?}: {}", pattern, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        for pattern in extra_patterns {
+            let re = Regex::new(pattern)
+                .map_err(|e| format!("Invalid log redaction pattern {:?}: {}", pattern, e))?;
+            rules.push((re, REDACTED.to_string()));
+        }
+        Ok(Self { rules })
+    }
+
+    pub(crate) fn redact<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut text = Cow::Borrowed(text);
+        for (re, replacement) in &self.rules {
+            if let Cow::Owned(replaced) = re.replace_all(&text, replacement.as_str()) {
+                text = Cow::Owned(replaced);
+            }
+        }
+        text
+    }
+}
+
+/// The redactor used by the log pipeline; built with the default rules on first use
+static REDACTOR: RwLock<Option<Redactor>> = RwLock::new(None);
+
+/// Replace the user-configured patterns (`logRedactionPatterns` in the app config)
+pub(crate) fn set_redaction_patterns(extra_patterns: &[String]) -> Result<(), String> {
+    let redactor = Redactor::new(extra_patterns)?;
+    *REDACTOR.write().map_err(|e| e.to_string())? = Some(redactor);
+    Ok(())
+}
+
+/// Mask secrets in `text` with the current rules. Never logs, so it is safe to call from
+/// the log formatter.
+pub(crate) fn redact(text: &str) -> String {
+    if let Ok(redactor) = REDACTOR.read() {
+        if let Some(redactor) = redactor.as_ref() {
+            return redactor.redact(text).into_owned();
+        }
+    }
+    let Ok(mut redactor) = REDACTOR.write() else {
+        return REDACTED.to_string();
+    };
+    redactor
+        .get_or_insert_with(|| Redactor::new(&[]).expect("built-in redaction patterns are valid"))
+        .redact(text)
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redact_default(text: &str) -> String {
+        Redactor::new(&[]).unwrap().redact(text).into_owned()
+    }
+
+    #[test]
+    fn redacts_tokens_and_cookies() {
+        assert_eq!(
+            redact_default(r#"fetch failed {"Authorization":"Bearer eyJhbGci.x.y"} again bearer abc"#),
+            r#"fetch failed {"Authorization":"Bearer [REDACTED]"} again bearer [REDACTED]"#
+        );
+        assert_eq!(redact_default("Cookie: sid=1; token=2"), "Cookie: [REDACTED]");
+        assert_eq!(redact_default(r#"{"cookie": "sid=1", "ok": true}"#), r#"{"cookie": "[REDACTED]", "ok": true}"#);
+        assert_eq!(redact_default("authorization: Basic dXNlcjpwYXNz"), "authorization: [REDACTED]");
+        assert_eq!(redact_default("Navigating to: https://x.com"), "Navigating to: https://x.com");
+    }
+
+    #[test]
+    fn redacts_master_key_signature_emails_and_wallets() {
+        let signature = format!("0x{}", "ab".repeat(65));
+        assert_eq!(
+            redact_default(&format!("env VANA_MASTER_KEY_SIGNATURE={} set", signature)),
+            "env VANA_MASTER_KEY_SIGNATURE=[REDACTED] set"
+        );
+        assert_eq!(redact_default(&format!("signed {}", signature)), "signed [REDACTED]");
+        assert_eq!(
+            redact_default("owner 0x52908400098527886E0F7030069857D2E4169EE7 is jane.doe+x@mail.example.co.uk"),
+            "owner [WALLET] is [EMAIL]"
+        );
+    }
+
+    #[test]
+    fn applies_configured_patterns() {
+        let redactor = Redactor::new(&[r"acct-\d+".to_string()]).unwrap();
+        assert_eq!(redactor.redact("loaded acct-12345"), "loaded [REDACTED]");
+        let error = Redactor::new(&["(unclosed".to_string()]).err().expect("bad pattern should be rejected");
+        assert!(error.contains("(unclosed"));
+    }
+}
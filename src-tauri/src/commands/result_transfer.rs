@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+use super::file_ops::{persist_run_result, SavedExport};
+use super::connector::chrono_timestamp;
+
+/// Subdirectory of the run directory results are staged in, so a staged file is never
+/// mistaken for the run's export
+const STAGING_DIR_NAME: &str = ".result";
+/// Name of a committed result inside the staging directory
+pub(crate) const RESULT_FILE_NAME: &str = "result.json";
+const PARTIAL_FILE_NAME: &str = "result.json.partial";
+
+/// A result being received chunk by chunk
+struct ResultTransfer {
+    file: File,
+    partial_path: PathBuf,
+    next_seq: u64,
+    bytes: u64,
+}
+
+/// A fully received, validated result
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CommittedResult {
+    pub path: PathBuf,
+    pub bytes: u64,
+}
+
+/// Transfers in progress, by run id
+static RESULT_TRANSFERS: Mutex<Option<HashMap<String, ResultTransfer>>> = Mutex::new(None);
+
+/// Append chunk `seq` of a run's JSON result. Chunk 0 starts a new transfer staged under
+/// `run_dir` (replacing any unfinished one); later chunks must arrive in order.
+pub(crate) fn append_result_chunk(run_dir: &Path, run_id: &str, seq: u64, data: &str) -> Result<(), String> {
+    let mut transfers = RESULT_TRANSFERS.lock().map_err(|e| e.to_string())?;
+    let transfers = transfers.get_or_insert_with(HashMap::new);
+
+    if seq == 0 {
+        if let Some(stale) = transfers.remove(run_id) {
+            discard_staged(&stale.partial_path);
+        }
+        let staging_dir = run_dir.join(STAGING_DIR_NAME);
+        fs::create_dir_all(&staging_dir).map_err(|e| format!("Failed to create staging directory: {}", e))?;
+        let partial_path = staging_dir.join(PARTIAL_FILE_NAME);
+        let file = File::create(&partial_path)
+            .map_err(|e| format!("Failed to create {:?}: {}", partial_path, e))?;
+        transfers.insert(run_id.to_string(), ResultTransfer { file, partial_path, next_seq: 0, bytes: 0 });
+    }
+
+    let transfer = transfers
+        .get_mut(run_id)
+        .ok_or_else(|| format!("No result transfer in progress for run {}", run_id))?;
+    if seq != transfer.next_seq {
+        let expected = transfer.next_seq;
+        if let Some(broken) = transfers.remove(run_id) {
+            discard_staged(&broken.partial_path);
+        }
+        return Err(format!("Result chunk {} for run {} arrived out of order (expected {})", seq, run_id, expected));
+    }
+
+    if let Err(e) = transfer.file.write_all(data.as_bytes()) {
+        if let Some(broken) = transfers.remove(run_id) {
+            discard_staged(&broken.partial_path);
+        }
+        return Err(format!("Failed to write result chunk: {}", e));
+    }
+    transfer.next_seq += 1;
+    transfer.bytes += data.len() as u64;
+    Ok(())
+}
+
+/// Finish a transfer of `chunks` chunks: check nothing went missing, validate the JSON and
+/// move it into place as `result.json`
+pub(crate) fn commit_result_transfer(run_id: &str, chunks: u64) -> Result<CommittedResult, String> {
+    let transfer = RESULT_TRANSFERS
+        .lock()
+        .map_err(|e| e.to_string())?
+        .as_mut()
+        .and_then(|transfers| transfers.remove(run_id))
+        .ok_or_else(|| format!("No result transfer in progress for run {}", run_id))?;
+
+    let ResultTransfer { file, partial_path, next_seq, bytes } = transfer;
+    let finish = || -> Result<CommittedResult, String> {
+        if next_seq != chunks {
+            return Err(format!("Result for run {} is incomplete: got {} of {} chunks", run_id, next_seq, chunks));
+        }
+        file.sync_all().map_err(|e| format!("Failed to flush result: {}", e))?;
+        drop(file);
+
+        let reader = BufReader::new(File::open(&partial_path).map_err(|e| e.to_string())?);
+        serde_json::from_reader::<_, serde::de::IgnoredAny>(reader)
+            .map_err(|e| format!("Result for run {} is not valid JSON: {}", run_id, e))?;
+
+        let path = partial_path.with_file_name(RESULT_FILE_NAME);
+        fs::rename(&partial_path, &path).map_err(|e| format!("Failed to move result into place: {}", e))?;
+        Ok(CommittedResult { path, bytes })
+    };
+
+    match finish() {
+        Ok(committed) => {
+            log::info!("Received result for run {} ({} bytes in {} chunks)", run_id, committed.bytes, chunks);
+            Ok(committed)
+        }
+        Err(e) => {
+            discard_staged(&partial_path);
+            Err(e)
+        }
+    }
+}
+
+/// Drop an unfinished transfer (run ended or failed before committing)
+pub(crate) fn abort_result_transfer(run_id: &str) {
+    let transfer = RESULT_TRANSFERS
+        .lock()
+        .ok()
+        .and_then(|mut transfers| transfers.as_mut()?.remove(run_id));
+    if let Some(transfer) = transfer {
+        log::warn!("Discarding unfinished result transfer for run {} ({} chunks received)", run_id, transfer.next_seq);
+        discard_staged(&transfer.partial_path);
+    }
+}
+
+/// Remove a staged result file and, once empty, its staging directory
+fn discard_staged(path: &Path) {
+    let _ = fs::remove_file(path);
+    if let Some(staging_dir) = path.parent() {
+        let _ = fs::remove_dir(staging_dir);
+    }
+}
+
+/// Commit a run's transfer and persist it as the run's export in `exported_data`.
+/// The staged result is removed whether or not that succeeds.
+pub(crate) fn persist_transferred_result(
+    app: &AppHandle,
+    run_id: &str,
+    platform_id: &str,
+    company: &str,
+    name: &str,
+    chunks: u64,
+) -> Result<SavedExport, String> {
+    let committed = commit_result_transfer(run_id, chunks)?;
+    let saved = persist_run_result(app, run_id, platform_id, company, name, &committed.path);
+    discard_staged(&committed.path);
+    saved
+}
+
+/// Announce a persisted export. Only a reference is sent; the frontend loads the data
+/// from `exportPath` when it needs it.
+pub(crate) fn emit_export_complete(
+    app: &AppHandle,
+    run_id: &str,
+    platform_id: &str,
+    company: &str,
+    name: &str,
+    saved: &SavedExport,
+) {
+    let _ = app.emit("export-complete", serde_json::json!({
+        "runId": run_id,
+        "platformId": platform_id,
+        "company": company,
+        "name": name,
+        "exportPath": saved.path,
+        "exportSize": saved.bytes,
+        "itemsExported": saved.items_exported,
+        "itemLabel": saved.item_label,
+        "timestamp": chrono_timestamp()
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn unique_temp_dir(prefix: &str) -> PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock should be after unix epoch")
+            .as_nanos();
+        std::env::temp_dir().join(format!("{}_{}", prefix, nanos))
+    }
+
+    #[test]
+    fn chunks_are_reassembled_and_validated() {
+        let dir = unique_temp_dir("result_transfer_ok");
+        let json = r#"{"platform":"chatgpt","conversations":[{"title":"héllo"}]}"#;
+        let (first, rest) = json.split_at(20);
+        append_result_chunk(&dir, "transfer-ok", 0, first).unwrap();
+        append_result_chunk(&dir, "transfer-ok", 1, rest).unwrap();
+
+        let committed = commit_result_transfer("transfer-ok", 2).unwrap();
+        assert_eq!(committed.path, dir.join(STAGING_DIR_NAME).join(RESULT_FILE_NAME));
+        assert_eq!(committed.bytes, json.len() as u64);
+        assert_eq!(fs::read_to_string(&committed.path).unwrap(), json);
+        assert!(!dir.join(STAGING_DIR_NAME).join(PARTIAL_FILE_NAME).exists());
+
+        discard_staged(&committed.path);
+        assert!(!dir.join(STAGING_DIR_NAME).exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn missing_or_reordered_chunks_are_rejected() {
+        let dir = unique_temp_dir("result_transfer_bad");
+        append_result_chunk(&dir, "transfer-gap", 0, "{\"a\":").unwrap();
+        assert!(append_result_chunk(&dir, "transfer-gap", 2, "1}").is_err());
+        // The broken transfer is dropped entirely
+        assert!(commit_result_transfer("transfer-gap", 2).is_err());
+        assert!(!dir.join(STAGING_DIR_NAME).exists());
+
+        append_result_chunk(&dir, "transfer-short", 0, "{\"a\":").unwrap();
+        assert!(commit_result_transfer("transfer-short", 2).unwrap_err().contains("incomplete"));
+
+        append_result_chunk(&dir, "transfer-invalid", 0, "{\"a\":").unwrap();
+        assert!(commit_result_transfer("transfer-invalid", 1).unwrap_err().contains("not valid JSON"));
+        assert!(!dir.join(STAGING_DIR_NAME).exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
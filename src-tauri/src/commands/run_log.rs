@@ -0,0 +1,166 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+use super::connector::chrono_timestamp;
+use super::diagnostics::find_run_dir;
+use super::redact::redact;
+
+/// A run's own log inside its run directory, one JSON object per line
+pub(crate) const RUN_LOG_FILE_NAME: &str = "run.log";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunLogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+/// Where a run log line came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunLogSource {
+    /// `connector-log` messages shown in the UI
+    Connector,
+    /// The Playwright runner's stderr
+    Runner,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunLogEntry {
+    /// Milliseconds since the unix epoch
+    pub timestamp: i64,
+    pub level: RunLogLevel,
+    pub source: RunLogSource,
+    pub message: String,
+}
+
+/// Log files of the runs started in this session, by run id
+static RUN_LOG_PATHS: Mutex<Option<HashMap<String, PathBuf>>> = Mutex::new(None);
+
+/// Start writing a run's log lines to `<run_dir>/run.log`
+pub(crate) fn register_run_log(run_id: &str, run_dir: &Path) {
+    if let Ok(mut paths) = RUN_LOG_PATHS.lock() {
+        paths
+            .get_or_insert_with(HashMap::new)
+            .insert(run_id.to_string(), run_dir.join(RUN_LOG_FILE_NAME));
+    }
+}
+
+fn append_entry(path: &Path, entry: &RunLogEntry) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create run directory: {}", e))?;
+    }
+    let mut line = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+    line.push('\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .map_err(|e| format!("Failed to write {:?}: {}", path, e))
+}
+
+/// Append a line to a run's log (redacted like the global log). Runs without a registered
+/// log are ignored.
+pub(crate) fn append_run_log(run_id: &str, level: RunLogLevel, source: RunLogSource, message: &str) {
+    let path = RUN_LOG_PATHS
+        .lock()
+        .ok()
+        .and_then(|paths| paths.as_ref()?.get(run_id).cloned());
+    let Some(path) = path else { return };
+
+    let entry = RunLogEntry {
+        timestamp: chrono_timestamp(),
+        level,
+        source,
+        message: redact(message),
+    };
+    if let Err(e) = append_entry(&path, &entry) {
+        log::warn!("{}", e);
+    }
+}
+
+/// Show a connector log line in the UI (`connector-log`) and keep it in the run's log
+pub(crate) fn emit_connector_log(app: &AppHandle, run_id: &str, level: RunLogLevel, message: &str) {
+    let _ = app.emit("connector-log", serde_json::json!({
+        "runId": run_id,
+        "message": message,
+        "timestamp": chrono_timestamp()
+    }));
+    append_run_log(run_id, level, RunLogSource::Connector, message);
+}
+
+/// Read a run log, keeping entries at `min_level` or above logged at or after `since` (ms).
+/// Lines that don't parse are skipped.
+fn read_run_log(path: &Path, min_level: Option<RunLogLevel>, since: Option<i64>) -> Result<Vec<RunLogEntry>, String> {
+    let file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to open {:?}: {}", path, e)),
+    };
+    Ok(BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<RunLogEntry>(&line).ok())
+        .filter(|entry| min_level.map_or(true, |min| entry.level >= min))
+        .filter(|entry| since.map_or(true, |since| entry.timestamp >= since))
+        .collect())
+}
+
+/// A run's log entries from `<run_dir>/run.log`, optionally filtered by minimum level and
+/// by time (`since`, milliseconds since the unix epoch)
+#[tauri::command]
+pub async fn get_run_logs(
+    app: AppHandle,
+    run_id: String,
+    level: Option<RunLogLevel>,
+    since: Option<i64>,
+) -> Result<Vec<RunLogEntry>, String> {
+    let registered = RUN_LOG_PATHS
+        .lock()
+        .map_err(|e| e.to_string())?
+        .as_ref()
+        .and_then(|paths| paths.get(&run_id).cloned());
+    let path = match registered {
+        Some(path) => path,
+        None => find_run_dir(&app, &run_id)?.join(RUN_LOG_FILE_NAME),
+    };
+    read_run_log(&path, level, since)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn run_log_round_trips_and_filters() {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        let run_dir = std::env::temp_dir().join(format!("run_log_{}", nanos));
+        register_run_log("run-log-test", &run_dir);
+
+        append_run_log("run-log-test", RunLogLevel::Info, RunLogSource::Connector, "Navigating to: https://x.com");
+        append_run_log("run-log-test", RunLogLevel::Error, RunLogSource::Runner, "Error: 401 for Bearer abc");
+        append_run_log("unregistered-run", RunLogLevel::Error, RunLogSource::Runner, "dropped");
+
+        let path = run_dir.join(RUN_LOG_FILE_NAME);
+        let all = read_run_log(&path, None, None).unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].source, RunLogSource::Connector);
+
+        let errors = read_run_log(&path, Some(RunLogLevel::Warn), None).unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message, "Error: 401 for Bearer [REDACTED]");
+
+        assert!(read_run_log(&path, None, Some(all[1].timestamp + 1)).unwrap().is_empty());
+        assert!(read_run_log(&run_dir.join("missing.log"), None, None).unwrap().is_empty());
+        let _ = fs::remove_dir_all(&run_dir);
+    }
+}
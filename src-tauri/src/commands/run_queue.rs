@@ -0,0 +1,244 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+use super::config::load_app_config;
+use super::connector::chrono_timestamp;
+
+/// Concurrent Playwright runs allowed when `AppConfig.maxConcurrentRuns` is unset
+pub const DEFAULT_MAX_CONCURRENT_RUNS: usize = 2;
+
+/// Finished runs kept around for `list_runs_in_progress`
+const FINISHED_HISTORY: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunState {
+    Queued,
+    Running,
+    Finished,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedRun {
+    #[serde(rename = "runId")]
+    pub run_id: String,
+    #[serde(rename = "platformId")]
+    pub platform_id: String,
+    /// Browser profile the run uses (the connector filename); at most one run per profile
+    pub profile: String,
+    pub state: RunState,
+    #[serde(rename = "queuedAt")]
+    pub queued_at: i64,
+    #[serde(rename = "startedAt")]
+    pub started_at: Option<i64>,
+    #[serde(rename = "finishedAt")]
+    pub finished_at: Option<i64>,
+}
+
+static RUN_QUEUE: Mutex<Vec<QueuedRun>> = Mutex::new(Vec::new());
+
+/// Signalled whenever a slot frees up or a queued run is cancelled
+static RUN_QUEUE_CHANGED: tokio::sync::Notify = tokio::sync::Notify::const_new();
+
+/// A running slot in the queue. Dropping it marks the run finished and lets the next one start.
+pub struct RunSlot {
+    app: AppHandle,
+    run_id: String,
+}
+
+impl Drop for RunSlot {
+    fn drop(&mut self) {
+        finish_run(&self.app, &self.run_id, RunState::Finished);
+    }
+}
+
+fn max_concurrent_runs() -> usize {
+    load_app_config()
+        .ok()
+        .and_then(|config| config.max_concurrent_runs)
+        .map(|max| max.max(1) as usize)
+        .unwrap_or(DEFAULT_MAX_CONCURRENT_RUNS)
+}
+
+/// Whether the queued run at `index` may start now: a free slot, no running run on the same
+/// browser profile, and no earlier queued run that could take the slot first (FIFO).
+fn can_start(queue: &[QueuedRun], index: usize, max_running: usize) -> bool {
+    let candidate = &queue[index];
+    let running: Vec<&QueuedRun> = queue.iter().filter(|r| r.state == RunState::Running).collect();
+    if running.len() >= max_running || running.iter().any(|r| r.profile == candidate.profile) {
+        return false;
+    }
+    !queue[..index].iter().any(|earlier| {
+        earlier.state == RunState::Queued && !running.iter().any(|r| r.profile == earlier.profile)
+    })
+}
+
+fn emit_queue_changed(app: &AppHandle) {
+    if let Ok(queue) = RUN_QUEUE.lock() {
+        let _ = app.emit("run-queue-changed", serde_json::json!({ "runs": *queue }));
+    }
+}
+
+fn finish_run(app: &AppHandle, run_id: &str, state: RunState) {
+    if let Ok(mut queue) = RUN_QUEUE.lock() {
+        if let Some(run) = queue.iter_mut().find(|r| r.run_id == run_id) {
+            if run.state == RunState::Queued || run.state == RunState::Running {
+                run.state = state;
+                run.finished_at = Some(chrono_timestamp());
+            }
+        }
+        // Keep only the most recent finished runs
+        let finished = queue
+            .iter()
+            .filter(|r| matches!(r.state, RunState::Finished | RunState::Cancelled))
+            .count();
+        let mut to_drop = finished.saturating_sub(FINISHED_HISTORY);
+        queue.retain(|r| {
+            if to_drop > 0 && matches!(r.state, RunState::Finished | RunState::Cancelled) {
+                to_drop -= 1;
+                false
+            } else {
+                true
+            }
+        });
+    }
+    RUN_QUEUE_CHANGED.notify_waiters();
+    emit_queue_changed(app);
+}
+
+/// Wait for a slot to run `run_id`. Returns a guard that holds the slot until dropped,
+/// or `None` if the run was cancelled while queued.
+pub(crate) async fn acquire_run_slot(
+    app: &AppHandle,
+    run_id: &str,
+    platform_id: &str,
+    profile: &str,
+) -> Result<Option<RunSlot>, String> {
+    {
+        let mut queue = RUN_QUEUE.lock().map_err(|e| e.to_string())?;
+        if queue
+            .iter()
+            .any(|r| r.run_id == run_id && matches!(r.state, RunState::Queued | RunState::Running))
+        {
+            return Err(format!("Run {} is already queued", run_id));
+        }
+        queue.push(QueuedRun {
+            run_id: run_id.to_string(),
+            platform_id: platform_id.to_string(),
+            profile: profile.to_string(),
+            state: RunState::Queued,
+            queued_at: chrono_timestamp(),
+            started_at: None,
+            finished_at: None,
+        });
+    }
+    emit_queue_changed(app);
+
+    let mut announced_wait = false;
+    loop {
+        // Register for wakeups before checking, so a release in between isn't missed
+        let notified = RUN_QUEUE_CHANGED.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+
+        let max_running = max_concurrent_runs();
+        let waiting_on = {
+            let mut queue = RUN_QUEUE.lock().map_err(|e| e.to_string())?;
+            // A cancelled run may already have been pruned from the history
+            let state = queue.iter().position(|r| r.run_id == run_id).map(|i| (i, queue[i].state));
+            match state {
+                None | Some((_, RunState::Cancelled)) => {
+                    log::info!("Run {} was cancelled while queued", run_id);
+                    return Ok(None);
+                }
+                Some((index, RunState::Queued)) if can_start(&queue, index, max_running) => {
+                    queue[index].state = RunState::Running;
+                    queue[index].started_at = Some(chrono_timestamp());
+                    None
+                }
+                _ => Some(
+                    queue
+                        .iter()
+                        .filter(|r| r.state == RunState::Running)
+                        .map(|r| r.platform_id.clone())
+                        .collect::<Vec<_>>(),
+                ),
+            }
+        };
+
+        let Some(waiting_on) = waiting_on else {
+            emit_queue_changed(app);
+            return Ok(Some(RunSlot {
+                app: app.clone(),
+                run_id: run_id.to_string(),
+            }));
+        };
+
+        if !announced_wait {
+            announced_wait = true;
+            log::info!("Run {} queued behind {:?}", run_id, waiting_on);
+            let _ = app.emit("connector-status", serde_json::json!({
+                "runId": run_id,
+                "status": { "type": "STARTED", "message": "Waiting for other imports to finish..." },
+                "timestamp": chrono_timestamp()
+            }));
+        }
+        notified.await;
+    }
+}
+
+/// Cancel a run that hasn't started yet. Returns true if it was queued.
+pub(crate) fn cancel_queued_run(app: &AppHandle, run_id: &str) -> bool {
+    let was_queued = RUN_QUEUE
+        .lock()
+        .map(|queue| queue.iter().any(|r| r.run_id == run_id && r.state == RunState::Queued))
+        .unwrap_or(false);
+    if was_queued {
+        finish_run(app, run_id, RunState::Cancelled);
+    }
+    was_queued
+}
+
+/// Playwright runs that are queued or running, plus recently finished ones
+#[tauri::command]
+pub async fn list_runs_in_progress() -> Result<Vec<QueuedRun>, String> {
+    Ok(RUN_QUEUE.lock().map_err(|e| e.to_string())?.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(id: &str, profile: &str, state: RunState) -> QueuedRun {
+        QueuedRun {
+            run_id: id.to_string(),
+            platform_id: profile.to_string(),
+            profile: profile.to_string(),
+            state,
+            queued_at: 0,
+            started_at: None,
+            finished_at: None,
+        }
+    }
+
+    #[test]
+    fn can_start_respects_limit_profiles_and_fifo() {
+        let queue = vec![
+            run("a", "chatgpt", RunState::Running),
+            run("b", "chatgpt", RunState::Queued),
+            run("c", "spotify", RunState::Queued),
+            run("d", "linkedin", RunState::Queued),
+        ];
+
+        // Same profile as a running run must wait
+        assert!(!can_start(&queue, 1, 3));
+        // A blocked earlier run doesn't hold up other profiles
+        assert!(can_start(&queue, 2, 3));
+        // FIFO among runs that could start
+        assert!(!can_start(&queue, 3, 3));
+        // Concurrency limit
+        assert!(!can_start(&queue, 2, 1));
+    }
+}
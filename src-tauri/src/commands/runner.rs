@@ -0,0 +1,1119 @@
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::config::ConnectorSettings;
+use super::connector::{chrono_timestamp, get_connectors_dir};
+use super::file_ops::{export_run_dir, save_run_export};
+use super::result_transfer::{
+    abort_result_transfer, append_result_chunk, emit_export_complete, persist_transferred_result,
+};
+use super::run_log::{append_run_log, emit_connector_log, RunLogLevel, RunLogSource};
+use super::run_queue::RunSlot;
+use super::runner_protocol::{
+    check_protocol_version, parse_runner_message, MalformedMessage, PromptKind, RunCommand,
+    RunnerCommand, RunnerMessage, RUNNER_PROTOCOL_VERSION,
+};
+
+/// How often the runner is pinged while alive
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// A runner that doesn't answer a ping within this long is considered hung and restarted
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+/// Give up restarting after this many crashes within `RESTART_WINDOW`
+const MAX_RESTARTS: usize = 3;
+const RESTART_WINDOW: Duration = Duration::from_secs(60);
+
+/// How often the run watchdog checks for timed-out and stalled runs
+const WATCHDOG_INTERVAL: Duration = Duration::from_secs(5);
+/// Run limits when the connector's settings don't set them
+const DEFAULT_RUN_TIMEOUT: Duration = Duration::from_secs(2 * 60 * 60);
+const DEFAULT_STALL_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+/// How long a timed-out run gets to release its browser before that browser is killed
+const STOP_GRACE: Duration = Duration::from_secs(15);
+/// Log lines kept per run for timeout reports
+const RECENT_LOG_LINES: usize = 20;
+
+/// The long-lived Playwright runner process shared by all runs
+struct RunnerProcess {
+    child: Child,
+    stdin: ChildStdin,
+    /// Bumped on every spawn so a dying process's reader can't clobber its replacement
+    generation: u64,
+}
+
+/// Host-side state of a run multiplexed over the runner
+pub(crate) struct RunContext {
+    platform_id: String,
+    company: String,
+    name: String,
+    /// Released (letting the next queued run start) once the runner reports the run done
+    _slot: RunSlot,
+    /// Whether COMPLETE / ERROR / STOPPED was already forwarded to the UI
+    terminal_seen: bool,
+    /// Prompts shown to the user and still waiting for an answer
+    pending_prompts: HashMap<String, PendingPrompt>,
+    limits: RunLimits,
+    started_at: Instant,
+    /// Last status/log/data message from the run
+    last_activity: Instant,
+    /// Whether the last status was the connector waiting on the user (login, 2FA, ...)
+    waiting_on_user: bool,
+    recent_logs: VecDeque<String>,
+    /// Set once the watchdog stopped the run
+    timed_out_at: Option<Instant>,
+    /// PID of the run's current browser, the leader of its process group
+    browser_pid: Option<u32>,
+    /// Set once the watchdog killed the browser of a run that wouldn't stop
+    browser_killed_at: Option<Instant>,
+    /// Set when the run's result couldn't be received; its later COMPLETE is dropped
+    result_failed: bool,
+}
+
+/// A prompt the connector is waiting on, kept to check the user's answer against
+struct PendingPrompt {
+    kind: PromptKind,
+    options: Option<Vec<serde_json::Value>>,
+    /// Unix millis after which the runner has given up on it
+    expires_at: i64,
+}
+
+impl PendingPrompt {
+    /// Reject answers the connector can't use: `confirm` needs a bool, `choice` one of
+    /// the options, and the free-form kinds a string
+    fn check_answer(&self, value: &serde_json::Value) -> Result<(), String> {
+        match self.kind {
+            PromptKind::Confirm if !value.is_boolean() => Err("Confirm prompts expect true or false".to_string()),
+            PromptKind::Choice if !self.options.as_ref().is_some_and(|options| options.contains(value)) => {
+                Err("Answer is not one of the prompt's options".to_string())
+            }
+            PromptKind::Text | PromptKind::Secret | PromptKind::Code if !value.is_string() => {
+                Err("Prompt expects a text answer".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Watchdog limits for one run
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RunLimits {
+    pub max_duration: Duration,
+    pub stall_timeout: Duration,
+}
+
+impl RunLimits {
+    pub(crate) fn from_settings(settings: &ConnectorSettings) -> Self {
+        Self {
+            max_duration: settings.timeout_secs.map(Duration::from_secs).unwrap_or(DEFAULT_RUN_TIMEOUT),
+            stall_timeout: settings.stall_timeout_secs.map(Duration::from_secs).unwrap_or(DEFAULT_STALL_TIMEOUT),
+        }
+    }
+
+    /// The reason a run that has been going for `elapsed`, with its last message `idle` ago,
+    /// must be stopped, or `None` if it is within its limits
+    fn check(&self, elapsed: Duration, idle: Duration, waiting_on_user: bool) -> Option<String> {
+        if elapsed >= self.max_duration {
+            return Some(format!("Timed out after {} minutes", self.max_duration.as_secs() / 60));
+        }
+        if !waiting_on_user && idle >= self.stall_timeout {
+            return Some(format!("Stalled: no progress for {} seconds", idle.as_secs()));
+        }
+        None
+    }
+}
+
+impl RunContext {
+    pub(crate) fn new(platform_id: String, company: String, name: String, slot: RunSlot, limits: RunLimits) -> Self {
+        Self {
+            platform_id,
+            company,
+            name,
+            _slot: slot,
+            terminal_seen: false,
+            pending_prompts: HashMap::new(),
+            limits,
+            started_at: Instant::now(),
+            last_activity: Instant::now(),
+            waiting_on_user: false,
+            recent_logs: VecDeque::new(),
+            timed_out_at: None,
+            browser_pid: None,
+            browser_killed_at: None,
+            result_failed: false,
+        }
+    }
+
+    fn remember_log(&mut self, line: &str) {
+        if self.recent_logs.len() == RECENT_LOG_LINES {
+            self.recent_logs.pop_front();
+        }
+        self.recent_logs.push_back(line.to_string());
+    }
+
+    /// Why the watchdog should stop this run now, if it should
+    fn timeout_reason(&self, now: Instant) -> Option<String> {
+        // Time spent waiting on the user isn't a stall; prompts have their own timeouts
+        let waiting_on_user = self.waiting_on_user || !self.pending_prompts.is_empty();
+        self.limits.check(
+            now.duration_since(self.started_at),
+            now.duration_since(self.last_activity),
+            waiting_on_user,
+        )
+    }
+}
+
+static RUNNER: Mutex<Option<RunnerProcess>> = Mutex::new(None);
+static RUNNER_GENERATION: Mutex<u64> = Mutex::new(0);
+static RUNNER_RUNS: Mutex<Option<HashMap<String, RunContext>>> = Mutex::new(None);
+/// Replies to `ping` / `test` requests, keyed by requestId
+static RUNNER_REQUESTS: Mutex<Option<HashMap<String, std::sync::mpsc::Sender<RunnerMessage>>>> =
+    Mutex::new(None);
+static RUNNER_REQUEST_SEQ: AtomicU64 = AtomicU64::new(0);
+/// Set when the runner failed the version handshake; it isn't auto-restarted until the next run
+static RUNNER_PROTOCOL_ERROR: Mutex<Option<String>> = Mutex::new(None);
+static RUNNER_RESTARTS: Mutex<Vec<Instant>> = Mutex::new(Vec::new());
+/// Set on app exit so the reader thread doesn't restart the runner
+static RUNNER_SHUTTING_DOWN: Mutex<bool> = Mutex::new(false);
+
+/// Get the path to the bundled Playwright runner binary (production) or None (dev)
+fn get_bundled_playwright_runner(app: &AppHandle) -> Option<(PathBuf, Option<PathBuf>)> {
+    // In production, the binary is in the resources directory
+    let resource_dir = app.path().resource_dir().ok()?;
+
+    #[cfg(target_os = "macos")]
+    let binary_name = "playwright-runner";
+    #[cfg(target_os = "windows")]
+    let binary_name = "playwright-runner.exe";
+    #[cfg(target_os = "linux")]
+    let binary_name = "playwright-runner";
+
+    log::info!("=== Looking for Playwright runner ===");
+    log::info!("Resource directory: {:?}", resource_dir);
+    log::info!("Binary name: {}", binary_name);
+
+    // List contents of resource directory for debugging
+    if let Ok(entries) = std::fs::read_dir(&resource_dir) {
+        let contents: Vec<_> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .collect();
+        log::info!("Resource dir contents: {:?}", contents);
+    }
+
+    // Try playwright-runner/dist path (matches tauri.conf.json resources config)
+    let dist_path = resource_dir.join("playwright-runner").join("dist");
+    let dist_binary = dist_path.join(binary_name);
+    let dist_browsers = dist_path.join("browsers");
+
+    log::info!("Checking path 1: {:?} (exists: {})", dist_binary, dist_binary.exists());
+    if dist_path.exists() {
+        if let Ok(entries) = std::fs::read_dir(&dist_path) {
+            let contents: Vec<_> = entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.file_name().to_string_lossy().to_string())
+                .collect();
+            log::info!("  dist path contents: {:?}", contents);
+        }
+    }
+
+    if dist_binary.exists() {
+        log::info!("Found bundled Playwright runner at {:?}", dist_binary);
+        let browsers = if dist_browsers.exists() {
+            Some(dist_browsers)
+        } else {
+            None
+        };
+        return Some((dist_binary, browsers));
+    }
+
+    // Try binaries/ path (alternative CI builds layout)
+    let binary_path = resource_dir.join("binaries").join(binary_name);
+    let browsers_path = resource_dir.join("binaries").join("browsers");
+
+    log::info!("Checking path 2: {:?} (exists: {})", binary_path, binary_path.exists());
+
+    if binary_path.exists() {
+        log::info!("Found bundled Playwright runner at {:?}", binary_path);
+        let browsers = if browsers_path.exists() {
+            Some(browsers_path)
+        } else {
+            None
+        };
+        return Some((binary_path, browsers));
+    }
+
+    // Try _up_/playwright-runner/dist path (local builds with "../playwright-runner/dist" resource)
+    let up_path = resource_dir.join("_up_").join("playwright-runner").join("dist");
+    let up_binary = up_path.join(binary_name);
+    let up_browsers = up_path.join("browsers");
+
+    log::info!("Checking path 3: {:?} (exists: {})", up_binary, up_binary.exists());
+    if up_path.exists() {
+        if let Ok(entries) = std::fs::read_dir(&up_path) {
+            let contents: Vec<_> = entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.file_name().to_string_lossy().to_string())
+                .collect();
+            log::info!("  _up_ path contents: {:?}", contents);
+        }
+    }
+
+    if up_binary.exists() {
+        log::info!("Found bundled Playwright runner at {:?}", up_binary);
+        let browsers = if up_browsers.exists() {
+            Some(up_browsers)
+        } else {
+            None
+        };
+        return Some((up_binary, browsers));
+    }
+
+    log::error!("=== Playwright runner NOT FOUND in any location ===");
+    None
+}
+
+
+/// Spawn a fresh runner process (bundled binary in release, `node index.cjs` in dev)
+fn spawn_runner_process(app: &AppHandle) -> Result<Child, String> {
+    // In debug mode, always use node directly (avoids macOS code signing issues with copied binaries)
+    // In release mode, use the bundled binary
+    #[cfg(debug_assertions)]
+    let use_bundled_binary = false;
+    #[cfg(not(debug_assertions))]
+    let use_bundled_binary = true;
+
+    let mut cmd = if use_bundled_binary {
+        let (binary_path, browsers_path) = get_bundled_playwright_runner(app)
+            .ok_or("Bundled Playwright runner not found")?;
+        log::info!("Found bundled Playwright runner at: {:?}", binary_path);
+
+        // Production mode: use bundled binary
+        let mut cmd = Command::new(&binary_path);
+
+        // Set browser path if bundled
+        if let Some(ref browsers) = browsers_path {
+            log::info!("Setting PLAYWRIGHT_BROWSERS_PATH to: {:?}", browsers);
+            cmd.env("PLAYWRIGHT_BROWSERS_PATH", browsers);
+        }
+        cmd
+    } else {
+        // Dev mode: use node index.cjs directly (avoids code signing issues)
+        log::info!("Dev mode: using node index.cjs directly");
+        let connectors_dir = get_connectors_dir(app);
+        let runner_dir = connectors_dir
+            .parent()
+            .map(|p| p.join("playwright-runner"))
+            .ok_or("Could not find playwright-runner directory")?;
+
+        if !runner_dir.exists() {
+            return Err(format!("Playwright runner not found: {:?}. Run 'npm install' in playwright-runner directory.", runner_dir));
+        }
+
+        let mut cmd = Command::new("node");
+        cmd.arg("index.cjs").current_dir(&runner_dir);
+        cmd
+    };
+
+    cmd.stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    // Create a new process group so we can kill all children (Chrome) at once
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+
+    log::info!("Spawning Playwright runner...");
+    cmd.spawn()
+        .map_err(|e| format!("Failed to spawn Playwright runner: {}", e))
+}
+
+/// Make sure the shared runner is alive, spawning it if needed
+fn ensure_runner(app: &AppHandle) -> Result<(), String> {
+    let mut guard = RUNNER.lock().map_err(|e| e.to_string())?;
+    if let Some(runner) = guard.as_mut() {
+        match runner.child.try_wait() {
+            Ok(None) => return Ok(()),
+            _ => {
+                log::warn!("Playwright runner (generation {}) is gone, respawning", runner.generation);
+                *guard = None;
+            }
+        }
+    }
+
+    let mut child = spawn_runner_process(app)?;
+    let mut stdin = child.stdin.take().ok_or("Failed to get stdin")?;
+    let stdout = child.stdout.take().ok_or("Failed to get stdout")?;
+    let stderr = child.stderr.take().ok_or("Failed to get stderr")?;
+
+    let generation = {
+        let mut counter = RUNNER_GENERATION.lock().map_err(|e| e.to_string())?;
+        *counter += 1;
+        *counter
+    };
+    log::info!("Playwright runner spawned (pid {}, generation {})", child.id(), generation);
+    if let Ok(mut protocol_error) = RUNNER_PROTOCOL_ERROR.lock() {
+        *protocol_error = None;
+    }
+
+    // Handshake: the runner refuses to start if it doesn't speak our version,
+    // and we check the version it announces in `ready`
+    let hello = RunnerCommand::Hello { protocol_version: RUNNER_PROTOCOL_VERSION };
+    let hello = serde_json::to_string(&hello).map_err(|e| e.to_string())?;
+    if let Err(e) = writeln!(stdin, "{}", hello).and_then(|_| stdin.flush()) {
+        let _ = child.kill();
+        return Err(format!("Failed to send handshake to Playwright runner: {}", e));
+    }
+
+    // Spawn thread to read stderr (for debug logs). Lines logged for a run are
+    // prefixed with `[run:<runId>]` and also go to that run's log.
+    std::thread::spawn(move || {
+        let reader = BufReader::new(stderr);
+        for line in reader.lines().map_while(Result::ok) {
+            match split_run_prefix(&line) {
+                Some((run_id, message)) => {
+                    log::info!("[Playwright:{}] {}", run_id, message);
+                    append_run_log(run_id, RunLogLevel::Info, RunLogSource::Runner, message);
+                }
+                None => log::info!("[Playwright] {}", line),
+            }
+        }
+    });
+
+    // Spawn thread to read stdout and route messages to their run
+    let app_for_stdout = app.clone();
+    std::thread::spawn(move || {
+        let reader = BufReader::new(stdout);
+        for line in reader.lines().map_while(Result::ok) {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match parse_runner_message(&line) {
+                Ok(msg) => handle_runner_message(&app_for_stdout, generation, msg),
+                Err(malformed) => handle_malformed_message(&app_for_stdout, &line, malformed),
+            }
+        }
+        handle_runner_exit(&app_for_stdout, generation);
+    });
+
+    *guard = Some(RunnerProcess { child, stdin, generation });
+    Ok(())
+}
+
+/// Write one JSON command line to the runner's stdin
+fn write_runner_command(cmd: &RunnerCommand) -> Result<(), String> {
+    let line = serde_json::to_string(cmd).map_err(|e| e.to_string())?;
+    let mut guard = RUNNER.lock().map_err(|e| e.to_string())?;
+    let runner = guard.as_mut().ok_or("Playwright runner is not running")?;
+    writeln!(runner.stdin, "{}", line)
+        .and_then(|_| runner.stdin.flush())
+        .map_err(|e| format!("Failed to send command to Playwright runner: {}", e))
+}
+
+/// Start a run on the shared runner. `ctx` holds the run's queue slot until it is done.
+pub(crate) fn start_runner_run(app: &AppHandle, ctx: RunContext, run_cmd: RunCommand) -> Result<(), String> {
+    ensure_runner(app)?;
+    let run_id = run_cmd.run_id.clone();
+    RUNNER_RUNS
+        .lock()
+        .map_err(|e| e.to_string())?
+        .get_or_insert_with(HashMap::new)
+        .insert(run_id.clone(), ctx);
+
+    if let Err(e) = write_runner_command(&RunnerCommand::Run(run_cmd)) {
+        take_run(&run_id);
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Ask the runner to stop a run. Returns false if the run isn't on the runner.
+pub(crate) fn stop_runner_run(run_id: &str) -> Result<bool, String> {
+    let known = RUNNER_RUNS
+        .lock()
+        .map_err(|e| e.to_string())?
+        .as_ref()
+        .is_some_and(|runs| runs.contains_key(run_id));
+    if !known {
+        return Ok(false);
+    }
+    log::info!("Stopping Playwright run {}", run_id);
+    write_runner_command(&RunnerCommand::Stop { run_id: run_id.to_string() })?;
+    Ok(true)
+}
+
+/// Number of Playwright runs currently in flight
+pub(crate) fn active_playwright_runs() -> usize {
+    RUNNER_RUNS
+        .lock()
+        .ok()
+        .and_then(|runs| runs.as_ref().map(|r| r.len()))
+        .unwrap_or(0)
+}
+
+fn take_run(run_id: &str) -> Option<RunContext> {
+    RUNNER_RUNS.lock().ok()?.as_mut()?.remove(run_id)
+}
+
+/// Answer a prompt a connector is waiting on. `value` omitted (null) means the user
+/// dismissed it; `confirm` prompts expect a bool and `choice` prompts one of the options.
+#[tauri::command]
+pub async fn answer_connector_prompt(
+    run_id: String,
+    prompt_id: String,
+    value: Option<serde_json::Value>,
+) -> Result<(), String> {
+    {
+        let mut runs = RUNNER_RUNS.lock().map_err(|e| e.to_string())?;
+        let ctx = runs
+            .as_mut()
+            .and_then(|runs| runs.get_mut(&run_id))
+            .ok_or_else(|| format!("Run {} is not running", run_id))?;
+        let prompt = ctx
+            .pending_prompts
+            .get(&prompt_id)
+            .ok_or_else(|| format!("Prompt {} is no longer waiting for an answer", prompt_id))?;
+        if chrono_timestamp() > prompt.expires_at {
+            ctx.pending_prompts.remove(&prompt_id);
+            return Err(format!("Prompt {} has timed out", prompt_id));
+        }
+        if let Some(value) = &value {
+            // Leave the prompt pending so the user can correct the answer
+            prompt.check_answer(value)?;
+        }
+        ctx.pending_prompts.remove(&prompt_id);
+    }
+
+    let cancelled = value.is_none();
+    log::info!(
+        "Answering prompt {} of run {}{}",
+        prompt_id,
+        run_id,
+        if cancelled { " (dismissed)" } else { "" }
+    );
+    write_runner_command(&RunnerCommand::PromptAnswer {
+        run_id,
+        prompt_id,
+        value: value.unwrap_or(serde_json::Value::Null),
+        cancelled,
+    })
+}
+
+/// Send a request (`ping`, `test`) to the runner and wait for the matching reply
+pub(crate) async fn runner_request(
+    app: &AppHandle,
+    request: fn(String) -> RunnerCommand,
+    timeout: Duration,
+) -> Result<RunnerMessage, String> {
+    ensure_runner(app)?;
+
+    let request_id = format!("req-{}", RUNNER_REQUEST_SEQ.fetch_add(1, Ordering::Relaxed));
+    let command = request(request_id.clone());
+    let (tx, rx) = std::sync::mpsc::channel();
+    RUNNER_REQUESTS
+        .lock()
+        .map_err(|e| e.to_string())?
+        .get_or_insert_with(HashMap::new)
+        .insert(request_id.clone(), tx);
+
+    let sent = write_runner_command(&command);
+    let reply = match sent {
+        Ok(()) => tokio::task::spawn_blocking(move || rx.recv_timeout(timeout))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|_| format!("No reply to {:?} from Playwright runner", command)),
+        Err(e) => Err(e),
+    };
+    let reply = match reply {
+        Ok(RunnerMessage::ProtocolError { message, .. }) => {
+            Err(format!("Playwright runner rejected request: {}", message))
+        }
+        other => other,
+    };
+
+    if let Ok(mut requests) = RUNNER_REQUESTS.lock() {
+        if let Some(requests) = requests.as_mut() {
+            requests.remove(&request_id);
+        }
+    }
+    reply
+}
+
+/// Split a runner stderr line of the form `[run:<runId>] message`
+fn split_run_prefix(line: &str) -> Option<(&str, &str)> {
+    let rest = line.strip_prefix("[run:")?;
+    let (run_id, message) = rest.split_once("] ")?;
+    Some((run_id, message))
+}
+
+fn emit_prompt_closed(app: &AppHandle, run_id: &str, prompt_id: &str, reason: &str) {
+    let _ = app.emit("connector-prompt-closed", serde_json::json!({
+        "runId": run_id,
+        "promptId": prompt_id,
+        "reason": reason,
+        "timestamp": chrono_timestamp()
+    }));
+}
+
+/// Dismiss any prompts the UI is still showing for a run that is gone
+fn close_run_prompts(app: &AppHandle, run_id: &str, ctx: &RunContext) {
+    for prompt_id in ctx.pending_prompts.keys() {
+        emit_prompt_closed(app, run_id, prompt_id, "run-ended");
+    }
+}
+
+/// The run's result could not be saved: report the run as failed and ignore its later statuses
+fn fail_run_result(app: &AppHandle, run_id: &str, error: String) {
+    log::error!("{}", error);
+    if let Ok(mut runs) = RUNNER_RUNS.lock() {
+        if let Some(ctx) = runs.as_mut().and_then(|runs| runs.get_mut(run_id)) {
+            ctx.result_failed = true;
+            ctx.terminal_seen = true;
+        }
+    }
+    emit_connector_log(app, run_id, RunLogLevel::Error, &format!("Error: {}", error));
+    let _ = app.emit("connector-status", serde_json::json!({
+        "runId": run_id,
+        "status": { "type": "ERROR", "message": "Failed to save the export" },
+        "timestamp": chrono_timestamp()
+    }));
+}
+
+/// Surface a protocol problem: logged, attributed to its run if known, and emitted for the UI
+fn emit_protocol_error(app: &AppHandle, run_id: Option<&str>, message: &str) {
+    log::error!("Playwright runner protocol error: {}", message);
+    if let Some(run_id) = run_id {
+        emit_connector_log(app, run_id, RunLogLevel::Error, &format!("Runner protocol error: {}", message));
+    }
+    let _ = app.emit("runner-protocol-error", serde_json::json!({
+        "runId": run_id,
+        "message": message,
+        "timestamp": chrono_timestamp()
+    }));
+}
+
+/// A stdout line that isn't a valid message. Non-JSON output (e.g. from a dependency
+/// writing to stdout) is only logged; JSON we can't decode is surfaced as a protocol error.
+fn handle_malformed_message(app: &AppHandle, line: &str, malformed: MalformedMessage) {
+    let preview: String = line.chars().take(200).collect();
+    if !line.trim_start().starts_with('{') {
+        log::warn!("[Playwright stdout] {}", preview);
+        return;
+    }
+    emit_protocol_error(app, malformed.run_id.as_deref(), &format!("{}: {}", malformed.error, preview));
+}
+
+/// The runner announced an incompatible protocol: fail its runs and stop it without restarting
+fn reject_runner(app: &AppHandle, generation: u64, reason: String) {
+    emit_protocol_error(app, None, &reason);
+    if let Ok(mut protocol_error) = RUNNER_PROTOCOL_ERROR.lock() {
+        *protocol_error = Some(reason);
+    }
+    if let Ok(mut guard) = RUNNER.lock() {
+        if let Some(runner) = guard.as_mut().filter(|r| r.generation == generation) {
+            // The reader thread sees stdout close and fails the in-flight runs
+            kill_runner(runner);
+        }
+    }
+}
+
+/// Route a runner stdout message to its run (or pending request) and forward it to the UI
+fn handle_runner_message(app: &AppHandle, generation: u64, msg: RunnerMessage) {
+    if let Some(request_id) = msg.request_id() {
+        let responder = RUNNER_REQUESTS
+            .lock()
+            .ok()
+            .and_then(|mut requests| requests.as_mut()?.remove(request_id));
+        if let Some(responder) = responder {
+            let _ = responder.send(msg);
+        } else {
+            log::debug!("Playwright runner reply for unknown request {}", request_id);
+        }
+        return;
+    }
+
+    let run_id = match &msg {
+        RunnerMessage::Ready { protocol_version, runner_version } => {
+            match check_protocol_version(*protocol_version) {
+                Ok(()) => log::info!(
+                    "Playwright runner ready (protocol v{}, runner {})",
+                    RUNNER_PROTOCOL_VERSION,
+                    runner_version.as_deref().unwrap_or("unknown")
+                ),
+                Err(reason) => reject_runner(app, generation, reason),
+            }
+            return;
+        }
+        RunnerMessage::Pong { .. } | RunnerMessage::TestResult { .. } => return,
+        RunnerMessage::ProtocolError { run_id, message, .. } => {
+            emit_protocol_error(app, run_id.as_deref(), message);
+            return;
+        }
+        RunnerMessage::Log { run_id, .. }
+        | RunnerMessage::Status { run_id, .. }
+        | RunnerMessage::Result { run_id, .. }
+        | RunnerMessage::ResultChunk { run_id, .. }
+        | RunnerMessage::ResultCommit { run_id, .. }
+        | RunnerMessage::Error { run_id, .. }
+        | RunnerMessage::Data { run_id, .. }
+        | RunnerMessage::NetworkCaptured { run_id, .. }
+        | RunnerMessage::Diagnostics { run_id, .. }
+        | RunnerMessage::Browser { run_id, .. }
+        | RunnerMessage::Prompt { run_id, .. }
+        | RunnerMessage::PromptClosed { run_id, .. }
+        | RunnerMessage::Done { run_id } => run_id.clone(),
+    };
+
+    if let RunnerMessage::Done { .. } = msg {
+        // Dropping the context frees the run's queue slot
+        abort_result_transfer(&run_id);
+        if let Some(ctx) = take_run(&run_id) {
+            log::info!("Playwright run {} done", run_id);
+            close_run_prompts(app, &run_id, &ctx);
+            if !ctx.terminal_seen {
+                let _ = app.emit("connector-status", serde_json::json!({
+                    "runId": run_id,
+                    "status": { "type": "STOPPED", "message": "Process ended" },
+                    "timestamp": chrono_timestamp()
+                }));
+            }
+        }
+        return;
+    }
+
+    let (platform_id, company, name) = {
+        let Ok(mut runs) = RUNNER_RUNS.lock() else {
+            return;
+        };
+        let Some(ctx) = runs.as_mut().and_then(|runs| runs.get_mut(&run_id)) else {
+            log::debug!("Playwright message for unknown run {}: {:?}", run_id, msg);
+            return;
+        };
+        ctx.last_activity = Instant::now();
+        match &msg {
+            RunnerMessage::Log { message, .. } => ctx.remember_log(message),
+            RunnerMessage::Error { message, .. } => ctx.remember_log(&format!("Error: {}", message)),
+            RunnerMessage::Status { status, .. } => {
+                if status.is_terminal() {
+                    ctx.terminal_seen = true;
+                }
+                ctx.waiting_on_user = matches!(
+                    status.status_type(),
+                    "WAITING_FOR_USER" | "WAITING_LOGIN" | "CONNECT_WEBSITE"
+                );
+            }
+            RunnerMessage::Prompt { prompt_id, kind, options, timeout_ms, .. } => {
+                let prompt = PendingPrompt {
+                    kind: *kind,
+                    options: options.clone(),
+                    expires_at: chrono_timestamp() + *timeout_ms as i64,
+                };
+                ctx.pending_prompts.insert(prompt_id.clone(), prompt);
+            }
+            RunnerMessage::PromptClosed { prompt_id, .. } => {
+                ctx.pending_prompts.remove(prompt_id);
+            }
+            RunnerMessage::Browser { pid, .. } => {
+                log::debug!("Run {} launched browser pid {}", run_id, pid);
+                ctx.browser_pid = Some(*pid);
+                return;
+            }
+            _ => {}
+        }
+        if ctx.result_failed {
+            if let RunnerMessage::Status { .. } = msg {
+                return;
+            }
+        }
+        if ctx.timed_out_at.is_some() {
+            // The run was already reported as timed out; don't let a late status override that
+            if let RunnerMessage::Status { .. }
+            | RunnerMessage::Result { .. }
+            | RunnerMessage::ResultChunk { .. }
+            | RunnerMessage::ResultCommit { .. } = msg
+            {
+                log::debug!("Dropping message for timed-out run {}: {:?}", run_id, msg);
+                return;
+            }
+        }
+        (ctx.platform_id.clone(), ctx.company.clone(), ctx.name.clone())
+    };
+
+    match msg {
+        RunnerMessage::Log { message, .. } => emit_connector_log(app, &run_id, RunLogLevel::Info, &message),
+        RunnerMessage::Status { status, .. } => {
+            let _ = app.emit("connector-status", serde_json::json!({
+                "runId": run_id,
+                "status": status.to_event_value(),
+                "timestamp": chrono_timestamp()
+            }));
+        }
+        RunnerMessage::Result { data, .. } => {
+            match save_run_export(app, &run_id, &platform_id, &company, &name, data) {
+                Ok(saved) => emit_export_complete(app, &run_id, &platform_id, &company, &name, &saved),
+                Err(e) => fail_run_result(app, &run_id, e),
+            }
+        }
+        RunnerMessage::ResultChunk { seq, data, .. } => {
+            // Large results are streamed to the run directory instead of through the event bus
+            let appended = export_run_dir(app, &company, &name, &run_id)
+                .and_then(|run_dir| append_result_chunk(&run_dir, &run_id, seq, &data));
+            if let Err(e) = appended {
+                log::error!("{}", e);
+                emit_connector_log(app, &run_id, RunLogLevel::Error, &format!("Error: {}", e));
+            }
+        }
+        RunnerMessage::ResultCommit { chunks, .. } => {
+            match persist_transferred_result(app, &run_id, &platform_id, &company, &name, chunks) {
+                Ok(saved) => emit_export_complete(app, &run_id, &platform_id, &company, &name, &saved),
+                Err(e) => fail_run_result(app, &run_id, e),
+            }
+        }
+        RunnerMessage::Error { message, .. } => emit_connector_log(app, &run_id, RunLogLevel::Error, &format!("Error: {}", message)),
+        RunnerMessage::Data { key, value, .. } => {
+            // Forward connector data events to frontend
+            let _ = app.emit("connector-data", serde_json::json!({
+                "runId": run_id,
+                "key": key,
+                "value": value,
+                "timestamp": chrono_timestamp()
+            }));
+        }
+        RunnerMessage::NetworkCaptured { key, url, .. } => {
+            log::debug!("Run {} captured network response '{}' from {}", run_id, key, url);
+        }
+        RunnerMessage::Diagnostics { files, .. } => {
+            log::info!("Run {} saved failure diagnostics: {}", run_id, files.join(", "));
+            emit_connector_log(app, &run_id, RunLogLevel::Info, "Saved failure diagnostics for bug reports");
+            let _ = app.emit("connector-diagnostics", serde_json::json!({
+                "runId": run_id,
+                "files": files,
+                "timestamp": chrono_timestamp()
+            }));
+        }
+        RunnerMessage::Prompt { prompt_id, kind, message, options, timeout_ms, .. } => {
+            log::info!("Run {} is asking the user: {}", run_id, message);
+            let _ = app.emit("connector-prompt", serde_json::json!({
+                "runId": run_id,
+                "platformId": platform_id,
+                "name": name,
+                "promptId": prompt_id,
+                "kind": kind,
+                "message": message,
+                "options": options,
+                "timeoutMs": timeout_ms,
+                "expiresAt": chrono_timestamp() + timeout_ms as i64,
+                "timestamp": chrono_timestamp()
+            }));
+        }
+        RunnerMessage::PromptClosed { prompt_id, reason, .. } => {
+            log::info!("Prompt {} of run {} closed: {}", prompt_id, run_id, reason);
+            emit_prompt_closed(app, &run_id, &prompt_id, &reason);
+        }
+        _ => {}
+    }
+}
+
+/// The runner's stdout closed: fail its in-flight runs and restart it unless the app is exiting
+fn handle_runner_exit(app: &AppHandle, generation: u64) {
+    let is_current = RUNNER
+        .lock()
+        .map(|mut guard| {
+            let current = guard.as_ref().is_some_and(|r| r.generation == generation);
+            if current {
+                if let Some(mut runner) = guard.take() {
+                    let _ = runner.child.wait();
+                }
+            }
+            current
+        })
+        .unwrap_or(false);
+    if !is_current {
+        return;
+    }
+
+    let orphaned: Vec<(String, RunContext)> = RUNNER_RUNS
+        .lock()
+        .ok()
+        .and_then(|mut runs| runs.as_mut().map(|r| r.drain().collect()))
+        .unwrap_or_default();
+
+    let shutting_down = RUNNER_SHUTTING_DOWN.lock().map(|flag| *flag).unwrap_or(false);
+    if shutting_down {
+        return;
+    }
+    let protocol_error = RUNNER_PROTOCOL_ERROR.lock().ok().and_then(|e| e.clone());
+
+    log::error!(
+        "Playwright runner (generation {}) exited unexpectedly with {} run(s) in flight",
+        generation,
+        orphaned.len()
+    );
+    for (run_id, ctx) in orphaned {
+        close_run_prompts(app, &run_id, &ctx);
+        abort_result_transfer(&run_id);
+        let message = protocol_error.as_deref().unwrap_or("Playwright runner exited unexpectedly");
+        emit_connector_log(app, &run_id, RunLogLevel::Error, &format!("Error: {}", message));
+        if !ctx.terminal_seen {
+            let status_message = if protocol_error.is_some() {
+                "Playwright runner is incompatible with this app"
+            } else {
+                "Playwright runner crashed"
+            };
+            let _ = app.emit("connector-status", serde_json::json!({
+                "runId": run_id,
+                "status": { "type": "ERROR", "message": status_message },
+                "timestamp": chrono_timestamp()
+            }));
+        }
+    }
+    if protocol_error.is_some() {
+        // Restarting would just fail the handshake again
+        return;
+    }
+
+    // Restart right away so the next run doesn't pay the startup cost, unless it keeps crashing
+    let allow_restart = RUNNER_RESTARTS
+        .lock()
+        .map(|mut restarts| {
+            restarts.retain(|t| t.elapsed() < RESTART_WINDOW);
+            restarts.push(Instant::now());
+            restarts.len() <= MAX_RESTARTS
+        })
+        .unwrap_or(false);
+    if !allow_restart {
+        log::error!("Playwright runner crashed {} times within {:?}; not restarting until the next run", MAX_RESTARTS, RESTART_WINDOW);
+        return;
+    }
+    if let Err(e) = ensure_runner(app) {
+        log::error!("Failed to restart Playwright runner: {}", e);
+    }
+}
+
+/// Kill the runner's whole process group (runner + Chrome children)
+fn kill_runner(runner: &mut RunnerProcess) {
+    #[cfg(unix)]
+    {
+        use crate::commands::server::kill_process_group;
+        kill_process_group(runner.child.id(), libc::SIGTERM);
+    }
+    // Brief wait then force kill
+    for _ in 0..10 {
+        if let Ok(Some(_)) = runner.child.try_wait() {
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    #[cfg(unix)]
+    {
+        use crate::commands::server::kill_process_group;
+        kill_process_group(runner.child.id(), libc::SIGKILL);
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = runner.child.kill();
+    }
+    let _ = runner.child.wait();
+}
+
+/// SIGKILL the runner's process group. The reader thread then sees stdout close,
+/// fails the runs still in flight and respawns the runner.
+fn kill_runner_group() {
+    if let Ok(mut guard) = RUNNER.lock() {
+        if let Some(runner) = guard.as_mut() {
+            #[cfg(unix)]
+            crate::commands::server::kill_process_group(runner.child.id(), libc::SIGKILL);
+            #[cfg(not(unix))]
+            let _ = runner.child.kill();
+        }
+    }
+}
+
+/// SIGKILL one run's browser process group. The runner sees that browser disconnect
+/// and releases the run; other runs keep going.
+fn kill_browser_group(run_id: &str, pid: u32) {
+    log::error!("Killing browser process group {} of run {}", pid, run_id);
+    #[cfg(unix)]
+    crate::commands::server::kill_process_group(pid, libc::SIGKILL);
+}
+
+/// Stop runs that exceeded their max duration or stopped making progress.
+/// A timed-out run is asked to stop; if the runner doesn't release it within
+/// `STOP_GRACE`, only that run's browser process group is killed. The whole runner
+/// is killed only if a run is still held another `STOP_GRACE` after that.
+fn check_run_timeouts(app: &AppHandle) {
+    let now = Instant::now();
+    let mut timed_out: Vec<(String, String, Vec<String>)> = Vec::new();
+    let mut stuck_browsers: Vec<(String, u32)> = Vec::new();
+    let mut unreleased: Vec<String> = Vec::new();
+    {
+        let Ok(mut runs) = RUNNER_RUNS.lock() else {
+            return;
+        };
+        let Some(runs) = runs.as_mut() else {
+            return;
+        };
+        for (run_id, ctx) in runs.iter_mut() {
+            if let Some(at) = ctx.timed_out_at {
+                if now.duration_since(at) < STOP_GRACE {
+                    continue;
+                }
+                match (ctx.browser_killed_at, ctx.browser_pid) {
+                    (None, Some(pid)) => {
+                        log::error!("Timed-out run {} was not released within {:?}", run_id, STOP_GRACE);
+                        ctx.browser_killed_at = Some(now);
+                        stuck_browsers.push((run_id.clone(), pid));
+                    }
+                    (Some(killed_at), _) if now.duration_since(killed_at) < STOP_GRACE => {}
+                    _ => unreleased.push(run_id.clone()),
+                }
+                continue;
+            }
+            if let Some(reason) = ctx.timeout_reason(now) {
+                ctx.timed_out_at = Some(now);
+                ctx.terminal_seen = true;
+                timed_out.push((run_id.clone(), reason, ctx.recent_logs.iter().cloned().collect()));
+            }
+        }
+    }
+
+    for (run_id, reason, last_logs) in timed_out {
+        log::warn!("Run {} stopped by watchdog: {}", run_id, reason);
+        for line in &last_logs {
+            log::warn!("  [{}] {}", run_id, line);
+        }
+        emit_connector_log(app, &run_id, RunLogLevel::Error, &format!("Error: {}", reason));
+        let _ = app.emit("connector-status", serde_json::json!({
+            "runId": run_id,
+            "status": {
+                "type": "ERROR",
+                "message": reason,
+                "data": { "timedOut": true, "lastLogs": last_logs }
+            },
+            "timestamp": chrono_timestamp()
+        }));
+        if let Err(e) = write_runner_command(&RunnerCommand::Stop { run_id: run_id.clone() }) {
+            log::error!("Failed to stop timed-out run {}: {}", run_id, e);
+        }
+    }
+
+    for (run_id, pid) in stuck_browsers {
+        kill_browser_group(&run_id, pid);
+    }
+
+    if unreleased.is_empty() {
+        return;
+    }
+    // Dropping the runs here would free their queue slots while the runner may still be
+    // driving them; restarting the runner releases them for certain
+    for run_id in &unreleased {
+        log::error!("Timed-out run {} is still held after killing its browser", run_id);
+    }
+    log::error!("Killing the Playwright runner to release {} timed-out run(s)", unreleased.len());
+    kill_runner_group();
+}
+
+/// Enforce run timeouts and stall detection. Called once from app setup.
+pub fn start_run_watchdog(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(WATCHDOG_INTERVAL).await;
+            check_run_timeouts(&app);
+        }
+    });
+}
+
+/// Periodically ping the runner while it is alive; restart it if it stops answering.
+/// Called once from app setup.
+pub fn start_runner_health_check(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(HEALTH_CHECK_INTERVAL).await;
+            let alive = RUNNER.lock().map(|guard| guard.is_some()).unwrap_or(false);
+            if !alive {
+                continue;
+            }
+            let ping = |request_id| RunnerCommand::Ping { request_id };
+            if let Err(e) = runner_request(&app, ping, HEALTH_CHECK_TIMEOUT).await {
+                log::error!("Playwright runner health check failed ({}), restarting it", e);
+                kill_runner_group();
+            }
+        }
+    });
+}
+
+/// Stop the Playwright runner on app exit (sync, best-effort).
+/// Uses process group kills so spawned Chrome children are also reaped.
+pub fn cleanup_playwright_processes() {
+    if let Ok(mut flag) = RUNNER_SHUTTING_DOWN.lock() {
+        *flag = true;
+    }
+    let Ok(mut guard) = RUNNER.lock() else {
+        return;
+    };
+    let Some(mut runner) = guard.take() else {
+        return;
+    };
+    log::info!("Shutting down Playwright runner on app exit...");
+    // Ask nicely first so browsers can close their profiles cleanly
+    let _ = writeln!(runner.stdin, "{}", serde_json::json!(RunnerCommand::Quit));
+    let _ = runner.stdin.flush();
+    for _ in 0..20 {
+        if let Ok(Some(_)) = runner.child.try_wait() {
+            log::info!("Playwright runner exited");
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    kill_runner(&mut runner);
+    log::info!("Playwright runner cleaned up");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_limits_catch_timeouts_and_stalls() {
+        let limits = RunLimits::from_settings(&ConnectorSettings {
+            timeout_secs: Some(600),
+            stall_timeout_secs: Some(60),
+            ..Default::default()
+        });
+        let secs = Duration::from_secs;
+
+        assert_eq!(limits.check(secs(300), secs(10), false), None);
+        assert_eq!(limits.check(secs(600), secs(1), false).as_deref(), Some("Timed out after 10 minutes"));
+        assert!(limits.check(secs(300), secs(61), false).unwrap().starts_with("Stalled"));
+        // Waiting on the user isn't a stall, but the overall limit still applies
+        assert_eq!(limits.check(secs(300), secs(200), true), None);
+        assert!(limits.check(secs(900), secs(200), true).is_some());
+
+        let defaults = RunLimits::from_settings(&ConnectorSettings::default());
+        assert_eq!(defaults.max_duration, DEFAULT_RUN_TIMEOUT);
+        assert_eq!(defaults.stall_timeout, DEFAULT_STALL_TIMEOUT);
+    }
+
+    #[test]
+    fn prompt_answers_must_match_the_prompt_kind() {
+        use serde_json::json;
+        let prompt = |kind, options| PendingPrompt { kind, options, expires_at: 0 };
+
+        assert!(prompt(PromptKind::Confirm, None).check_answer(&json!(true)).is_ok());
+        assert!(prompt(PromptKind::Confirm, None).check_answer(&json!("yes")).is_err());
+
+        let choice = prompt(PromptKind::Choice, Some(vec![json!("sms"), json!("email")]));
+        assert!(choice.check_answer(&json!("email")).is_ok());
+        assert!(choice.check_answer(&json!("fax")).is_err());
+
+        assert!(prompt(PromptKind::Code, None).check_answer(&json!("123456")).is_ok());
+        assert!(prompt(PromptKind::Secret, None).check_answer(&json!(123456)).is_err());
+    }
+}
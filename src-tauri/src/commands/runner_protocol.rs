@@ -0,0 +1,413 @@
+use serde::{Deserialize, Serialize};
+
+use super::manifest::ConnectorPermission;
+
+/// Version of the stdin/stdout protocol spoken with the Playwright runner.
+/// Bump on any incompatible change and keep `playwright-runner/index.cjs` in step.
+pub const RUNNER_PROTOCOL_VERSION: u32 = 5;
+
+/// Commands written to the runner's stdin, one JSON object per line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum RunnerCommand {
+    /// First command after spawn; the runner exits if it doesn't speak this version
+    Hello {
+        #[serde(rename = "protocolVersion")]
+        protocol_version: u32,
+    },
+    Run(RunCommand),
+    Stop {
+        #[serde(rename = "runId")]
+        run_id: String,
+    },
+    /// The user's reply to a `prompt`; `cancelled` when they dismissed it
+    PromptAnswer {
+        #[serde(rename = "runId")]
+        run_id: String,
+        #[serde(rename = "promptId")]
+        prompt_id: String,
+        value: serde_json::Value,
+        #[serde(default)]
+        cancelled: bool,
+    },
+    Ping {
+        #[serde(rename = "requestId")]
+        request_id: String,
+    },
+    Test {
+        #[serde(rename = "requestId")]
+        request_id: String,
+    },
+    Quit,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunCommand {
+    #[serde(rename = "runId")]
+    pub run_id: String,
+    #[serde(rename = "connectorPath")]
+    pub connector_path: String,
+    pub url: String,
+    pub headless: bool,
+    /// Reopen headed if the connector reports CONNECT_WEBSITE while headless
+    #[serde(rename = "headedFallback", default)]
+    pub headed_fallback: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scopes: Option<Vec<String>>,
+    #[serde(rename = "timeoutMs", skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    #[serde(rename = "simulateNoChrome", default)]
+    pub simulate_no_chrome: bool,
+    /// Where the runner saves a failure bundle (screenshot, DOM, HAR, console) if the run fails
+    #[serde(rename = "diagnosticsDir", skip_serializing_if = "Option::is_none")]
+    pub diagnostics_dir: Option<String>,
+    /// Hosts the connector may navigate to and fetch from (`*.example.com` also matches
+    /// example.com); empty means the run isn't confined
+    #[serde(rename = "allowedDomains", default)]
+    pub allowed_domains: Vec<String>,
+    /// Capabilities the connector was granted
+    #[serde(default)]
+    pub permissions: Vec<ConnectorPermission>,
+}
+
+/// Messages the runner writes to stdout, one JSON object per line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum RunnerMessage {
+    /// Sent once on startup. Runners predating the handshake omit the version.
+    Ready {
+        #[serde(rename = "protocolVersion", default)]
+        protocol_version: Option<u32>,
+        #[serde(rename = "runnerVersion", default)]
+        runner_version: Option<String>,
+    },
+    Log {
+        #[serde(rename = "runId")]
+        run_id: String,
+        message: String,
+    },
+    Status {
+        #[serde(rename = "runId")]
+        run_id: String,
+        status: RunnerStatus,
+    },
+    /// A whole result in one message (small results only; see `ResultChunk`)
+    Result {
+        #[serde(rename = "runId")]
+        run_id: String,
+        data: serde_json::Value,
+    },
+    /// One piece of the run's JSON-serialized result, numbered from 0
+    ResultChunk {
+        #[serde(rename = "runId")]
+        run_id: String,
+        seq: u64,
+        data: String,
+    },
+    /// All `chunks` result chunks have been sent
+    ResultCommit {
+        #[serde(rename = "runId")]
+        run_id: String,
+        chunks: u64,
+    },
+    Error {
+        #[serde(rename = "runId")]
+        run_id: String,
+        message: String,
+    },
+    Data {
+        #[serde(rename = "runId")]
+        run_id: String,
+        key: String,
+        #[serde(default)]
+        value: serde_json::Value,
+    },
+    NetworkCaptured {
+        #[serde(rename = "runId")]
+        run_id: String,
+        key: String,
+        url: String,
+    },
+    /// A failed run's diagnostics were written to its `diagnosticsDir`
+    Diagnostics {
+        #[serde(rename = "runId")]
+        run_id: String,
+        files: Vec<String>,
+    },
+    /// A run launched its browser; `pid` leads the browser's own process group
+    Browser {
+        #[serde(rename = "runId")]
+        run_id: String,
+        pid: u32,
+    },
+    /// The connector is waiting for the user to answer a question
+    Prompt {
+        #[serde(rename = "runId")]
+        run_id: String,
+        #[serde(rename = "promptId")]
+        prompt_id: String,
+        kind: PromptKind,
+        message: String,
+        #[serde(default)]
+        options: Option<Vec<serde_json::Value>>,
+        #[serde(rename = "timeoutMs")]
+        timeout_ms: u64,
+    },
+    /// A prompt stopped waiting without an answer (timed out, or the run ended)
+    PromptClosed {
+        #[serde(rename = "runId")]
+        run_id: String,
+        #[serde(rename = "promptId")]
+        prompt_id: String,
+        reason: String,
+    },
+    /// The run's browser has been released; sent exactly once per run
+    Done {
+        #[serde(rename = "runId")]
+        run_id: String,
+    },
+    Pong {
+        #[serde(rename = "requestId")]
+        request_id: String,
+        #[serde(rename = "activeRuns", default)]
+        active_runs: usize,
+    },
+    TestResult {
+        #[serde(rename = "requestId")]
+        request_id: Option<String>,
+        data: serde_json::Value,
+    },
+    /// The runner couldn't make sense of a command we sent
+    ProtocolError {
+        #[serde(rename = "runId", default)]
+        run_id: Option<String>,
+        #[serde(rename = "requestId", default)]
+        request_id: Option<String>,
+        message: String,
+    },
+}
+
+impl RunnerMessage {
+    /// The requestId of a reply to `runner_request`, if this is one
+    pub fn request_id(&self) -> Option<&str> {
+        match self {
+            RunnerMessage::Pong { request_id, .. } => Some(request_id),
+            RunnerMessage::TestResult { request_id, .. } | RunnerMessage::ProtocolError { request_id, .. } => {
+                request_id.as_deref()
+            }
+            _ => None,
+        }
+    }
+}
+
+/// How the UI should ask: free text, a masked secret, a short code (2FA),
+/// one of `options`, or yes/no
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PromptKind {
+    Text,
+    Secret,
+    Code,
+    Choice,
+    Confirm,
+}
+
+/// Run status: either a bare string ("RUNNING", "COMPLETE") or a structured object
+/// (`{ type: "COLLECTING", message, phase, count }`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RunnerStatus {
+    Simple(String),
+    Detailed {
+        #[serde(rename = "type")]
+        status_type: String,
+        #[serde(flatten)]
+        details: serde_json::Map<String, serde_json::Value>,
+    },
+}
+
+impl RunnerStatus {
+    pub fn status_type(&self) -> &str {
+        match self {
+            RunnerStatus::Simple(status) => status,
+            RunnerStatus::Detailed { status_type, .. } => status_type,
+        }
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        matches!(self.status_type(), "COMPLETE" | "ERROR" | "STOPPED")
+    }
+
+    /// The status object forwarded to the UI in `connector-status`. A COMPLETE status's
+    /// `data` is dropped: the export is delivered as a result, not over the event bus.
+    pub fn to_event_value(&self) -> serde_json::Value {
+        match self {
+            RunnerStatus::Simple(status) => serde_json::json!({ "type": status }),
+            RunnerStatus::Detailed { status_type, details } => {
+                let mut value = details.clone();
+                if status_type == "COMPLETE" {
+                    value.remove("data");
+                }
+                value.insert("type".to_string(), serde_json::json!(status_type));
+                serde_json::Value::Object(value)
+            }
+        }
+    }
+}
+
+/// A stdout line that isn't a valid runner message
+#[derive(Debug, Clone, PartialEq)]
+pub struct MalformedMessage {
+    /// The runId, if the line was JSON carrying one, so the error can be shown on that run
+    pub run_id: Option<String>,
+    pub error: String,
+}
+
+/// Parse one line of runner stdout
+pub fn parse_runner_message(line: &str) -> Result<RunnerMessage, MalformedMessage> {
+    let value: serde_json::Value = serde_json::from_str(line).map_err(|e| MalformedMessage {
+        run_id: None,
+        error: format!("not JSON ({})", e),
+    })?;
+    let run_id = value.get("runId").and_then(|v| v.as_str()).map(|s| s.to_string());
+    let msg_type = value.get("type").and_then(|v| v.as_str()).unwrap_or("<missing>").to_string();
+    serde_json::from_value(value).map_err(|e| MalformedMessage {
+        run_id,
+        error: format!("invalid '{}' message ({})", msg_type, e),
+    })
+}
+
+/// Whether a runner announcing `version` can be driven by this app
+pub fn check_protocol_version(version: Option<u32>) -> Result<(), String> {
+    match version {
+        Some(v) if v == RUNNER_PROTOCOL_VERSION => Ok(()),
+        Some(v) => Err(format!(
+            "Playwright runner speaks protocol v{}, but this app needs v{}",
+            v, RUNNER_PROTOCOL_VERSION
+        )),
+        None => Err(format!(
+            "Playwright runner is too old (no protocol version); this app needs v{}",
+            RUNNER_PROTOCOL_VERSION
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_runner_messages() {
+        let status = parse_runner_message(r#"{"type":"status","runId":"r1","status":"COMPLETE"}"#).unwrap();
+        match &status {
+            RunnerMessage::Status { run_id, status } => {
+                assert_eq!(run_id, "r1");
+                assert!(status.is_terminal());
+                assert_eq!(status.to_event_value(), serde_json::json!({ "type": "COMPLETE" }));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let detailed = parse_runner_message(
+            r#"{"type":"status","runId":"r1","status":{"type":"COLLECTING","message":"Fetching","count":3}}"#,
+        )
+        .unwrap();
+        let RunnerMessage::Status { status, .. } = detailed else { panic!("expected status") };
+        assert_eq!(status.status_type(), "COLLECTING");
+        assert_eq!(
+            status.to_event_value(),
+            serde_json::json!({ "type": "COLLECTING", "message": "Fetching", "count": 3 })
+        );
+
+        let complete = parse_runner_message(
+            r#"{"type":"status","runId":"r1","status":{"type":"COMPLETE","message":"Done","data":{"items":[1,2]}}}"#,
+        )
+        .unwrap();
+        let RunnerMessage::Status { status, .. } = complete else { panic!("expected status") };
+        assert_eq!(status.to_event_value(), serde_json::json!({ "type": "COMPLETE", "message": "Done" }));
+
+        let ready = parse_runner_message(r#"{"type":"ready","protocolVersion":1}"#).unwrap();
+        assert_eq!(ready, RunnerMessage::Ready { protocol_version: Some(1), runner_version: None });
+
+        let pong = parse_runner_message(r#"{"type":"pong","requestId":"ping-1","activeRuns":2}"#).unwrap();
+        assert_eq!(pong.request_id(), Some("ping-1"));
+
+        let prompt = parse_runner_message(
+            r#"{"type":"prompt","runId":"r1","promptId":"prompt-1","kind":"code","message":"Enter the 2FA code","options":null,"timeoutMs":300000}"#,
+        )
+        .unwrap();
+        let RunnerMessage::Prompt { kind, timeout_ms, .. } = prompt else { panic!("expected prompt") };
+        assert_eq!(kind, PromptKind::Code);
+        assert_eq!(timeout_ms, 300_000);
+
+        let browser = parse_runner_message(r#"{"type":"browser","runId":"r1","pid":4242}"#).unwrap();
+        assert_eq!(browser, RunnerMessage::Browser { run_id: "r1".to_string(), pid: 4242 });
+    }
+
+    #[test]
+    fn reports_malformed_messages() {
+        let not_json = parse_runner_message("Downloading Chromium...").unwrap_err();
+        assert_eq!(not_json.run_id, None);
+
+        // Known type, missing field: the error is attributed to the run
+        let bad = parse_runner_message(r#"{"type":"log","runId":"r1"}"#).unwrap_err();
+        assert_eq!(bad.run_id.as_deref(), Some("r1"));
+        assert!(bad.error.contains("'log'"));
+
+        let unknown = parse_runner_message(r#"{"type":"teleport","runId":"r2"}"#).unwrap_err();
+        assert_eq!(unknown.run_id.as_deref(), Some("r2"));
+    }
+
+    #[test]
+    fn serializes_commands_and_checks_version() {
+        let run = RunnerCommand::Run(RunCommand {
+            run_id: "r1".to_string(),
+            connector_path: "/c/chatgpt.js".to_string(),
+            url: "https://chatgpt.com".to_string(),
+            headless: true,
+            headed_fallback: false,
+            scopes: None,
+            timeout_ms: Some(60_000),
+            diagnostics_dir: None,
+            simulate_no_chrome: false,
+            allowed_domains: vec!["*.chatgpt.com".to_string()],
+            permissions: vec![ConnectorPermission::NetworkCapture],
+        });
+        assert_eq!(
+            serde_json::to_value(&run).unwrap(),
+            serde_json::json!({
+                "type": "run",
+                "runId": "r1",
+                "connectorPath": "/c/chatgpt.js",
+                "url": "https://chatgpt.com",
+                "headless": true,
+                "headedFallback": false,
+                "timeoutMs": 60000,
+                "simulateNoChrome": false,
+                "allowedDomains": ["*.chatgpt.com"],
+                "permissions": ["network-capture"]
+            })
+        );
+        assert_eq!(serde_json::to_value(RunnerCommand::Quit).unwrap(), serde_json::json!({ "type": "quit" }));
+        let answer = RunnerCommand::PromptAnswer {
+            run_id: "r1".to_string(),
+            prompt_id: "prompt-1".to_string(),
+            value: serde_json::json!("123456"),
+            cancelled: false,
+        };
+        assert_eq!(
+            serde_json::to_value(&answer).unwrap(),
+            serde_json::json!({
+                "type": "prompt-answer",
+                "runId": "r1",
+                "promptId": "prompt-1",
+                "value": "123456",
+                "cancelled": false
+            })
+        );
+
+        assert!(check_protocol_version(Some(RUNNER_PROTOCOL_VERSION)).is_ok());
+        assert!(check_protocol_version(Some(RUNNER_PROTOCOL_VERSION + 1)).is_err());
+        assert!(check_protocol_version(None).is_err());
+    }
+}
@@ -0,0 +1,396 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Listener, Manager};
+
+use super::config::{load_app_config, ConnectorSettings, ScheduledRunsPolicy};
+use super::connector::{load_platforms, start_playwright_run, Platform, PlaywrightRunOptions};
+use super::runner::active_playwright_runs;
+use super::file_ops::{lock_file, write_file_atomic_locked};
+use super::manifest::ConnectorRuntime;
+
+/// How often the scheduler checks for due platforms
+const TICK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+/// Give the app time to settle (personal server, frontend listeners) before the first check
+const STARTUP_DELAY: Duration = Duration::from_secs(2 * 60);
+
+const RETRY_BASE_SECS: i64 = 30 * 60;
+const RETRY_MAX_SECS: i64 = 24 * 60 * 60;
+const DEFAULT_IDLE_MINUTES: u64 = 10;
+
+/// Scheduled runs in flight: run id -> platform id
+static SCHEDULED_RUNS: Mutex<BTreeMap<String, String>> = Mutex::new(BTreeMap::new());
+
+/// Per-platform scheduling state, persisted so backoff survives restarts.
+/// Times are unix seconds.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScheduleState {
+    #[serde(rename = "lastSuccessAt")]
+    pub last_success_at: Option<i64>,
+    #[serde(rename = "lastAttemptAt")]
+    pub last_attempt_at: Option<i64>,
+    #[serde(rename = "consecutiveFailures", default)]
+    pub consecutive_failures: u32,
+}
+
+/// Seconds between runs for an `exportFrequency`; `None` for manual / unknown values
+fn frequency_interval_secs(frequency: &str) -> Option<i64> {
+    match frequency {
+        "daily" => Some(24 * 60 * 60),
+        "weekly" => Some(7 * 24 * 60 * 60),
+        "monthly" => Some(30 * 24 * 60 * 60),
+        _ => None,
+    }
+}
+
+/// Delay before retrying after `failures` consecutive failed runs: 30m, 1h, 2h, ... capped at 24h
+fn failure_backoff_secs(failures: u32) -> i64 {
+    RETRY_BASE_SECS
+        .saturating_mul(1i64 << failures.saturating_sub(1).min(16))
+        .min(RETRY_MAX_SECS)
+}
+
+/// When a platform is next due, or `None` if it should not be scheduled.
+/// Platforms never exported successfully are left to the user: they usually need a login.
+fn next_run_at(frequency: &str, last_success_at: Option<i64>, state: &ScheduleState) -> Option<i64> {
+    let interval = frequency_interval_secs(frequency)?;
+    let due = last_success_at? + interval;
+    match (state.consecutive_failures, state.last_attempt_at) {
+        (0, _) | (_, None) => Some(due),
+        (failures, Some(attempt)) => Some(due.max(attempt + failure_backoff_secs(failures))),
+    }
+}
+
+fn get_schedule_state_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?
+        .join("schedule_state.json"))
+}
+
+/// Read-modify-write the persisted schedule state under its file lock
+fn update_schedule_state<T>(
+    app: &AppHandle,
+    f: impl FnOnce(&mut HashMap<String, ScheduleState>) -> T,
+) -> Result<T, String> {
+    let path = get_schedule_state_path(app)?;
+    let _lock = lock_file(&path)?;
+
+    let mut state: HashMap<String, ScheduleState> = fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+
+    let result = f(&mut state);
+    let json = serde_json::to_string_pretty(&state)
+        .map_err(|e| format!("Failed to serialize schedule state: {}", e))?;
+    write_file_atomic_locked(&path, json.as_bytes())?;
+    Ok(result)
+}
+
+/// Latest export timestamp per platform id, from the run history in exported_data
+/// (files are named `platformId_timestamp.json`).
+fn last_exports_by_platform(app: &AppHandle) -> HashMap<String, i64> {
+    let mut latest = HashMap::new();
+    let Ok(exported_dir) = app.path().app_data_dir().map(|d| d.join("exported_data")) else {
+        return latest;
+    };
+
+    for entry in walkdir::WalkDir::new(&exported_dir)
+        .min_depth(4)
+        .max_depth(4)
+        .into_iter()
+        .flatten()
+    {
+        let path = entry.path();
+        if path.extension().map_or(true, |ext| ext != "json") {
+            continue;
+        }
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        if let Some((platform_id, ts)) = stem.rsplit_once('_') {
+            if let Ok(ts) = ts.parse::<i64>() {
+                let slot = latest.entry(platform_id.to_string()).or_insert(ts);
+                *slot = (*slot).max(ts);
+            }
+        }
+    }
+    latest
+}
+
+/// Seconds since the last keyboard / mouse input, if the platform can tell us
+fn system_idle_secs() -> Option<u64> {
+    #[cfg(target_os = "macos")]
+    {
+        let output = std::process::Command::new("ioreg")
+            .args(["-c", "IOHIDSystem", "-d", "4"])
+            .output()
+            .ok()?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let line = stdout.lines().find(|l| l.contains("\"HIDIdleTime\""))?;
+        let nanos: u64 = line.rsplit('=').next()?.trim().parse().ok()?;
+        Some(nanos / 1_000_000_000)
+    }
+    #[cfg(target_os = "linux")]
+    {
+        // xprintidle reports milliseconds; not installed everywhere
+        let output = std::process::Command::new("xprintidle").output().ok()?;
+        let millis: u64 = String::from_utf8_lossy(&output.stdout).trim().parse().ok()?;
+        Some(millis / 1000)
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    {
+        None
+    }
+}
+
+/// Whether the machine runs on mains power, if the platform can tell us
+fn on_ac_power() -> Option<bool> {
+    #[cfg(target_os = "macos")]
+    {
+        let output = std::process::Command::new("pmset").args(["-g", "batt"]).output().ok()?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if stdout.contains("'AC Power'") {
+            Some(true)
+        } else if stdout.contains("'Battery Power'") {
+            Some(false)
+        } else {
+            None
+        }
+    }
+    #[cfg(target_os = "linux")]
+    {
+        let mut saw_mains = false;
+        for entry in fs::read_dir("/sys/class/power_supply").ok()?.flatten() {
+            let kind = fs::read_to_string(entry.path().join("type")).unwrap_or_default();
+            if kind.trim() == "Mains" {
+                saw_mains = true;
+                if fs::read_to_string(entry.path().join("online")).unwrap_or_default().trim() == "1" {
+                    return Some(true);
+                }
+            }
+        }
+        // Desktops often expose no supplies at all
+        if saw_mains {
+            Some(false)
+        } else {
+            None
+        }
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    {
+        None
+    }
+}
+
+/// Check the global policy. Unknown idle / power state never blocks a run.
+fn policy_allows_runs(policy: &ScheduledRunsPolicy) -> Result<(), String> {
+    if !policy.enabled.unwrap_or(true) {
+        return Err("scheduled runs are disabled".to_string());
+    }
+    if policy.only_on_ac_power.unwrap_or(false) && on_ac_power() == Some(false) {
+        return Err("on battery power".to_string());
+    }
+    if policy.only_when_idle.unwrap_or(false) {
+        let required = policy.idle_minutes.unwrap_or(DEFAULT_IDLE_MINUTES) * 60;
+        if let Some(idle) = system_idle_secs() {
+            if idle < required {
+                return Err(format!("user active ({}s idle, need {}s)", idle, required));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Record the outcome of a scheduled run from its terminal `connector-status`
+fn handle_run_status(app: &AppHandle, payload: &str) {
+    let Ok(payload) = serde_json::from_str::<serde_json::Value>(payload) else {
+        return;
+    };
+    let Some(run_id) = payload.get("runId").and_then(|v| v.as_str()) else {
+        return;
+    };
+    let status = payload
+        .get("status")
+        .and_then(|s| s.get("type").and_then(|t| t.as_str()).or_else(|| s.as_str()))
+        .unwrap_or("");
+    if !matches!(status, "COMPLETE" | "ERROR" | "STOPPED") {
+        return;
+    }
+
+    let Some(platform_id) = SCHEDULED_RUNS.lock().ok().and_then(|mut runs| runs.remove(run_id)) else {
+        return;
+    };
+    let succeeded = status == "COMPLETE";
+    let now = chrono::Utc::now().timestamp();
+    let result = update_schedule_state(app, |state| {
+        let entry = state.entry(platform_id.clone()).or_default();
+        if succeeded {
+            entry.last_success_at = Some(now);
+            entry.consecutive_failures = 0;
+        } else {
+            entry.consecutive_failures += 1;
+        }
+        entry.consecutive_failures
+    });
+    match result {
+        Ok(0) => log::info!("Scheduled run {} for {} completed", run_id, platform_id),
+        Ok(failures) => log::warn!(
+            "Scheduled run {} for {} ended with {} ({} consecutive failure(s), retrying in {}m)",
+            run_id,
+            platform_id,
+            status,
+            failures,
+            failure_backoff_secs(failures) / 60
+        ),
+        Err(e) => log::error!("Failed to record scheduled run outcome: {}", e),
+    }
+}
+
+/// Start a headless run for a due platform
+async fn start_scheduled_run(app: &AppHandle, platform: &Platform, settings: ConnectorSettings) -> Result<(), String> {
+    let now = chrono::Utc::now().timestamp();
+    let run_id = format!("{}-{}", platform.id, chrono::Utc::now().timestamp_millis());
+    update_schedule_state(app, |state| {
+        state.entry(platform.id.clone()).or_default().last_attempt_at = Some(now);
+    })?;
+    SCHEDULED_RUNS
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert(run_id.clone(), platform.id.clone());
+
+    let connect_url = platform.connect_url.clone().unwrap_or_default();
+    let _ = app.emit(
+        "scheduled-run-started",
+        serde_json::json!({
+            "runId": run_id,
+            "platformId": platform.id,
+            "filename": platform.filename,
+            "company": platform.company,
+            "name": platform.name,
+            "url": connect_url,
+        }),
+    );
+
+    let result = start_playwright_run(
+        app.clone(),
+        run_id.clone(),
+        platform.id.clone(),
+        platform.filename.clone(),
+        platform.company.clone(),
+        platform.name.clone(),
+        connect_url,
+        // Background runs never pop up a browser window; a run that needs a login fails instead
+        PlaywrightRunOptions {
+            headless: Some(true),
+            headed_fallback: false,
+            ..Default::default()
+        },
+        settings,
+    )
+    .await;
+
+    if let Err(ref e) = result {
+        // Report the failure through the same path as a run that errored later
+        handle_run_status(
+            app,
+            &serde_json::json!({ "runId": run_id, "status": { "type": "ERROR", "message": e } }).to_string(),
+        );
+    }
+    result
+}
+
+/// One scheduler pass: start at most one due platform, one run at a time
+async fn run_due_platforms(app: &AppHandle) {
+    let config = match load_app_config() {
+        Ok(config) => config,
+        Err(e) => {
+            log::warn!("Scheduler: failed to load app config: {}", e);
+            return;
+        }
+    };
+    if let Err(reason) = policy_allows_runs(&config.scheduled_runs) {
+        log::debug!("Scheduler: skipping pass ({})", reason);
+        return;
+    }
+    if active_playwright_runs() > 0 {
+        log::debug!("Scheduler: a connector is already running, skipping pass");
+        return;
+    }
+
+    let platforms = load_platforms(app).platforms;
+    let last_exports = last_exports_by_platform(app);
+    let state = update_schedule_state(app, |state| state.clone()).unwrap_or_default();
+    let now = chrono::Utc::now().timestamp();
+
+    for platform in platforms {
+        if !platform.enabled || platform.runtime != Some(ConnectorRuntime::Playwright) {
+            continue;
+        }
+        let Some(frequency) = platform.export_frequency.as_deref() else {
+            continue;
+        };
+        let platform_state = state.get(&platform.id).cloned().unwrap_or_default();
+        let last_success = platform_state
+            .last_success_at
+            .max(last_exports.get(&platform.id).copied());
+        let Some(due_at) = next_run_at(frequency, last_success, &platform_state) else {
+            continue;
+        };
+        if due_at > now {
+            continue;
+        }
+
+        log::info!("Scheduler: {} is due ({} export), starting background run", platform.id, frequency);
+        let settings = config.connector_settings(&platform.id);
+        if let Err(e) = start_scheduled_run(app, &platform, settings).await {
+            log::error!("Scheduler: failed to start run for {}: {}", platform.id, e);
+        }
+        return;
+    }
+}
+
+/// Start the background scheduler. Called once from app setup.
+pub fn start_scheduler(app: AppHandle) {
+    let app_for_status = app.clone();
+    app.listen("connector-status", move |event| {
+        handle_run_status(&app_for_status, event.payload());
+    });
+
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(STARTUP_DELAY).await;
+        loop {
+            run_due_platforms(&app).await;
+            tokio::time::sleep(TICK_INTERVAL).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_run_at_follows_frequency_and_backs_off_after_failures() {
+        let day = 24 * 60 * 60;
+        let fresh = ScheduleState::default();
+
+        assert_eq!(next_run_at("daily", Some(1_000), &fresh), Some(1_000 + day));
+        assert_eq!(next_run_at("manual", Some(1_000), &fresh), None);
+        assert_eq!(next_run_at("daily", None, &fresh), None);
+
+        let failing = ScheduleState {
+            last_success_at: Some(1_000),
+            last_attempt_at: Some(1_000 + day),
+            consecutive_failures: 3,
+        };
+        assert_eq!(
+            next_run_at("daily", Some(1_000), &failing),
+            Some(1_000 + day + 2 * 60 * 60)
+        );
+        assert_eq!(failure_backoff_secs(20), RETRY_MAX_SECS);
+    }
+}
@@ -389,6 +389,7 @@ pub async fn start_personal_server(
                             "ready" => {
                                 let port = msg.get("port").and_then(|p| p.as_u64()).map(|p| p as u16);
                                 log::info!("Personal server ready on port {:?}", port);
+                                super::sync_queue::wake_sync_queue(&app_handle);
                                 let _ = app_handle.emit(
                                     "personal-server-ready",
                                     serde_json::json!({ "port": port }),
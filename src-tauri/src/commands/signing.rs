@@ -0,0 +1,91 @@
+use minisign_verify::{PublicKey, Signature};
+
+/// Minisign public keys of the connector publisher. A new key must sign the published
+/// registry before a release that trusts only it ships.
+pub const PUBLISHER_KEYS: &[&str] = &["RWRikRaRRu9KW8UyJYd1S2JJ7ql8Nic5LLvxiCQXGygaeV3ctTWCjAVK"];
+
+/// Publisher keys trusted to sign the connector registry and its packages: `PUBLISHER_KEYS`
+/// plus any added at build time with `DATACONNECT_CONNECTOR_PUBLIC_KEYS` (comma-separated),
+/// e.g. while rotating keys.
+pub fn pinned_publisher_keys() -> Vec<&'static str> {
+    let extra_keys = option_env!("DATACONNECT_CONNECTOR_PUBLIC_KEYS")
+        .into_iter()
+        .flat_map(|keys| keys.split(','))
+        .map(str::trim)
+        .filter(|k| !k.is_empty());
+    PUBLISHER_KEYS.iter().copied().chain(extra_keys).collect()
+}
+
+/// Parse a minisign public key (the base64 line of a `.pub` file)
+pub fn parse_public_key(key: &str) -> Result<PublicKey, String> {
+    PublicKey::from_base64(key.trim()).map_err(|e| format!("invalid minisign public key: {}", e))
+}
+
+/// URL of the detached minisign signature published next to `url`
+pub fn signature_url(url: &str) -> String {
+    format!("{}.minisig", url)
+}
+
+/// Check `data` against a minisign signature (`.minisig` contents) made by one of `keys`
+pub fn verify_signature(data: &[u8], signature: &str, keys: &[&str]) -> Result<(), String> {
+    if keys.is_empty() {
+        return Err("no publisher keys are pinned in this build".to_string());
+    }
+    let signature =
+        Signature::decode(signature).map_err(|e| format!("invalid signature: {}", e))?;
+
+    let mut last_error = None;
+    for key in keys {
+        let public_key = match parse_public_key(key) {
+            Ok(public_key) => public_key,
+            Err(e) => {
+                log::warn!("Skipping invalid publisher key {}: {}", key, e);
+                continue;
+            }
+        };
+        // Only prehashed signatures (minisign's default): legacy ones sign the raw file
+        match public_key.verify(data, &signature, false) {
+            Ok(()) => return Ok(()),
+            Err(e) => last_error = Some(e),
+        }
+    }
+
+    Err(match last_error {
+        Some(e) => format!("not signed by a trusted publisher key ({})", e),
+        None => "none of the pinned publisher keys are valid".to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_KEY: &str = "RWS8g44f99AbBvdTxpB+200eDRTZWTH+3CvsL3mbyd7niBWRoeuTRY32";
+    const OTHER_KEY: &str = "RWQ0q6t9J7dV8nfQS5Z/gsoy4DXcOrFfPBkjSvgyarr3bjUopBBg1hqZ";
+    const DATA: &[u8] = br#"{"id":"test-connector"}"#;
+    const SIGNATURE: &str = "untrusted comment: signature from minisign secret key
+RUS8g44f99AbBt/OwfZqa4seOBTn/VjsTSWmV2knF6Va2uDQ9VgL5n3oC7VNPBqV4vYZ/qsSAzcbGg92bw5iKSA/We5r2r+BPA4=
+trusted comment: timestamp:1760000000\tfile:test-connector.json
+S/kc3Oqn/ldODPNnDdGuPeRfeDRhwI6FVyn781a6Cd8W2UOMcj0CzHyCt3oXNrPpbM+Bgqq40OUStJB2uCNjAg==
+";
+
+    #[test]
+    fn accepts_only_signatures_from_pinned_keys() {
+        assert!(verify_signature(DATA, SIGNATURE, &[TEST_KEY]).is_ok());
+        assert!(verify_signature(DATA, SIGNATURE, &[OTHER_KEY, TEST_KEY]).is_ok());
+
+        assert!(verify_signature(br#"{"id":"evil-connector"}"#, SIGNATURE, &[TEST_KEY]).is_err());
+        assert!(verify_signature(DATA, SIGNATURE, &[OTHER_KEY]).is_err());
+        assert!(verify_signature(DATA, SIGNATURE, &[]).is_err());
+        assert!(verify_signature(DATA, "not a signature", &[TEST_KEY]).is_err());
+    }
+
+    #[test]
+    fn builds_ship_with_valid_publisher_keys() {
+        assert!(!PUBLISHER_KEYS.is_empty());
+        for key in PUBLISHER_KEYS {
+            assert!(parse_public_key(key).is_ok(), "invalid publisher key {}", key);
+        }
+        assert!(pinned_publisher_keys().starts_with(PUBLISHER_KEYS));
+    }
+}
@@ -0,0 +1,572 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::connector::chrono_timestamp;
+use super::file_ops::{
+    lock_file, mark_run_export_synced, read_export_json, resolve_run_export_json,
+    summarize_export_content, synced_content_archive_path, write_file_atomic_locked,
+};
+use super::server::{PERSONAL_SERVER_DEV_TOKEN, PERSONAL_SERVER_PORT};
+
+/// Top-level export keys that are never treated as ingest scopes
+const METADATA_KEYS: [&str; 4] = ["exportSummary", "timestamp", "version", "platform"];
+
+/// How long the worker sleeps when nothing is due (it is also woken on enqueue / server ready)
+const IDLE_POLL: Duration = Duration::from_secs(60);
+
+const RETRY_BASE_MS: u64 = 5_000;
+const RETRY_MAX_MS: u64 = 10 * 60 * 1000;
+
+/// Wakes the sync worker when new work arrives or the personal server comes up
+static SYNC_QUEUE_WAKE: tokio::sync::Notify = tokio::sync::Notify::const_new();
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncQueueEntry {
+    #[serde(rename = "runId")]
+    pub run_id: String,
+    #[serde(rename = "exportPath")]
+    pub export_path: String,
+    #[serde(rename = "platformId")]
+    pub platform_id: String,
+    /// Fallback scope for exports without scoped (`source.kind`) keys
+    pub scope: Option<String>,
+    #[serde(rename = "itemsExported")]
+    pub items_exported: Option<i64>,
+    #[serde(rename = "itemLabel")]
+    pub item_label: Option<String>,
+    /// Scopes already accepted by the server, so a retry doesn't re-upload them
+    #[serde(rename = "ingestedScopes", default)]
+    pub ingested_scopes: Vec<String>,
+    #[serde(default)]
+    pub attempts: u32,
+    /// Unix millis; the entry is skipped until then
+    #[serde(rename = "nextAttemptAt", default)]
+    pub next_attempt_at: i64,
+    #[serde(rename = "lastError")]
+    pub last_error: Option<String>,
+    /// Set when the server rejected the data; cleared by `retry_export_sync`
+    #[serde(default)]
+    pub failed: bool,
+}
+
+struct SyncFailure {
+    message: String,
+    retryable: bool,
+}
+
+/// How a queued run left the queue
+enum SyncOutcome {
+    /// Every scope was accepted and the export is marked synced
+    Synced,
+    /// The export is gone from disk; nothing was delivered
+    Dropped,
+}
+
+fn get_sync_queue_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?
+        .join("sync_queue.json"))
+}
+
+/// Read-modify-write the persisted queue under its file lock.
+fn update_queue<T>(
+    app: &AppHandle,
+    f: impl FnOnce(&mut Vec<SyncQueueEntry>) -> T,
+) -> Result<T, String> {
+    let path = get_sync_queue_path(app)?;
+    let _lock = lock_file(&path)?;
+
+    let mut queue: Vec<SyncQueueEntry> = if path.exists() {
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read sync queue: {}", e))?;
+        serde_json::from_str(&content).unwrap_or_else(|e| {
+            log::error!("Corrupt sync queue {:?}, starting empty: {}", path, e);
+            Vec::new()
+        })
+    } else {
+        Vec::new()
+    };
+
+    let before = serde_json::to_string_pretty(&queue).unwrap_or_default();
+    let result = f(&mut queue);
+    let after = serde_json::to_string_pretty(&queue)
+        .map_err(|e| format!("Failed to serialize sync queue: {}", e))?;
+    // Skip the write for read-only passes
+    if after != before {
+        write_file_atomic_locked(&path, after.as_bytes())?;
+    }
+    Ok(result)
+}
+
+/// Exponential backoff: 5s, 10s, 20s, ... capped at 10 minutes.
+fn retry_delay_ms(attempts: u32) -> u64 {
+    RETRY_BASE_MS
+        .saturating_mul(1u64 << attempts.saturating_sub(1).min(16))
+        .min(RETRY_MAX_MS)
+}
+
+/// Split export content into `(scope, payload)` uploads. Scoped keys (containing a dot)
+/// are ingested individually; old-format exports go to `fallback_scope` as a whole.
+fn scope_payloads(
+    content: &serde_json::Value,
+    fallback_scope: Option<&str>,
+) -> Vec<(String, serde_json::Value)> {
+    let scoped: Vec<(String, serde_json::Value)> = content
+        .as_object()
+        .map(|obj| {
+            obj.iter()
+                .filter(|(key, _)| key.contains('.') && !METADATA_KEYS.contains(&key.as_str()))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if !scoped.is_empty() {
+        return scoped;
+    }
+    match fallback_scope {
+        Some(scope) => vec![(scope.to_string(), content.clone())],
+        None => Vec::new(),
+    }
+}
+
+fn emit_sync_progress(app: &AppHandle, entry: &SyncQueueEntry, status: &str, scopes_total: usize) {
+    let _ = app.emit(
+        "sync-progress",
+        serde_json::json!({
+            "runId": entry.run_id,
+            "platformId": entry.platform_id,
+            "status": status,
+            "attempts": entry.attempts,
+            "scopesDone": entry.ingested_scopes.len(),
+            "scopesTotal": scopes_total,
+            "nextAttemptAt": entry.next_attempt_at,
+            "error": entry.last_error,
+        }),
+    );
+}
+
+/// Add (or refresh) a run in the persistent sync queue and wake the worker.
+fn enqueue_entry(app: &AppHandle, entry: SyncQueueEntry) -> Result<(), String> {
+    update_queue(app, |queue| {
+        if let Some(existing) = queue.iter_mut().find(|e| e.run_id == entry.run_id) {
+            existing.export_path = entry.export_path;
+            existing.scope = entry.scope.or(existing.scope.take());
+            existing.items_exported = entry.items_exported.or(existing.items_exported);
+            existing.item_label = entry.item_label.or(existing.item_label.take());
+        } else {
+            queue.push(entry);
+        }
+    })?;
+    SYNC_QUEUE_WAKE.notify_one();
+    Ok(())
+}
+
+/// Queue a run's export for delivery to the personal server. Delivery happens in the
+/// background and is retried until it succeeds; progress is reported via `sync-progress`.
+#[tauri::command]
+pub async fn enqueue_export_sync(
+    app: AppHandle,
+    run_id: String,
+    export_path: String,
+    platform_id: String,
+    scope: Option<String>,
+    items_exported: Option<i64>,
+    item_label: Option<String>,
+) -> Result<(), String> {
+    log::info!("Queueing run {} for personal server sync", run_id);
+    enqueue_entry(
+        &app,
+        SyncQueueEntry {
+            run_id,
+            export_path,
+            platform_id,
+            scope,
+            items_exported,
+            item_label,
+            ingested_scopes: Vec::new(),
+            attempts: 0,
+            next_attempt_at: 0,
+            last_error: None,
+            failed: false,
+        },
+    )
+}
+
+/// List runs still waiting to be delivered to the personal server
+#[tauri::command]
+pub async fn get_sync_queue(app: AppHandle) -> Result<Vec<SyncQueueEntry>, String> {
+    update_queue(&app, |queue| queue.clone())
+}
+
+/// Retry queued runs immediately, including ones the server previously rejected.
+/// With no `run_id`, every queued run is retried.
+#[tauri::command]
+pub async fn retry_export_sync(app: AppHandle, run_id: Option<String>) -> Result<(), String> {
+    update_queue(&app, |queue| {
+        for entry in queue
+            .iter_mut()
+            .filter(|e| run_id.as_ref().map_or(true, |id| &e.run_id == id))
+        {
+            entry.failed = false;
+            entry.next_attempt_at = 0;
+        }
+    })?;
+    SYNC_QUEUE_WAKE.notify_one();
+    Ok(())
+}
+
+/// Reset retry backoff after the personal server (re)starts, so queued runs go out right away.
+pub fn wake_sync_queue(app: &AppHandle) {
+    if let Err(e) = update_queue(app, |queue| {
+        for entry in queue.iter_mut().filter(|e| !e.failed) {
+            entry.next_attempt_at = 0;
+        }
+    }) {
+        log::warn!("Failed to reset sync queue backoff: {}", e);
+    }
+    SYNC_QUEUE_WAKE.notify_one();
+}
+
+/// Queue exports left unsynced by a previous session (e.g. the app quit mid-retry, or the
+/// export predates the queue). Only exports whose scopes can be derived are picked up.
+/// Reads export files, so run it off the async runtime.
+fn enqueue_unsynced_exports(app: &AppHandle) -> Result<(), String> {
+    let exported_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?
+        .join("exported_data");
+    if !exported_dir.exists() {
+        return Ok(());
+    }
+
+    let queued: Vec<String> = update_queue(app, |queue| queue.iter().map(|e| e.run_id.clone()).collect())?;
+    let mut found = Vec::new();
+
+    for company in fs::read_dir(&exported_dir).map_err(|e| e.to_string())?.flatten() {
+        for platform in fs::read_dir(company.path()).into_iter().flatten().flatten() {
+            for run in fs::read_dir(platform.path()).into_iter().flatten().flatten() {
+                let run_id = run.file_name().to_string_lossy().to_string();
+                if !run.path().is_dir() || queued.contains(&run_id) {
+                    continue;
+                }
+                let export_path = run.path().to_string_lossy().to_string();
+                let Ok(Some(json_path)) = resolve_run_export_json(app, &export_path) else {
+                    continue;
+                };
+                // Synced exports whose content was archived are skipped without parsing them
+                if synced_content_archive_path(&json_path).exists() {
+                    continue;
+                }
+                let Ok(data) = read_export_json(&json_path) else {
+                    continue;
+                };
+                if data.get("syncedToPersonalServer").and_then(|v| v.as_bool()).unwrap_or(false) {
+                    continue;
+                }
+                let content = data.get("content").cloned().unwrap_or(serde_json::Value::Null);
+                let scope = data.get("scope").and_then(|v| v.as_str()).map(|s| s.to_string());
+                if scope_payloads(&content, scope.as_deref()).is_empty() {
+                    continue;
+                }
+
+                // Filename format: platformId_timestamp.json
+                let stem = json_path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+                let platform_id = stem.rsplit_once('_').map(|(id, _)| id.to_string()).unwrap_or(stem);
+                let (items_exported, item_label) = summarize_export_content(&data);
+
+                found.push(SyncQueueEntry {
+                    run_id,
+                    export_path,
+                    platform_id,
+                    scope,
+                    items_exported,
+                    item_label,
+                    ingested_scopes: Vec::new(),
+                    attempts: 0,
+                    next_attempt_at: 0,
+                    last_error: None,
+                    failed: false,
+                });
+            }
+        }
+    }
+
+    if !found.is_empty() {
+        log::info!("Queueing {} unsynced export(s) from a previous session", found.len());
+        for entry in found {
+            enqueue_entry(app, entry)?;
+        }
+    }
+    Ok(())
+}
+
+/// Upload every remaining scope of a queued run, then mark the export as synced.
+async fn sync_entry(
+    app: &AppHandle,
+    client: &reqwest::Client,
+    port: u16,
+    token: Option<&str>,
+    entry: &mut SyncQueueEntry,
+) -> Result<SyncOutcome, SyncFailure> {
+    let permanent = |message: String| SyncFailure { message, retryable: false };
+
+    let Some(json_path) = resolve_run_export_json(app, &entry.export_path).map_err(permanent)? else {
+        log::info!("Export for run {} was removed, dropping it from the sync queue", entry.run_id);
+        return Ok(SyncOutcome::Dropped);
+    };
+    let data = read_export_json(&json_path).map_err(permanent)?;
+    if data.get("syncedToPersonalServer").and_then(|v| v.as_bool()).unwrap_or(false) {
+        return Ok(SyncOutcome::Synced);
+    }
+
+    let content = data.get("content").cloned().unwrap_or(serde_json::Value::Null);
+    let payloads = scope_payloads(&content, entry.scope.as_deref());
+    if payloads.is_empty() {
+        return Err(permanent(format!("No ingest scope for platform {}", entry.platform_id)));
+    }
+
+    emit_sync_progress(app, entry, "uploading", payloads.len());
+    for (scope, payload) in &payloads {
+        if entry.ingested_scopes.contains(scope) {
+            continue;
+        }
+        let mut request = client
+            .post(format!("http://localhost:{}/v1/data/{}", port, scope))
+            .json(payload);
+        if let Some(token) = token {
+            request = request.bearer_auth(token);
+        }
+        let response = request.send().await.map_err(|e| SyncFailure {
+            message: format!("Ingest request for {} failed: {}", scope, e),
+            retryable: true,
+        })?;
+        let status = response.status();
+        if !status.is_success() {
+            // Auth / validation errors won't fix themselves; server errors might
+            return Err(SyncFailure {
+                message: format!("Ingest failed for {}: {}", scope, status),
+                retryable: status.is_server_error()
+                    || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                    || status == reqwest::StatusCode::UNAUTHORIZED,
+            });
+        }
+        entry.ingested_scopes.push(scope.clone());
+        emit_sync_progress(app, entry, "uploading", payloads.len());
+    }
+
+    let (items_exported, item_label) = match (entry.items_exported, entry.item_label.clone()) {
+        (None, None) => summarize_export_content(&data),
+        known => known,
+    };
+    let scope = entry.scope.clone().unwrap_or_else(|| entry.ingested_scopes.join(","));
+    mark_run_export_synced(app, &entry.run_id, &entry.export_path, items_exported, item_label, Some(scope))
+        .map_err(|message| SyncFailure { message, retryable: true })?;
+    Ok(SyncOutcome::Synced)
+}
+
+/// Drop a synced run from the queue, unless it was re-enqueued with another export meanwhile
+fn remove_synced_entry(queue: &mut Vec<SyncQueueEntry>, synced: &SyncQueueEntry) {
+    queue.retain(|e| e.run_id != synced.run_id || e.export_path != synced.export_path);
+}
+
+/// Copy the outcome of a failed pass onto the stored entry. Only the fields the pass
+/// changed are written, so edits made while it was uploading (a re-enqueue, a retry) survive.
+fn record_failed_attempt(queue: &mut [SyncQueueEntry], attempt: &SyncQueueEntry) {
+    let Some(existing) = queue.iter_mut().find(|e| e.run_id == attempt.run_id) else {
+        return;
+    };
+    if existing.export_path != attempt.export_path {
+        // Re-enqueued with a new export; this attempt says nothing about it
+        return;
+    }
+    existing.attempts = attempt.attempts;
+    existing.last_error = attempt.last_error.clone();
+    existing.next_attempt_at = attempt.next_attempt_at;
+    existing.failed = attempt.failed;
+    for scope in &attempt.ingested_scopes {
+        if !existing.ingested_scopes.contains(scope) {
+            existing.ingested_scopes.push(scope.clone());
+        }
+    }
+}
+
+/// Process every due entry once. Returns how long to sleep before the next pass.
+async fn process_due_entries(app: &AppHandle, client: &reqwest::Client) -> Duration {
+    let port = PERSONAL_SERVER_PORT.lock().ok().and_then(|guard| *guard);
+    let Some(port) = port else {
+        // Server isn't running; `wake_sync_queue` is called once it is ready
+        return IDLE_POLL;
+    };
+    let token = PERSONAL_SERVER_DEV_TOKEN.lock().ok().and_then(|guard| guard.clone());
+
+    let entries = match update_queue(app, |queue| queue.clone()) {
+        Ok(entries) => entries,
+        Err(e) => {
+            log::error!("Failed to load sync queue: {}", e);
+            return IDLE_POLL;
+        }
+    };
+
+    let mut next_due: Option<i64> = None;
+    for mut entry in entries {
+        if entry.failed {
+            continue;
+        }
+        if entry.next_attempt_at > chrono_timestamp() {
+            next_due = Some(next_due.map_or(entry.next_attempt_at, |t| t.min(entry.next_attempt_at)));
+            continue;
+        }
+
+        match sync_entry(app, client, port, token.as_deref(), &mut entry).await {
+            Ok(SyncOutcome::Synced) => {
+                log::info!("Synced run {} to personal server", entry.run_id);
+                entry.last_error = None;
+                let _ = update_queue(app, |queue| remove_synced_entry(queue, &entry));
+                emit_sync_progress(app, &entry, "synced", entry.ingested_scopes.len());
+            }
+            Ok(SyncOutcome::Dropped) => {
+                entry.last_error = Some("Export was removed before it was synced".to_string());
+                let _ = update_queue(app, |queue| queue.retain(|e| e.run_id != entry.run_id));
+                emit_sync_progress(app, &entry, "removed", 0);
+            }
+            Err(failure) => {
+                entry.attempts += 1;
+                entry.last_error = Some(failure.message.clone());
+                let status = if failure.retryable {
+                    entry.next_attempt_at = chrono_timestamp() + retry_delay_ms(entry.attempts) as i64;
+                    next_due = Some(next_due.map_or(entry.next_attempt_at, |t| t.min(entry.next_attempt_at)));
+                    log::warn!(
+                        "Sync of run {} failed (attempt {}), retrying in {}s: {}",
+                        entry.run_id,
+                        entry.attempts,
+                        retry_delay_ms(entry.attempts) / 1000,
+                        failure.message
+                    );
+                    "retrying"
+                } else {
+                    entry.failed = true;
+                    log::error!("Sync of run {} failed permanently: {}", entry.run_id, failure.message);
+                    "failed"
+                };
+                let _ = update_queue(app, |queue| record_failed_attempt(queue, &entry));
+                emit_sync_progress(app, &entry, status, 0);
+            }
+        }
+    }
+
+    next_due
+        .map(|t| Duration::from_millis((t - chrono_timestamp()).max(0) as u64).min(IDLE_POLL))
+        .unwrap_or(IDLE_POLL)
+}
+
+/// Start the background worker that drains the sync queue. Called once from app setup.
+pub fn start_sync_worker(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let scan_app = app.clone();
+        match tauri::async_runtime::spawn_blocking(move || enqueue_unsynced_exports(&scan_app)).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => log::warn!("Failed to scan for unsynced exports: {}", e),
+            Err(e) => log::warn!("Unsynced export scan panicked: {}", e),
+        }
+
+        let client = match reqwest::Client::builder().timeout(Duration::from_secs(120)).build() {
+            Ok(client) => client,
+            Err(e) => {
+                log::error!("Failed to create sync HTTP client: {}", e);
+                return;
+            }
+        };
+
+        loop {
+            let sleep_for = process_due_entries(&app, &client).await;
+            tokio::select! {
+                _ = SYNC_QUEUE_WAKE.notified() => {}
+                _ = tokio::time::sleep(sleep_for) => {}
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_delay_grows_exponentially_and_caps() {
+        assert_eq!(retry_delay_ms(1), 5_000);
+        assert_eq!(retry_delay_ms(2), 10_000);
+        assert_eq!(retry_delay_ms(4), 40_000);
+        assert_eq!(retry_delay_ms(50), RETRY_MAX_MS);
+    }
+
+    #[test]
+    fn scope_payloads_prefers_scoped_keys_over_fallback() {
+        let scoped = serde_json::json!({
+            "platform": "chatgpt",
+            "exportSummary": { "count": 1 },
+            "chatgpt.conversations": [1, 2],
+            "chatgpt.memories": []
+        });
+        let mut scopes: Vec<String> = scope_payloads(&scoped, Some("chatgpt.conversations"))
+            .into_iter()
+            .map(|(scope, _)| scope)
+            .collect();
+        scopes.sort();
+        assert_eq!(scopes, vec!["chatgpt.conversations", "chatgpt.memories"]);
+
+        let legacy = serde_json::json!({ "conversations": [] });
+        let payloads = scope_payloads(&legacy, Some("chatgpt.conversations"));
+        assert_eq!(payloads, vec![("chatgpt.conversations".to_string(), legacy.clone())]);
+        assert!(scope_payloads(&legacy, None).is_empty());
+    }
+
+    fn entry(run_id: &str, export_path: &str) -> SyncQueueEntry {
+        SyncQueueEntry {
+            run_id: run_id.to_string(),
+            export_path: export_path.to_string(),
+            platform_id: "chatgpt".to_string(),
+            scope: None,
+            items_exported: None,
+            item_label: None,
+            ingested_scopes: Vec::new(),
+            attempts: 0,
+            next_attempt_at: 0,
+            last_error: None,
+            failed: false,
+        }
+    }
+
+    #[test]
+    fn failed_attempt_only_updates_retry_fields() {
+        let mut stored = entry("run-1", "/exports/run-1");
+        stored.scope = Some("chatgpt.conversations".to_string());
+        stored.ingested_scopes = vec!["chatgpt.memories".to_string()];
+        let mut queue = vec![stored];
+
+        let mut attempt = entry("run-1", "/exports/run-1");
+        attempt.attempts = 2;
+        attempt.last_error = Some("Ingest failed".to_string());
+        attempt.next_attempt_at = 42;
+        attempt.ingested_scopes = vec!["chatgpt.conversations".to_string()];
+        record_failed_attempt(&mut queue, &attempt);
+
+        assert_eq!(queue[0].attempts, 2);
+        assert_eq!(queue[0].next_attempt_at, 42);
+        assert_eq!(queue[0].scope.as_deref(), Some("chatgpt.conversations"));
+        assert_eq!(queue[0].ingested_scopes, vec!["chatgpt.memories", "chatgpt.conversations"]);
+
+        // A run re-enqueued with a new export keeps its fresh state
+        let mut queue = vec![entry("run-1", "/exports/run-1-rerun")];
+        record_failed_attempt(&mut queue, &attempt);
+        assert_eq!(queue[0].attempts, 0);
+        remove_synced_entry(&mut queue, &attempt);
+        assert_eq!(queue.len(), 1);
+    }
+}
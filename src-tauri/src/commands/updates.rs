@@ -5,6 +5,10 @@ use std::fs;
 use std::path::PathBuf;
 use tauri::{AppHandle, Manager};
 
+use super::config::{load_app_config, load_connector_settings, RegistrySource};
+use super::manifest::{ConnectorGrant, ConnectorManifest};
+use super::signing::{pinned_publisher_keys, signature_url, verify_signature};
+
 const DEFAULT_REGISTRY_URL: &str =
     "https://raw.githubusercontent.com/vana-com/data-connectors/main/registry.json";
 
@@ -58,6 +62,18 @@ pub struct ConnectorUpdateInfo {
     pub has_update: bool,
     #[serde(rename = "isNew")]
     pub is_new: bool,
+    /// Registry the latest version comes from
+    #[serde(rename = "registryUrl")]
+    pub registry_url: String,
+}
+
+/// A connector offered by one of the configured registries
+#[derive(Debug, Clone)]
+struct RegistryEntry {
+    connector: RegistryConnector,
+    /// Absolute URL the connector's files are relative to
+    base_url: String,
+    source: RegistrySource,
 }
 
 /// Local connector metadata (read from JSON files)
@@ -198,22 +214,24 @@ fn verify_checksum(data: &[u8], expected: &str) -> bool {
     actual == expected
 }
 
-/// Cache path for registry
-fn get_registry_cache_path() -> Option<PathBuf> {
+/// Cache path for a registry, keyed by its URL
+fn get_registry_cache_path(url: &str) -> Option<PathBuf> {
     let home = std::env::var("HOME")
         .or_else(|_| std::env::var("USERPROFILE"))
         .ok()?;
+    let key = format!("{:x}", Sha256::digest(url.as_bytes()));
     Some(
         PathBuf::from(home)
             .join(".dataconnect")
             .join("cache")
-            .join("registry.json"),
+            .join("registries")
+            .join(format!("{}.json", &key[..16])),
     )
 }
 
-/// Load cached registry if available and not stale
-fn load_cached_registry() -> Option<Registry> {
-    let cache_path = get_registry_cache_path()?;
+/// Load a cached registry if available, not stale and still validly signed
+fn load_cached_registry(url: &str, keys: &[&str]) -> Option<Vec<u8>> {
+    let cache_path = get_registry_cache_path(url)?;
     if !cache_path.exists() {
         return None;
     }
@@ -230,13 +248,19 @@ fn load_cached_registry() -> Option<Registry> {
         return None;
     }
 
-    let content = fs::read_to_string(&cache_path).ok()?;
-    serde_json::from_str(&content).ok()
+    // The cache keeps the registry exactly as signed, so it's checked like a fresh download
+    let content = fs::read(&cache_path).ok()?;
+    let signature = fs::read_to_string(signature_url(&cache_path.to_string_lossy())).ok()?;
+    if let Err(e) = verify_signature(&content, &signature, keys) {
+        log::warn!("Ignoring cached registry {}: {}", url, e);
+        return None;
+    }
+    Some(content)
 }
 
-/// Save registry to cache
-fn save_registry_cache(registry: &Registry) -> Result<(), String> {
-    let cache_path = get_registry_cache_path()
+/// Save a signed registry and its signature to cache
+fn save_registry_cache(url: &str, content: &[u8], signature: &str) -> Result<(), String> {
+    let cache_path = get_registry_cache_path(url)
         .ok_or("Could not determine cache path")?;
 
     // Create cache directory if needed
@@ -245,48 +269,181 @@ fn save_registry_cache(registry: &Registry) -> Result<(), String> {
             .map_err(|e| format!("Failed to create cache directory: {}", e))?;
     }
 
-    let content = serde_json::to_string_pretty(registry)
-        .map_err(|e| format!("Failed to serialize registry: {}", e))?;
-
     fs::write(&cache_path, content)
         .map_err(|e| format!("Failed to write cache: {}", e))?;
+    fs::write(signature_url(&cache_path.to_string_lossy()), signature)
+        .map_err(|e| format!("Failed to write cache: {}", e))?;
 
     Ok(())
 }
 
-/// Fetch registry from remote
-async fn fetch_registry(force: bool) -> Result<Registry, String> {
-    // Try cache first unless forced
-    if !force {
-        if let Some(cached) = load_cached_registry() {
-            log::info!("Using cached registry");
-            return Ok(cached);
-        }
+/// Download `url`, failing on non-success statuses
+async fn fetch_bytes(url: &str, what: &str) -> Result<Vec<u8>, String> {
+    if url.starts_with("file:") {
+        let path = reqwest::Url::parse(url)
+            .ok()
+            .and_then(|u| u.to_file_path().ok())
+            .ok_or_else(|| format!("Invalid {} URL: {}", what, url))?;
+        return fs::read(&path).map_err(|e| format!("Failed to read {} from {:?}: {}", what, path, e));
     }
 
-    log::info!("Fetching registry from {}", DEFAULT_REGISTRY_URL);
-
-    let response = reqwest::get(DEFAULT_REGISTRY_URL)
+    let response = reqwest::get(url)
         .await
-        .map_err(|e| format!("Failed to fetch registry: {}", e))?;
+        .map_err(|e| format!("Failed to download {}: {}", what, e))?;
 
     if !response.status().is_success() {
-        return Err(format!("Registry fetch failed with status: {}", response.status()));
+        return Err(format!("{} download failed with status: {}", what, response.status()));
     }
 
-    let registry: Registry = response
-        .json()
+    let bytes = response
+        .bytes()
         .await
-        .map_err(|e| format!("Failed to parse registry: {}", e))?;
+        .map_err(|e| format!("Failed to read {}: {}", what, e))?;
+    Ok(bytes.to_vec())
+}
 
-    // Cache the registry
-    if let Err(e) = save_registry_cache(&registry) {
-        log::warn!("Failed to cache registry: {}", e);
+/// Download `url` and its detached signature, and check it was signed by one of `keys`
+async fn fetch_signed(url: &str, what: &str, keys: &[&str]) -> Result<(Vec<u8>, String), String> {
+    let data = fetch_bytes(url, what).await?;
+    let signature = fetch_bytes(&signature_url(url), &format!("{} signature", what)).await?;
+    let signature = String::from_utf8(signature)
+        .map_err(|_| format!("{} signature is not valid UTF-8", what))?;
+
+    verify_signature(&data, &signature, keys)
+        .map_err(|e| format!("{} signature verification failed: {}", what, e))?;
+    log::info!("Verified {} signature", what);
+    Ok((data, signature))
+}
+
+/// Keys a registry's listing and packages must be signed with
+fn registry_keys(source: &RegistrySource) -> Vec<&str> {
+    match &source.trust_key {
+        Some(key) => vec![key.as_str()],
+        None => pinned_publisher_keys(),
+    }
+}
+
+/// Registries to install from: the configured ones, or the default registry
+fn configured_registries() -> Vec<RegistrySource> {
+    let registries = load_app_config()
+        .map(|config| config.registries)
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to load app config, using the default registry: {}", e);
+            Vec::new()
+        });
+    if registries.is_empty() {
+        vec![RegistrySource::new(DEFAULT_REGISTRY_URL)]
+    } else {
+        registries
     }
+}
+
+/// Resolve a registry's `baseUrl`, which may be relative to the registry itself
+fn resolve_base_url(registry_url: &str, base_url: &str) -> Result<String, String> {
+    let resolved = reqwest::Url::parse(registry_url)
+        .and_then(|url| url.join(base_url))
+        .map_err(|e| format!("Invalid baseUrl {:?}: {}", base_url, e))?;
+    Ok(resolved.as_str().trim_end_matches('/').to_string())
+}
+
+/// Fetch one registry (or its cache) and check its signature.
+/// Local file:// registries aren't cached so edits show up immediately.
+async fn fetch_registry_source(source: &RegistrySource, force: bool) -> Result<Registry, String> {
+    let keys = registry_keys(source);
+    let cacheable = !source.url.starts_with("file:");
+
+    // Try cache first unless forced
+    let cached = if force || !cacheable {
+        None
+    } else {
+        load_cached_registry(&source.url, &keys)
+    };
+    let content = match cached {
+        Some(content) => {
+            log::info!("Using cached registry {}", source.url);
+            content
+        }
+        None => {
+            log::info!("Fetching registry from {}", source.url);
+            let (content, signature) = fetch_signed(&source.url, "Registry", &keys).await?;
+            if cacheable {
+                if let Err(e) = save_registry_cache(&source.url, &content, &signature) {
+                    log::warn!("Failed to cache registry: {}", e);
+                }
+            }
+            content
+        }
+    };
 
+    let mut registry: Registry = serde_json::from_slice(&content)
+        .map_err(|e| format!("Failed to parse registry: {}", e))?;
+    registry.base_url = resolve_base_url(&source.url, &registry.base_url)?;
     Ok(registry)
 }
 
+/// Merge registry listings. When several offer the same connector the highest-priority
+/// registry wins, then the newest version, then the registry listed first.
+fn merge_registries(registries: Vec<(RegistrySource, Registry)>) -> Vec<RegistryEntry> {
+    let mut entries: Vec<RegistryEntry> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+
+    for (source, registry) in registries {
+        for connector in registry.connectors {
+            let entry = RegistryEntry {
+                connector,
+                base_url: registry.base_url.clone(),
+                source: source.clone(),
+            };
+            let Some(&i) = index.get(&entry.connector.id) else {
+                index.insert(entry.connector.id.clone(), entries.len());
+                entries.push(entry);
+                continue;
+            };
+
+            let current = &entries[i];
+            let wins = entry.source.priority > current.source.priority
+                || (entry.source.priority == current.source.priority
+                    && is_newer_version(&current.connector.version, &entry.connector.version));
+            let winner = if wins { &entry } else { current };
+            log::info!(
+                "Connector {} is offered by {} and {}; using {} v{}",
+                entry.connector.id,
+                current.source.url,
+                entry.source.url,
+                winner.source.url,
+                winner.connector.version
+            );
+            if wins {
+                entries[i] = entry;
+            }
+        }
+    }
+
+    entries
+}
+
+/// Fetch every configured registry and merge their connectors.
+/// A registry that can't be fetched or verified is skipped unless all of them fail.
+async fn fetch_registry(force: bool) -> Result<Vec<RegistryEntry>, String> {
+    let mut fetched = Vec::new();
+    let mut errors = Vec::new();
+
+    for source in configured_registries() {
+        match fetch_registry_source(&source, force).await {
+            Ok(registry) => fetched.push((source, registry)),
+            Err(e) => {
+                log::warn!("Skipping registry {}: {}", source.url, e);
+                errors.push(format!("{}: {}", source.url, e));
+            }
+        }
+    }
+
+    if fetched.is_empty() {
+        return Err(errors.join("; "));
+    }
+    Ok(merge_registries(fetched))
+}
+
 /// Check for connector updates
 /// Returns a list of connectors that have updates or are new
 #[tauri::command]
@@ -297,7 +454,7 @@ pub async fn check_connector_updates(
     let registry = fetch_registry(force).await?;
     let mut updates = Vec::new();
 
-    for connector in registry.connectors {
+    for RegistryEntry { connector, source, .. } in registry {
         let is_installed = is_connector_installed(&app, &connector.id, &connector.company);
         let current_version = get_installed_connector_version(&app, &connector.id, &connector.company);
 
@@ -320,6 +477,7 @@ pub async fn check_connector_updates(
                 latest_version: connector.version,
                 has_update,
                 is_new,
+                registry_url: source.url,
             });
         }
     }
@@ -328,52 +486,40 @@ pub async fn check_connector_updates(
     Ok(updates)
 }
 
+/// An installed connector and what it asks for that the user hasn't approved yet
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConnectorInstall {
+    #[serde(rename = "platformId")]
+    pub platform_id: String,
+    /// The connector won't run until these are approved (`approve_connector_permissions`)
+    #[serde(rename = "pendingPermissions")]
+    pub pending_permissions: Option<ConnectorGrant>,
+}
+
 /// Download and install a connector
 #[tauri::command]
-pub async fn download_connector(_app: AppHandle, id: String) -> Result<(), String> {
+pub async fn download_connector(_app: AppHandle, id: String) -> Result<ConnectorInstall, String> {
     log::info!("=== Starting connector download: {} ===", id);
 
-    // Fetch registry to get connector info
+    // Fetch registries to get connector info
     let registry = fetch_registry(false).await?;
 
-    let connector = registry
-        .connectors
+    let entry = registry
         .iter()
-        .find(|c| c.id == id)
-        .ok_or_else(|| format!("Connector {} not found in registry", id))?;
-
-    log::info!("Found connector in registry: {} v{} (company: {})",
-        connector.id, connector.version, connector.company);
-
-    // Get user connectors directory
-    let user_dir = get_user_connectors_dir()
-        .ok_or("Could not determine user connectors directory")?;
-
-    log::info!("User connectors directory: {:?}", user_dir);
+        .find(|e| e.connector.id == id)
+        .ok_or_else(|| format!("Connector {} not found in any registry", id))?;
+    let connector = &entry.connector;
 
-    // Create company subdirectory
-    let company_dir = user_dir.join(connector.company.to_lowercase());
-    fs::create_dir_all(&company_dir)
-        .map_err(|e| format!("Failed to create connector directory: {}", e))?;
+    log::info!("Found connector in registry {}: {} v{} (company: {})",
+        entry.source.url, connector.id, connector.version, connector.company);
 
-    log::info!("Company directory: {:?}", company_dir);
+    let keys = registry_keys(&entry.source);
 
     // Download script file
-    let script_url = format!("{}/{}", registry.base_url, connector.files.script);
+    let script_url = format!("{}/{}", entry.base_url, connector.files.script);
     log::info!("Downloading script from: {}", script_url);
 
-    let script_response = reqwest::get(&script_url)
-        .await
-        .map_err(|e| format!("Failed to download script: {}", e))?;
-
-    if !script_response.status().is_success() {
-        return Err(format!("Script download failed with status: {}", script_response.status()));
-    }
-
-    let script_bytes = script_response
-        .bytes()
-        .await
-        .map_err(|e| format!("Failed to read script: {}", e))?;
+    let (script_bytes, _) = fetch_signed(&script_url, "Script", &keys).await?;
 
     log::info!("Downloaded {} bytes of script", script_bytes.len());
 
@@ -389,21 +535,10 @@ pub async fn download_connector(_app: AppHandle, id: String) -> Result<(), Strin
     log::info!("Script checksum verified");
 
     // Download metadata file
-    let metadata_url = format!("{}/{}", registry.base_url, connector.files.metadata);
+    let metadata_url = format!("{}/{}", entry.base_url, connector.files.metadata);
     log::info!("Downloading metadata from: {}", metadata_url);
 
-    let metadata_response = reqwest::get(&metadata_url)
-        .await
-        .map_err(|e| format!("Failed to download metadata: {}", e))?;
-
-    if !metadata_response.status().is_success() {
-        return Err(format!("Metadata download failed with status: {}", metadata_response.status()));
-    }
-
-    let metadata_bytes = metadata_response
-        .bytes()
-        .await
-        .map_err(|e| format!("Failed to read metadata: {}", e))?;
+    let (metadata_bytes, _) = fetch_signed(&metadata_url, "Metadata", &keys).await?;
 
     log::info!("Downloaded {} bytes of metadata", metadata_bytes.len());
 
@@ -418,6 +553,19 @@ pub async fn download_connector(_app: AppHandle, id: String) -> Result<(), Strin
     }
     log::info!("Metadata checksum verified");
 
+    // Nothing is written until both files are verified
+    let user_dir = get_user_connectors_dir()
+        .ok_or("Could not determine user connectors directory")?;
+
+    log::info!("User connectors directory: {:?}", user_dir);
+
+    // Create company subdirectory
+    let company_dir = user_dir.join(connector.company.to_lowercase());
+    fs::create_dir_all(&company_dir)
+        .map_err(|e| format!("Failed to create connector directory: {}", e))?;
+
+    log::info!("Company directory: {:?}", company_dir);
+
     // Extract filename from path
     let script_filename = connector
         .files
@@ -457,13 +605,38 @@ pub async fn download_connector(_app: AppHandle, id: String) -> Result<(), Strin
     }
 
     log::info!("=== Successfully installed connector: {} ===", id);
-    Ok(())
+
+    let manifest: ConnectorManifest = serde_json::from_slice(&metadata_bytes)
+        .map_err(|e| format!("Installed connector has an invalid manifest: {}", e))?;
+    let stem = metadata_path.file_stem().unwrap_or_default().to_string_lossy();
+    let platform_id = manifest.platform_id(&stem);
+    let approved = load_connector_settings(&platform_id)
+        .approved_permissions
+        .unwrap_or_default();
+    let pending = manifest.requested_grant().not_covered_by(&approved);
+    if !pending.is_empty() {
+        log::info!("Connector {} asks for new permissions: {}", id, pending.summary());
+    }
+    Ok(ConnectorInstall {
+        platform_id,
+        pending_permissions: (!pending.is_empty()).then_some(pending),
+    })
 }
 
-/// Get the registry URL (for debugging/display purposes)
+/// Get the highest-priority registry URL (for debugging/display purposes)
 #[tauri::command]
 pub fn get_registry_url() -> String {
-    DEFAULT_REGISTRY_URL.to_string()
+    configured_registries()
+        .into_iter()
+        .min_by_key(|r| std::cmp::Reverse(r.priority))
+        .map(|r| r.url)
+        .unwrap_or_else(|| DEFAULT_REGISTRY_URL.to_string())
+}
+
+/// Get the registries connectors are installed from, in config order
+#[tauri::command]
+pub fn get_registries() -> Vec<RegistrySource> {
+    configured_registries()
 }
 
 /// Get all installed connector versions
@@ -538,3 +711,115 @@ fn scan_connectors_dir_no_overwrite(dir: &PathBuf, versions: &mut HashMap<String
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_KEY: &str = "RWT0MSfoGYCNNSJ9VIiKS17io2Uk49yLQRvBW92uq2ObQwOPr1AMPg2e";
+    const REGISTRY: &str = r#"{"version":"1","lastUpdated":"2026-10-01","baseUrl":".","connectors":[{"id":"test-connector","company":"Test","version":"1.0.0","name":"Test","description":"Test connector","files":{"script":"test/test-connector.js","metadata":"test/test-connector.json"},"checksums":{"script":"sha256:66c476933067cc8626eb30a6233f378a905eb05983fe88644341f62719f1e503","metadata":"sha256:d2d6e4b82557a4a874d82974e3f0d981e01eb7a7377f2ae98561e7156490216d"}}]}"#;
+    const REGISTRY_SIGNATURE: &str = "untrusted comment: signature from minisign secret key
+RUT0MSfoGYCNNZVydysjxAE+S6IEbDasI8vbbr2oU2kvO5wTYZawDu+7CkDUsOHq7UT3xzbMpAt8k8gWpFF93XsxxB6mURsBQwQ=
+trusted comment: timestamp:1760000000
+bM/L+xMBH2x07sv3i+WPeQhhavHfOBeC2YOj5ayOHzchglBb5WFa2zeKheOTNCuFzR/NVRDey6M2nKEK+ePdBg==
+";
+    const SCRIPT: &str = "// test connector\n";
+    const SCRIPT_SIGNATURE: &str = "untrusted comment: signature from minisign secret key
+RUT0MSfoGYCNNcsryihgNOE6XRiHMFKfK6/m2srGKvjOLCtRgjFADZjG2kncR/DBqU074eOuHhgXlW8bAEYOhW+lvfWET59zkwg=
+trusted comment: timestamp:1760000000
+vftbSap/toQCOLO0B+YFntfbkT7HnVCStNZoei9Mx+ZHXN/NlKLxIvXTHYl2W+H2ClagO1qfcJQQNQXDVsrvCQ==
+";
+
+    fn registry(base_url: &str, connectors: &[(&str, &str)]) -> Registry {
+        Registry {
+            version: "1".to_string(),
+            last_updated: "2026-10-01".to_string(),
+            base_url: base_url.to_string(),
+            connectors: connectors
+                .iter()
+                .map(|(id, version)| RegistryConnector {
+                    id: id.to_string(),
+                    company: "Test".to_string(),
+                    version: version.to_string(),
+                    name: id.to_string(),
+                    description: String::new(),
+                    files: ConnectorFiles {
+                        script: format!("test/{}.js", id),
+                        metadata: format!("test/{}.json", id),
+                    },
+                    checksums: ConnectorChecksums {
+                        script: String::new(),
+                        metadata: String::new(),
+                    },
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn merge_prefers_priority_then_version_then_order() {
+        let public = RegistrySource::new("https://public.example.com/registry.json");
+        let private = RegistrySource {
+            priority: 10,
+            ..RegistrySource::new("https://private.example.com/registry.json")
+        };
+        let mirror = RegistrySource::new("https://mirror.example.com/registry.json");
+
+        let entries = merge_registries(vec![
+            (public, registry("https://public.example.com", &[("chatgpt", "1.0.0"), ("github", "1.0.0"), ("linkedin", "2.0.0")])),
+            (private, registry("https://private.example.com", &[("chatgpt", "0.9.0"), ("internal", "1.0.0")])),
+            (mirror, registry("https://mirror.example.com", &[("github", "1.1.0"), ("linkedin", "2.0.0")])),
+        ]);
+
+        let picked: Vec<_> = entries
+            .iter()
+            .map(|e| (e.connector.id.as_str(), e.connector.version.as_str(), e.base_url.as_str()))
+            .collect();
+        assert_eq!(
+            picked,
+            vec![
+                ("chatgpt", "0.9.0", "https://private.example.com"),
+                ("github", "1.1.0", "https://mirror.example.com"),
+                ("linkedin", "2.0.0", "https://public.example.com"),
+                ("internal", "1.0.0", "https://private.example.com"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn fetches_signed_file_registry() {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("registry_{}", nanos));
+        fs::create_dir_all(dir.join("test")).unwrap();
+        fs::write(dir.join("registry.json"), REGISTRY).unwrap();
+        fs::write(dir.join("registry.json.minisig"), REGISTRY_SIGNATURE).unwrap();
+        fs::write(dir.join("test/test-connector.js"), SCRIPT).unwrap();
+        fs::write(dir.join("test/test-connector.js.minisig"), SCRIPT_SIGNATURE).unwrap();
+
+        let url = reqwest::Url::from_file_path(dir.join("registry.json")).unwrap();
+        let source = RegistrySource {
+            trust_key: Some(TEST_KEY.to_string()),
+            ..RegistrySource::new(url.as_str())
+        };
+        let registry = fetch_registry_source(&source, false).await.expect("registry should verify");
+        let base_url = reqwest::Url::from_directory_path(&dir).unwrap();
+        assert_eq!(format!("{}/", registry.base_url), base_url.as_str());
+
+        let connector = &registry.connectors[0];
+        let script_url = format!("{}/{}", registry.base_url, connector.files.script);
+        let (script, _) = fetch_signed(&script_url, "Script", &registry_keys(&source)).await.unwrap();
+        assert!(verify_checksum(&script, &connector.checksums.script));
+
+        // Packages must be signed by the registry's key, not just listed in it
+        fs::write(dir.join("test/test-connector.js"), "// tampered\n").unwrap();
+        assert!(fetch_signed(&script_url, "Script", &registry_keys(&source)).await.is_err());
+
+        let untrusted = RegistrySource::new(url.as_str());
+        assert!(fetch_registry_source(&untrusted, true).await.is_err());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -2,18 +2,44 @@ mod commands;
 mod processors;
 
 use commands::{
+    answer_connector_prompt, apply_log_levels, approve_connector_permissions,
     check_browser_available, check_connected_platforms, check_connector_updates,
     cleanup_personal_server, cleanup_playwright_processes, clear_browser_session,
-    debug_connector_paths, download_browser, download_chromium_rust, download_connector,
-    get_app_config, get_installed_connectors, get_log_path, get_personal_server_status,
-    get_platforms, get_registry_url, get_run_files, get_user_data_path, handle_download,
-    list_browser_sessions, load_latest_source_export_full, load_latest_source_export_preview,
-    load_run_export_data, load_runs, mark_export_synced, open_folder, open_platform_export_folder,
-    set_app_config, start_connector_run, start_personal_server,
-    stop_connector_run, stop_personal_server, test_nodejs, write_export_data,
+    connector_message, connector_window_allows, create_diagnostics_bundle, debug_connector_paths,
+    download_browser, download_chromium_rust, download_connector, enqueue_export_sync,
+    export_run_diagnostics, get_app_config, get_installed_connectors, get_log_path,
+    get_personal_server_status, get_platforms, get_registries, get_registry_url, get_run_files,
+    get_run_logs, get_sync_queue, get_user_data_path, handle_download, list_browser_sessions,
+    list_runs_in_progress, load_latest_source_export_full, load_latest_source_export_preview,
+    load_run_export_data, load_runs, log_enabled, mark_export_synced, open_folder,
+    open_platform_export_folder, restore_synced_export, retry_export_sync, set_app_config,
+    set_log_level, start_connector_run, start_log_pruning, start_personal_server,
+    start_run_watchdog, start_runner_health_check, start_scheduler, start_sync_worker,
+    stop_connector_run, stop_personal_server, test_nodejs, validate_connector, write_export_data,
+    LOG_MAX_FILE_SIZE,
 };
+use commands::config::load_app_config;
+use commands::redact::{redact, set_redaction_patterns};
 use tauri::{Listener, Manager};
 
+/// Connector windows load third-party pages: they may only report on their own run
+fn guard_connector_windows<R: tauri::Runtime>(
+    handler: impl Fn(tauri::ipc::Invoke<R>) -> bool + Send + Sync + 'static,
+) -> impl Fn(tauri::ipc::Invoke<R>) -> bool + Send + Sync + 'static {
+    move |invoke| {
+        let label = invoke.message.webview().label().to_string();
+        let command = invoke.message.command().to_string();
+        if !connector_window_allows(&label, &command) {
+            log::warn!("Blocked command {} from connector window {}", command, label);
+            invoke
+                .resolver
+                .reject(format!("Command {} is not available to connector windows", command));
+            return true;
+        }
+        handler(invoke)
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     let builder = tauri::Builder::default()
@@ -31,11 +57,37 @@ pub fn run() {
             // Enable logging in both debug and release builds, writing to both stdout and a file
             // Default targets are already [Stdout, LogDir] — do NOT add
             // .target() calls or each log line gets written twice.
+            // Every line goes through the redaction layer: connector output and server env
+            // values end up here, and users share this file in bug reports.
+            // Levels are filtered by `log_enabled` so `set_log_level` can change them at runtime;
+            // full files are archived with a date and pruned by `start_log_pruning`.
             app.handle().plugin(
                 tauri_plugin_log::Builder::default()
-                    .level(log::LevelFilter::Info)
+                    .level(log::LevelFilter::Trace)
+                    .filter(log_enabled)
+                    .max_file_size(LOG_MAX_FILE_SIZE)
+                    .rotation_strategy(tauri_plugin_log::RotationStrategy::KeepAll)
+                    .format(|out, message, record| {
+                        out.finish(format_args!(
+                            "{}[{}][{}] {}",
+                            chrono::Utc::now().format("[%Y-%m-%d][%H:%M:%S]"),
+                            record.target(),
+                            record.level(),
+                            redact(&message.to_string())
+                        ))
+                    })
                     .build(),
             )?;
+            apply_log_levels();
+            start_log_pruning(app.handle().clone());
+            match load_app_config() {
+                Ok(config) => {
+                    if let Err(e) = set_redaction_patterns(&config.log_redaction_patterns) {
+                        log::warn!("Ignoring configured log redaction patterns: {}", e);
+                    }
+                }
+                Err(e) => log::warn!("Failed to load app config for log redaction: {}", e),
+            }
 
             // Listen for close window events from connectors
             let app_handle = app.handle().clone();
@@ -52,10 +104,21 @@ pub fn run() {
                 }
             });
 
+            // Deliver queued exports to the personal server in the background
+            start_sync_worker(app.handle().clone());
+
+            // Keep the shared Playwright runner responsive
+            start_runner_health_check(app.handle().clone());
+            start_run_watchdog(app.handle().clone());
+
+            // Re-run connected platforms in the background according to their exportFrequency
+            start_scheduler(app.handle().clone());
+
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![
+        .invoke_handler(guard_connector_windows(tauri::generate_handler![
             get_platforms,
+            validate_connector,
             start_connector_run,
             stop_connector_run,
             check_connected_platforms,
@@ -77,6 +140,7 @@ pub fn run() {
             check_connector_updates,
             download_connector,
             get_registry_url,
+            get_registries,
             get_installed_connectors,
             get_app_config,
             set_app_config,
@@ -87,7 +151,19 @@ pub fn run() {
             list_browser_sessions,
             clear_browser_session,
             mark_export_synced,
-        ])
+            restore_synced_export,
+            enqueue_export_sync,
+            get_sync_queue,
+            retry_export_sync,
+            list_runs_in_progress,
+            answer_connector_prompt,
+            approve_connector_permissions,
+            connector_message,
+            export_run_diagnostics,
+            get_run_logs,
+            set_log_level,
+            create_diagnostics_bundle,
+        ]))
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
         .run(|_app, event| {
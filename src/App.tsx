@@ -5,6 +5,8 @@ import { store } from "./state/store"
 import { useEvents } from "./hooks/useEvents"
 import { useInitialize } from "./hooks/useInitialize"
 import { TopNav } from "./components/top-nav"
+import { ConnectorPromptDialog } from "./components/connector-prompt-dialog"
+import { ConnectorPermissionsDialog } from "./components/connector-permissions-dialog"
 import { useDeepLink } from "./hooks/use-deep-link"
 import { usePersonalServer } from "./hooks/usePersonalServer"
 import { usePendingApprovalRetry } from "./hooks/usePendingApproval"
@@ -64,6 +66,8 @@ function AppContent() {
           </Suspense>
         </main>
       </div>
+      <ConnectorPromptDialog />
+      <ConnectorPermissionsDialog />
     </div>
   )
 }
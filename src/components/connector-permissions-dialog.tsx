@@ -0,0 +1,83 @@
+import { useEffect, useState, useSyncExternalStore } from "react"
+import { invoke } from "@tauri-apps/api/core"
+import {
+  AlertDialog,
+  AlertDialogContent,
+  AlertDialogDescription,
+  AlertDialogFooter,
+  AlertDialogHeader,
+  AlertDialogTitle,
+} from "@/components/ui/alert-dialog"
+import { Button } from "@/components/ui/button"
+import {
+  describeConnectorGrant,
+  getCurrentPermissionRequest,
+  subscribePermissionRequests,
+} from "@/lib/connector-permissions"
+
+/**
+ * Asks the user to approve what a user-installed connector asks for (see
+ * `confirmConnectorPermissions`) and records the approval with `approve_connector_permissions`.
+ */
+export function ConnectorPermissionsDialog() {
+  const request = useSyncExternalStore(subscribePermissionRequests, getCurrentPermissionRequest)
+  const [error, setError] = useState<string | null>(null)
+  const [submitting, setSubmitting] = useState(false)
+
+  useEffect(() => {
+    setError(null)
+  }, [request])
+
+  if (!request) return null
+
+  const allow = async () => {
+    setSubmitting(true)
+    setError(null)
+    try {
+      await invoke("approve_connector_permissions", { platformId: request.platformId })
+      request.settle(true)
+    } catch (err) {
+      setError(String(err))
+    } finally {
+      setSubmitting(false)
+    }
+  }
+
+  return (
+    <AlertDialog
+      open
+      onOpenChange={open => {
+        if (!open && !submitting) request.settle(false)
+      }}
+    >
+      <AlertDialogContent size="sm">
+        <AlertDialogHeader>
+          <AlertDialogTitle>{request.name} asks for new permissions</AlertDialogTitle>
+          <AlertDialogDescription className="text-small text-foreground-dim">
+            It can't run until you allow them.
+          </AlertDialogDescription>
+        </AlertDialogHeader>
+        <ul className="list-disc pl-5 text-small">
+          {describeConnectorGrant(request.grant).map(line => (
+            <li key={line}>{line}</li>
+          ))}
+        </ul>
+        {error ? <p className="text-small text-destructive">{error}</p> : null}
+        <AlertDialogFooter>
+          <Button
+            type="button"
+            size="sm"
+            variant="outline"
+            disabled={submitting}
+            onClick={() => request.settle(false)}
+          >
+            Don&apos;t allow
+          </Button>
+          <Button type="button" size="sm" disabled={submitting} onClick={() => void allow()}>
+            Allow
+          </Button>
+        </AlertDialogFooter>
+      </AlertDialogContent>
+    </AlertDialog>
+  )
+}
@@ -0,0 +1,181 @@
+import { useEffect, useState } from "react"
+import type { FormEvent } from "react"
+import { invoke } from "@tauri-apps/api/core"
+import { useDispatch, useSelector } from "react-redux"
+import {
+  AlertDialog,
+  AlertDialogContent,
+  AlertDialogDescription,
+  AlertDialogFooter,
+  AlertDialogHeader,
+  AlertDialogTitle,
+} from "@/components/ui/alert-dialog"
+import { Button } from "@/components/ui/button"
+import { Input } from "@/components/ui/input"
+import { removeRunPrompt } from "@/state/store"
+import type { RootState } from "@/state/store"
+import type { ConnectorPrompt } from "@/types"
+
+function optionLabel(option: unknown): string {
+  if (typeof option === "string") return option
+  if (typeof option === "object" && option !== null && "label" in option) {
+    return String((option as { label: unknown }).label)
+  }
+  return JSON.stringify(option)
+}
+
+/**
+ * Shows the oldest question a running connector asked via `page.askUser()` and
+ * sends the answer (or a dismissal) back with `answer_connector_prompt`.
+ */
+export function ConnectorPromptDialog() {
+  const dispatch = useDispatch()
+  const run = useSelector((state: RootState) =>
+    state.app.runs.find(run => (run.pendingPrompts?.length ?? 0) > 0)
+  )
+  const pending = run?.pendingPrompts?.[0]
+    ? { runId: run.id, runName: run.name, prompt: run.pendingPrompts[0] }
+    : null
+
+  const [text, setText] = useState("")
+  const [error, setError] = useState<string | null>(null)
+  const [submitting, setSubmitting] = useState(false)
+  const promptId = pending?.prompt.promptId
+
+  // Start every prompt with an empty answer
+  useEffect(() => {
+    setText("")
+    setError(null)
+  }, [promptId])
+
+  if (!pending) return null
+  const { runId, runName, prompt } = pending
+
+  const answer = async (value: unknown) => {
+    setSubmitting(true)
+    setError(null)
+    try {
+      await invoke("answer_connector_prompt", { runId, promptId: prompt.promptId, value })
+      dispatch(removeRunPrompt({ runId, promptId: prompt.promptId }))
+    } catch (err) {
+      setError(String(err))
+    } finally {
+      setSubmitting(false)
+    }
+  }
+
+  const dismiss = async () => {
+    try {
+      await invoke("answer_connector_prompt", { runId, promptId: prompt.promptId, value: null })
+    } catch {
+      // Already closed (timed out or the run ended); nothing is waiting for the answer
+    }
+    dispatch(removeRunPrompt({ runId, promptId: prompt.promptId }))
+  }
+
+  const onSubmit = (event: FormEvent) => {
+    event.preventDefault()
+    // Secrets are sent as typed; surrounding spaces may be part of a password
+    const value = prompt.kind === "secret" ? text : text.trim()
+    if (value) void answer(value)
+  }
+
+  return (
+    <AlertDialog
+      open
+      onOpenChange={open => {
+        if (!open) void dismiss()
+      }}
+    >
+      <AlertDialogContent size="sm">
+        <AlertDialogHeader>
+          <AlertDialogTitle>{runName} needs your input</AlertDialogTitle>
+          <AlertDialogDescription className="text-small text-foreground-dim">
+            {prompt.message}
+          </AlertDialogDescription>
+        </AlertDialogHeader>
+        <PromptAnswer
+          prompt={prompt}
+          text={text}
+          onTextChange={setText}
+          onSubmit={onSubmit}
+          onAnswer={value => void answer(value)}
+          disabled={submitting}
+        />
+        {error ? <p className="text-small text-destructive">{error}</p> : null}
+        <AlertDialogFooter>
+          <Button type="button" size="sm" variant="outline" onClick={() => void dismiss()}>
+            Dismiss
+          </Button>
+          {prompt.kind === "text" || prompt.kind === "secret" || prompt.kind === "code" ? (
+            <Button
+              type="submit"
+              form="connector-prompt-form"
+              size="sm"
+              disabled={submitting || !text.trim()}
+            >
+              Submit
+            </Button>
+          ) : null}
+        </AlertDialogFooter>
+      </AlertDialogContent>
+    </AlertDialog>
+  )
+}
+
+interface PromptAnswerProps {
+  prompt: ConnectorPrompt
+  text: string
+  onTextChange: (text: string) => void
+  onSubmit: (event: FormEvent) => void
+  onAnswer: (value: unknown) => void
+  disabled: boolean
+}
+
+function PromptAnswer({ prompt, text, onTextChange, onSubmit, onAnswer, disabled }: PromptAnswerProps) {
+  if (prompt.kind === "confirm") {
+    return (
+      <div className="flex gap-2">
+        <Button type="button" size="sm" variant="outline" disabled={disabled} onClick={() => onAnswer(false)}>
+          No
+        </Button>
+        <Button type="button" size="sm" disabled={disabled} onClick={() => onAnswer(true)}>
+          Yes
+        </Button>
+      </div>
+    )
+  }
+
+  if (prompt.kind === "choice") {
+    return (
+      <div className="flex flex-col gap-2">
+        {(prompt.options ?? []).map((option, index) => (
+          <Button
+            key={index}
+            type="button"
+            size="sm"
+            variant="outline"
+            disabled={disabled}
+            onClick={() => onAnswer(option)}
+          >
+            {optionLabel(option)}
+          </Button>
+        ))}
+      </div>
+    )
+  }
+
+  return (
+    <form id="connector-prompt-form" onSubmit={onSubmit}>
+      <Input
+        autoFocus
+        type={prompt.kind === "secret" ? "password" : "text"}
+        inputMode={prompt.kind === "code" ? "numeric" : undefined}
+        autoComplete={prompt.kind === "code" ? "one-time-code" : "off"}
+        value={text}
+        disabled={disabled}
+        onChange={event => onTextChange(event.target.value)}
+      />
+    </form>
+  )
+}
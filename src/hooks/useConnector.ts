@@ -4,13 +4,16 @@ import { useDispatch, useSelector } from 'react-redux';
 import { startRun, updateRunStatus, stopRun } from '../state/store';
 import type { RootState } from '../state/store';
 import type { Platform, Run } from '../types';
+import { confirmConnectorPermissions } from '../lib/connector-permissions';
 
 export function useConnector() {
   const dispatch = useDispatch();
   const runs = useSelector((state: RootState) => state.app.runs);
 
+  // `options.headless` overrides the connector's setting for this run only
+  // (false shows the browser so the user can watch or intervene)
   const startImport = useCallback(
-    async (platform: Platform) => {
+    async (platform: Platform, options: { headless?: boolean } = {}) => {
       const runId = `${platform.id}-${Date.now()}`;
 
       const newRun: Run = {
@@ -29,6 +32,14 @@ export function useConnector() {
       dispatch(startRun(newRun));
 
       try {
+        // User-installed connectors run only with the permissions the user approved
+        if (
+          platform.pendingPermissions &&
+          !(await confirmConnectorPermissions(platform.id, platform.name, platform.pendingPermissions))
+        ) {
+          throw new Error(`Permissions for ${platform.name} were not approved`);
+        }
+
         // Check if simulate no chrome is enabled (for testing download flow)
         const simulateNoChrome = localStorage.getItem('dataconnect_simulate_no_chrome') === 'true';
 
@@ -41,6 +52,7 @@ export function useConnector() {
           connectUrl: platform.connectURL || '',
           runtime: platform.runtime || null,
           simulateNoChrome,
+          headless: options.headless ?? null,
         });
       } catch (error) {
         console.error('Failed to start connector run:', error);
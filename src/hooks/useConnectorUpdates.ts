@@ -7,7 +7,8 @@ import {
   removeConnectorUpdate,
 } from '../state/store';
 import type { RootState } from '../state/store';
-import type { ConnectorUpdateInfo } from '../types';
+import type { ConnectorInstall, ConnectorUpdateInfo } from '../types';
+import { confirmConnectorPermissions } from '../lib/connector-permissions';
 
 export function useConnectorUpdates() {
   const dispatch = useDispatch();
@@ -52,9 +53,24 @@ export function useConnectorUpdates() {
       setDownloadingIds((prev) => new Set(prev).add(id));
 
       try {
-        await invoke('download_connector', { id });
+        const install = await invoke<ConnectorInstall>('download_connector', { id });
         // Remove from updates list after successful download
         dispatch(removeConnectorUpdate(id));
+        // New domains or capabilities need the user's approval before the connector can run;
+        // if they decline now they're asked again on the next run
+        if (install.pendingPermissions) {
+          const name = updates.find((u) => u.id === id)?.name ?? id;
+          const approved = await confirmConnectorPermissions(
+            install.platformId,
+            name,
+            install.pendingPermissions
+          );
+          if (!approved) {
+            setError(
+              `${name} was updated but won't run until you allow its new permissions`
+            );
+          }
+        }
         // Note: Caller is responsible for reloading platforms after successful download
         return true;
       } catch (err) {
@@ -71,7 +87,7 @@ export function useConnectorUpdates() {
         });
       }
     },
-    [dispatch]
+    [dispatch, updates]
   );
 
   const isDownloading = useCallback(
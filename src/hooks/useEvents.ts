@@ -3,24 +3,27 @@ import { listen } from '@tauri-apps/api/event';
 import { invoke } from '@tauri-apps/api/core';
 import { useDispatch } from 'react-redux';
 import {
+  startRun,
   updateRunLogs,
   updateRunStatus,
   updateExportStatus,
   updateRunConnected,
   updateRunExportData,
   markRunSynced,
+  addRunPrompt,
+  removeRunPrompt,
   type AppDispatch,
   store,
 } from '../state/store';
 import type {
   ConnectorLogEvent,
+  ConnectorPromptClosedEvent,
+  ConnectorPromptEvent,
   DownloadProgressEvent,
   ExportCompleteEvent,
-  ExportedData,
   ProgressPhase,
 } from '../types';
-import { normalizeExportData } from '../lib/export-data';
-import { getScopeForPlatform, ingestData } from '../services/personalServerIngest';
+import { getScopeForPlatform } from '../services/personalServerIngest';
 
 const isDev = import.meta.env.DEV;
 
@@ -42,144 +45,59 @@ interface ConnectorStatusEventPayload {
   timestamp: number;
 }
 
-// Export complete event from connector (includes display name)
+// Export complete event from connector (includes display name). The export is
+// already saved by the backend; only a reference to it is sent.
 interface ConnectorExportCompleteEvent {
   runId: string;
   platformId: string;
   company: string;
   name: string; // Display name (e.g., "Instagram (Playwright)")
-  data: unknown;
+  exportPath: string;
+  exportSize: number;
+  itemsExported: number | null;
+  itemLabel: string | null;
   timestamp: number;
 }
 
-function isExportedData(value: unknown): value is ExportedData {
-  if (typeof value !== 'object' || value === null) return false;
-  const candidate = value as Partial<ExportedData>;
-  return typeof candidate.platform === 'string' && typeof candidate.company === 'string';
+// Progress event from the Rust-side personal server sync queue
+interface SyncProgressEvent {
+  runId: string;
+  platformId: string;
+  status: 'uploading' | 'synced' | 'retrying' | 'failed' | 'removed';
+  attempts: number;
+  scopesDone: number;
+  scopesTotal: number;
+  nextAttemptAt: number;
+  error?: string | null;
 }
 
 /**
- * Deliver a single run's export data to the personal server and mark staging as synced.
- * Returns true on success, false on failure (non-throwing).
+ * Queue a run's export for delivery to the personal server. The Rust sync queue
+ * uploads it in the background, retries on failure and emits `sync-progress`.
  */
-async function deliverRunToPersonalServer(
+async function enqueueRunSync(
   run: { id: string; platformId: string; exportPath?: string; itemsExported?: number; itemLabel?: string; syncedToPersonalServer?: boolean },
-  port: number,
-  dispatch: AppDispatch,
 ): Promise<boolean> {
   if (!run.exportPath || run.syncedToPersonalServer) return false;
 
-  const scope = getScopeForPlatform(run.platformId);
-  if (!scope) return false;
-
-  // Normalize exportPath to directory
-  const dirPath = run.exportPath.endsWith('.json')
-    ? run.exportPath.replace(/\/[^/]+$/, '')
-    : run.exportPath;
-
   try {
-    const data = await invoke<Record<string, unknown>>('load_run_export_data', {
-      runId: run.id,
-      exportPath: dirPath,
-    });
-    const payload = (data.content ?? data) as object;
-    await ingestData(port, scope, payload);
-
-    // Mark staging as synced (trims the large JSON)
-    await invoke('mark_export_synced', {
+    await invoke('enqueue_export_sync', {
       runId: run.id,
       exportPath: run.exportPath,
+      platformId: run.platformId,
+      scope: getScopeForPlatform(run.platformId),
       itemsExported: run.itemsExported ?? null,
       itemLabel: run.itemLabel ?? null,
-      scope,
     });
-
-    dispatch(markRunSynced(run.id));
-    debugLog('[Data Delivery] Synced run', run.id, 'to personal server, scope:', scope);
     return true;
   } catch (err) {
     if (isDev) {
-      console.warn('[Data Delivery] Failed for run', run.id, '(non-blocking):', err);
+      console.warn('[Data Delivery] Failed to queue run', run.id, '(non-blocking):', err);
     }
     return false;
   }
 }
 
-async function persistAndDeliverExport({
-  runId,
-  platformId,
-  company,
-  name,
-  exportData,
-  dispatch,
-  persistedRunIds,
-}: {
-  runId: string;
-  platformId: string;
-  company: string;
-  name: string;
-  exportData: ExportedData;
-  dispatch: AppDispatch;
-  persistedRunIds: Set<string>;
-}): Promise<void> {
-  if (persistedRunIds.has(runId)) return;
-
-  const serializedExport = JSON.stringify(exportData);
-  const { itemsExported, itemLabel } = normalizeExportData(exportData);
-
-  dispatch(
-    updateRunExportData({
-      runId,
-      statusMessage: 'Export complete',
-      itemsExported,
-      itemLabel,
-      exportData,
-    })
-  );
-
-  persistedRunIds.add(runId);
-
-  try {
-    const exportPath = await invoke<string>('write_export_data', {
-      runId,
-      platformId,
-      company,
-      name: name || platformId,
-      data: serializedExport,
-    });
-
-    dispatch(
-      updateExportStatus({
-        runId,
-        exportPath,
-        exportSize: serializedExport.length,
-      })
-    );
-
-    const scope = getScopeForPlatform(platformId);
-    if (!scope) return;
-
-    const serverStatus = await invoke<{ running: boolean; port?: number }>('get_personal_server_status');
-    if (!serverStatus.running || !serverStatus.port) return;
-
-    await ingestData(serverStatus.port, scope, exportData as object);
-    await invoke('mark_export_synced', {
-      runId,
-      exportPath,
-      itemsExported: itemsExported ?? null,
-      itemLabel: itemLabel ?? null,
-      scope,
-    });
-    dispatch(markRunSynced(runId));
-    debugLog('[Data Delivery] Synced run', runId, 'immediately after export');
-  } catch (err) {
-    persistedRunIds.delete(runId);
-    if (isDev) {
-      console.warn('[Export Persistence] Deferred or failed for run', runId, err);
-    }
-  }
-}
-
 export function useEvents() {
   const dispatch = useDispatch();
   const deliveryInProgressRef = useRef(false);
@@ -188,7 +106,7 @@ export function useEvents() {
    * App-wide runtime event bridge:
    * - subscribes to Tauri connector/server events
    * - normalizes them into Redux run state
-   * - persists and delivers completed exports
+   * - queues completed exports for delivery
    */
   useEffect(() => {
     let cancelled = false;
@@ -270,18 +188,7 @@ export function useEvents() {
           })
         );
         dispatch(updateRunConnected({ runId, isConnected: true }));
-
-        if (typeof status === 'object' && isExportedData(status.data)) {
-          void persistAndDeliverExport({
-            runId,
-            platformId: status.data.platform,
-            company: status.data.company,
-            name: status.data.platform,
-            exportData: status.data,
-            dispatch,
-            persistedRunIds,
-          });
-        }
+        // The export itself arrives as a reference on `export-complete`
       } else if (statusType === 'ERROR') {
         dispatch(
           updateRunStatus({
@@ -311,6 +218,28 @@ export function useEvents() {
       }
     });
 
+    // Background runs started by the Rust scheduler show up in run history like manual ones
+    addListener<{ runId: string; platformId: string; filename: string; company: string; name: string; url: string }>(
+      'scheduled-run-started',
+      ({ runId, platformId, filename, company, name, url }) => {
+        debugLog('[Scheduler] Background run started', runId);
+        dispatch(
+          startRun({
+            id: runId,
+            platformId,
+            filename,
+            isConnected: false,
+            startDate: new Date().toISOString(),
+            status: 'running',
+            url,
+            company,
+            name,
+            logs: '',
+          })
+        );
+      }
+    );
+
     // Listen for download progress events
     addListener<DownloadProgressEvent>('download-progress', ({ percent }) => {
       if (isDev) {
@@ -318,29 +247,60 @@ export function useEvents() {
       }
     });
 
-    // When personal server becomes ready, deliver all pending (unsynced) exports sequentially
+    // When personal server becomes ready, make sure all pending (unsynced) exports are queued.
+    // The Rust sync queue also resets its backoff at this point and starts delivering.
     addListener<{ port: number }>('personal-server-ready', async ({ port }) => {
       if (!port || deliveryInProgressRef.current) return;
       deliveryInProgressRef.current = true;
-      debugLog('[Data Delivery] Personal server ready on port', port, '— scanning for pending exports');
+      debugLog('[Data Delivery] Personal server ready on port', port, '— queueing pending exports');
       try {
         const runs = store.getState().app.runs;
         const pending = runs.filter(
           (r) => r.exportPath && !r.syncedToPersonalServer && r.status === 'success'
         );
-        debugLog('[Data Delivery]', pending.length, 'pending exports to deliver');
+        debugLog('[Data Delivery]', pending.length, 'pending exports to queue');
         for (const run of pending) {
           if (cancelled) break;
-          await deliverRunToPersonalServer(run, port, dispatch);
+          await enqueueRunSync(run);
         }
       } finally {
         deliveryInProgressRef.current = false;
       }
     });
 
+    // Reflect sync queue results in run state
+    addListener<SyncProgressEvent>('sync-progress', ({ runId, status, attempts, error }) => {
+      if (status === 'synced') {
+        dispatch(markRunSynced(runId));
+        debugLog('[Data Delivery] Synced run', runId, 'to personal server');
+      } else if ((status === 'retrying' || status === 'failed' || status === 'removed') && isDev) {
+        console.warn('[Data Delivery] Sync', status, 'for run', runId, `(attempt ${attempts}):`, error);
+      }
+    });
+
+    // Connector questions for the user; answered via answer_connector_prompt
+    addListener<ConnectorPromptEvent>(
+      'connector-prompt',
+      ({ runId, promptId, kind, message, options, timeoutMs, expiresAt }) => {
+        debugLog('[Connector Prompt]', runId, promptId, message);
+        dispatch(addRunPrompt({ runId, prompt: { promptId, kind, message, options, timeoutMs, expiresAt } }));
+      }
+    );
+
+    addListener<ConnectorPromptClosedEvent>('connector-prompt-closed', ({ runId, promptId, reason }) => {
+      debugLog('[Connector Prompt] Closed', runId, promptId, reason);
+      dispatch(removeRunPrompt({ runId, promptId }));
+    });
+
     // Listen for export complete events from connector
-    addListener<ConnectorExportCompleteEvent>('export-complete', ({ runId, platformId, company, name, data }) => {
-      if (!isExportedData(data)) return;
+    addListener<ConnectorExportCompleteEvent>('export-complete', (event) => {
+      const { runId, platformId, exportPath, exportSize } = event;
+      if (persistedRunIds.has(runId)) return;
+      persistedRunIds.add(runId);
+
+      // Unknown counts stay unknown rather than showing as "0 items"
+      const itemsExported = event.itemsExported ?? undefined;
+      const itemLabel = event.itemLabel ?? undefined;
 
       dispatch(
         updateRunStatus({
@@ -349,15 +309,18 @@ export function useEvents() {
           endDate: new Date().toISOString(),
         })
       );
+      dispatch(
+        updateRunExportData({
+          runId,
+          statusMessage: 'Export complete',
+          itemsExported,
+          itemLabel,
+        })
+      );
+      dispatch(updateExportStatus({ runId, exportPath, exportSize }));
 
-      void persistAndDeliverExport({
-        runId,
-        platformId,
-        company,
-        name,
-        exportData: data,
-        dispatch,
-        persistedRunIds,
+      void enqueueRunSync({ id: runId, platformId, exportPath, itemsExported, itemLabel }).then((queued) => {
+        if (queued) debugLog('[Data Delivery] Queued run', runId, 'for sync after export');
       });
     });
 
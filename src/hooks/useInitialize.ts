@@ -17,6 +17,7 @@ interface SavedRun {
   itemsExported?: number;
   itemLabel?: string;
   syncedToPersonalServer?: boolean;
+  loadError?: string;
 }
 
 export function useInitialize() {
@@ -51,6 +52,7 @@ export function useInitialize() {
           itemsExported: saved.itemsExported,
           itemLabel: saved.itemLabel,
           syncedToPersonalServer: saved.syncedToPersonalServer,
+          statusMessage: saved.loadError,
           logs: '',
         }));
 
@@ -3,7 +3,7 @@ import { invoke } from "@tauri-apps/api/core"
 import { useDispatch, useSelector } from "react-redux"
 import { setPlatforms, setConnectedPlatforms } from "../state/store"
 import type { RootState } from "../state/store"
-import type { Platform } from "../types"
+import type { ConnectorLoadError, PlatformsLoad } from "../types"
 
 export function usePlatforms() {
   const dispatch = useDispatch()
@@ -15,13 +15,19 @@ export function usePlatforms() {
   const [platformLoadError, setPlatformLoadError] = useState<string | null>(
     null
   )
+  const [connectorLoadErrors, setConnectorLoadErrors] = useState<
+    ConnectorLoadError[]
+  >([])
 
   const loadPlatforms = useCallback(async () => {
     setPlatformsLoaded(false)
     setPlatformLoadError(null)
     try {
-      const loadedPlatforms = await invoke<Platform[]>("get_platforms")
+      // Broken manifests are skipped and reported in `diagnostics`
+      const { platforms: loadedPlatforms, diagnostics } =
+        await invoke<PlatformsLoad>("get_platforms")
       dispatch(setPlatforms(loadedPlatforms))
+      setConnectorLoadErrors(diagnostics)
 
       // Check which platforms are connected
       const platformIds = loadedPlatforms.map(p => p.id)
@@ -82,5 +88,6 @@ export function usePlatforms() {
     isPlatformConnected,
     platformsLoaded,
     platformLoadError,
+    connectorLoadErrors,
   }
 }
@@ -0,0 +1,61 @@
+import type { ConnectorGrant } from "@/types"
+
+const PERMISSION_LABELS: Record<string, string> = {
+  "network-capture": "Read the responses of pages it opens",
+  downloads: "Download files",
+}
+
+export const describeConnectorGrant = (grant: ConnectorGrant): string[] => [
+  ...grant.allowedDomains.map(domain => `Open and fetch from ${domain}`),
+  ...grant.permissions.map(permission => PERMISSION_LABELS[permission] ?? permission),
+]
+
+/** An approval waiting for the user in `ConnectorPermissionsDialog` */
+export interface ConnectorPermissionRequest {
+  platformId: string
+  name: string
+  grant: ConnectorGrant
+  /** Called once the user allowed (after the approval was recorded) or declined */
+  settle: (approved: boolean) => void
+}
+
+let permissionRequests: ConnectorPermissionRequest[] = []
+const permissionListeners = new Set<() => void>()
+
+const setPermissionRequests = (requests: ConnectorPermissionRequest[]) => {
+  permissionRequests = requests
+  permissionListeners.forEach(listener => listener())
+}
+
+export function subscribePermissionRequests(listener: () => void): () => void {
+  permissionListeners.add(listener)
+  return () => permissionListeners.delete(listener)
+}
+
+/** The request shown to the user; later ones wait until it is settled */
+export const getCurrentPermissionRequest = (): ConnectorPermissionRequest | undefined =>
+  permissionRequests[0]
+
+/**
+ * Ask the user to approve what a user-installed connector asks for. The dialog records the
+ * approval with `approve_connector_permissions` before this resolves to true; it resolves to
+ * false when the user declines, and the connector then can't run.
+ */
+export function confirmConnectorPermissions(
+  platformId: string,
+  name: string,
+  grant: ConnectorGrant
+): Promise<boolean> {
+  return new Promise(resolve => {
+    const request: ConnectorPermissionRequest = {
+      platformId,
+      name,
+      grant,
+      settle: approved => {
+        setPermissionRequests(permissionRequests.filter(r => r !== request))
+        resolve(approved)
+      },
+    }
+    setPermissionRequests([...permissionRequests, request])
+  })
+}
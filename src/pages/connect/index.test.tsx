@@ -62,6 +62,7 @@ function defaultPlatforms(overrides: Partial<ReturnType<typeof mockUsePlatforms>
     isPlatformConnected: vi.fn(() => false),
     platformsLoaded: true,
     platformLoadError: null,
+    connectorLoadErrors: [],
     ...overrides,
   }
 }
@@ -117,8 +117,13 @@ export function useConnectPage(): UseConnectPageResult {
     }
   }, [params.secret, params.sessionId])
 
-  const { platforms, isPlatformConnected, platformsLoaded, platformLoadError } =
-    usePlatforms()
+  const {
+    platforms,
+    isPlatformConnected,
+    platformsLoaded,
+    platformLoadError,
+    connectorLoadErrors,
+  } = usePlatforms()
   const { startImport } = useConnector()
   const [connectRunId, setConnectRunId] = useState<string | null>(null)
   const activeRun = useSelector((state: RootState) =>
@@ -176,7 +181,13 @@ export function useConnectPage(): UseConnectPageResult {
       : isMissingConnector
         ? `No connector installed for ${
             dataSourceLabel ?? "requested scope"
-          }.${scopeSummary ? ` Scope: ${scopeSummary}.` : ""}`
+          }.${scopeSummary ? ` Scope: ${scopeSummary}.` : ""}${
+            connectorLoadErrors.length > 0
+              ? ` ${connectorLoadErrors.length} connector(s) failed to load: ${connectorLoadErrors
+                  .map(loadError => loadError.error)
+                  .join("; ")}`
+              : ""
+          }`
         : null
 
   const isBusy = isCheckingPlatforms || isConnecting
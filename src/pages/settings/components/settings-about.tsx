@@ -1,5 +1,6 @@
 import { useCallback, useEffect, useState } from "react"
 import {
+  ArchiveIcon,
   ArrowUpRightIcon,
   BookOpenIcon,
   GithubIcon,
@@ -48,6 +49,8 @@ interface SettingsAboutProps {
   onStopPersonalServer: () => void
   onSimulateNoChromeChange: (value: boolean) => void
   onOpenLogFolder: () => void
+  isCreatingDiagnosticsBundle?: boolean
+  onCreateDiagnosticsBundle?: () => void
 }
 
 const BROWSER_REFRESH_FEEDBACK_MS = 700
@@ -70,6 +73,8 @@ export function SettingsAbout({
   onStopPersonalServer,
   onSimulateNoChromeChange,
   onOpenLogFolder,
+  isCreatingDiagnosticsBundle = false,
+  onCreateDiagnosticsBundle,
 }: SettingsAboutProps) {
   const [isBrowserRefreshLoading, setIsBrowserRefreshLoading] = useState(false)
   const [isNodeTestResultOpen, setIsNodeTestResultOpen] = useState(false)
@@ -411,6 +416,21 @@ export function SettingsAbout({
                 </SettingsRowAction>
               }
             />
+            {onCreateDiagnosticsBundle ? (
+              <SettingsRow
+                icon={<ArchiveIcon aria-hidden="true" />}
+                title="Diagnostics Bundle"
+                description="Redacted logs, connector versions, browser and server status in one ZIP."
+                right={
+                  <SettingsRowAction
+                    onClick={onCreateDiagnosticsBundle}
+                    disabled={isCreatingDiagnosticsBundle}
+                  >
+                    {isCreatingDiagnosticsBundle ? "Creating…" : "Create"}
+                  </SettingsRowAction>
+                }
+              />
+            ) : null}
           </SettingsCard>
         </SettingsCardStack>
       </SettingsSection>
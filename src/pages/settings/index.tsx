@@ -52,6 +52,7 @@ export function Settings() {
     dataPath,
     appVersion,
     logPath,
+    isCreatingDiagnosticsBundle,
     nodeTestStatus,
     nodeTestResult,
     nodeTestError,
@@ -66,6 +67,7 @@ export function Settings() {
     walletAddress,
     onOpenDataFolder,
     onOpenLogFolder,
+    onCreateDiagnosticsBundle,
     onTestNodeJs,
     onDebugPaths,
     onClearDebugPaths,
@@ -138,6 +140,8 @@ export function Settings() {
         onStopPersonalServer={personalServer.stopServer}
         onSimulateNoChromeChange={onSimulateNoChromeChange}
         onOpenLogFolder={onOpenLogFolder}
+        isCreatingDiagnosticsBundle={isCreatingDiagnosticsBundle}
+        onCreateDiagnosticsBundle={onCreateDiagnosticsBundle}
       />
     )
   } else if (activeSection === "imports") {
@@ -30,6 +30,7 @@ export function useSettingsPage() {
   const [dataPath, setDataPath] = useState<string>("")
   const [appVersion, setAppVersion] = useState<string>("")
   const [logPath, setLogPath] = useState<string>("")
+  const [isCreatingDiagnosticsBundle, setIsCreatingDiagnosticsBundle] = useState(false)
   const [nodeTestStatus, setNodeTestStatus] = useState<
     "idle" | "testing" | "success" | "error"
   >("idle")
@@ -88,6 +89,18 @@ export function useSettingsPage() {
     await invoke("open_folder", { path: logPath })
   }, [logPath])
 
+  const createDiagnosticsBundle = useCallback(async () => {
+    setIsCreatingDiagnosticsBundle(true)
+    try {
+      const bundlePath = await invoke<string>("create_diagnostics_bundle")
+      await invoke("open_folder", { path: bundlePath.replace(/[\\/][^\\/]+$/, "") })
+    } catch (error) {
+      console.error("Failed to create diagnostics bundle:", error)
+    } finally {
+      setIsCreatingDiagnosticsBundle(false)
+    }
+  }, [])
+
   const testNodeJs = useCallback(async () => {
     setNodeTestStatus("testing")
     setNodeTestResult(null)
@@ -227,6 +240,7 @@ export function useSettingsPage() {
     dataPath,
     appVersion,
     logPath,
+    isCreatingDiagnosticsBundle,
     nodeTestStatus,
     nodeTestResult,
     nodeTestError,
@@ -241,6 +255,7 @@ export function useSettingsPage() {
     walletAddress,
     onOpenDataFolder: openDataFolder,
     onOpenLogFolder: openLogFolder,
+    onCreateDiagnosticsBundle: createDiagnosticsBundle,
     onTestNodeJs: testNodeJs,
     onDebugPaths: debugPaths,
     onClearDebugPaths: clearDebugPaths,
@@ -1,6 +1,6 @@
 import { configureStore, createSlice } from '@reduxjs/toolkit';
 import type { PayloadAction } from '@reduxjs/toolkit';
-import type { Run, Platform, AppState, ExportedData, ProgressPhase, ConnectorUpdateInfo, AuthState, AuthUser, ConnectedApp, AppConfig } from '../types';
+import type { Run, Platform, AppState, ExportedData, ProgressPhase, ConnectorUpdateInfo, AuthState, AuthUser, ConnectedApp, AppConfig, ConnectorPrompt } from '../types';
 import { getAccountRole } from '../config/account-access';
 
 const initialAuthState: AuthState = {
@@ -237,6 +237,21 @@ const appSlice = createSlice({
         run.syncedToPersonalServer = true;
       }
     },
+    addRunPrompt(state, action: PayloadAction<{ runId: string; prompt: ConnectorPrompt }>) {
+      const run = state.runs.find((r) => r.id === action.payload.runId);
+      if (run) {
+        const prompts = (run.pendingPrompts || []).filter(
+          (p) => p.promptId !== action.payload.prompt.promptId
+        );
+        run.pendingPrompts = [...prompts, action.payload.prompt];
+      }
+    },
+    removeRunPrompt(state, action: PayloadAction<{ runId: string; promptId: string }>) {
+      const run = state.runs.find((r) => r.id === action.payload.runId);
+      if (run?.pendingPrompts) {
+        run.pendingPrompts = run.pendingPrompts.filter((p) => p.promptId !== action.payload.promptId);
+      }
+    },
   },
 });
 
@@ -270,6 +285,8 @@ export const {
   removeConnectedApp,
   setAppConfig,
   markRunSynced,
+  addRunPrompt,
+  removeRunPrompt,
 } = appSlice.actions;
 
 export const store = configureStore({
@@ -11,8 +11,79 @@ export interface Platform {
   connectSelector: string | null;
   exportFrequency: string | null;
   vectorize_config: Record<string, unknown> | null;
-  /** Runtime type: "vanilla" (default) or "network-capture" (uses network interception) */
-  runtime?: string | null;
+  /** How the connector runs; null means "vanilla" */
+  runtime?: ConnectorRuntime | null;
+  /** Script file, relative to the connector's company directory */
+  entry?: string;
+  manifestVersion?: number;
+  scopes?: ConnectorScope[];
+  /** Capabilities the connector asks for */
+  permissions?: ConnectorPermission[];
+  /** Hosts the connector may open and fetch from ("*.example.com" also matches example.com) */
+  allowedDomains?: string[];
+  /** Hosts Playwright runs are confined to; empty unless the manifest lists allowedDomains */
+  navigationDomains?: string[];
+  minAppVersion?: string | null;
+  source?: 'bundled' | 'user';
+  /** What a user-installed connector asks for that the user hasn't approved; it can't run until then */
+  pendingPermissions?: ConnectorGrant | null;
+  /** False when the user disabled this connector in settings */
+  enabled?: boolean;
+  /** User overrides from AppConfig.connectors, if any */
+  settings?: ConnectorSettings | null;
+}
+
+export type ConnectorRuntime = 'vanilla' | 'network-capture' | 'playwright';
+
+export type ConnectorPermission = 'network-capture' | 'downloads';
+
+/** Domains and capabilities a connector asks for, or that the user approved */
+export interface ConnectorGrant {
+  allowedDomains: string[];
+  permissions: ConnectorPermission[];
+}
+
+/** A data scope declared in a connector manifest */
+export interface ConnectorScope {
+  scope: string;
+  label: string;
+  description: string;
+  /** JSON Schema of the data exported for this scope */
+  schema?: Record<string, unknown> | null;
+}
+
+/** A connector manifest that get_platforms skipped, and why */
+export interface ConnectorLoadError {
+  path: string;
+  error: string;
+  /** Which connectors directory the manifest is in */
+  source: 'bundled' | 'user';
+}
+
+/** Result of get_platforms */
+export interface PlatformsLoad {
+  platforms: Platform[];
+  diagnostics: ConnectorLoadError[];
+}
+
+/** Result of validate_connector */
+export interface ConnectorValidation {
+  path: string;
+  valid: boolean;
+  errors: string[];
+  platform: Platform | null;
+}
+
+/** Per-connector preferences stored in AppConfig.connectors, keyed by connector id */
+export interface ConnectorSettings {
+  enabled?: boolean;
+  headless?: boolean;
+  scopes?: string[];
+  timeoutSecs?: number;
+  stallTimeoutSecs?: number;
+  exportFrequency?: 'daily' | 'weekly' | 'monthly' | 'manual';
+  /** Set by approve_connector_permissions */
+  approvedPermissions?: ConnectorGrant;
 }
 
 export interface ProgressPhase {
@@ -45,6 +116,33 @@ export interface Run {
   itemCount?: number;  // Real-time count during collection
   // Sync status
   syncedToPersonalServer?: boolean;
+  // Questions the connector is waiting on the user to answer
+  pendingPrompts?: ConnectorPrompt[];
+}
+
+export type ConnectorPromptKind = 'text' | 'secret' | 'code' | 'choice' | 'confirm';
+
+export interface ConnectorPrompt {
+  promptId: string;
+  kind: ConnectorPromptKind;
+  message: string;
+  options?: unknown[] | null;
+  timeoutMs: number;
+  expiresAt: number;
+}
+
+export interface ConnectorPromptEvent extends ConnectorPrompt {
+  runId: string;
+  platformId: string;
+  name: string;
+  timestamp: number;
+}
+
+export interface ConnectorPromptClosedEvent {
+  runId: string;
+  promptId: string;
+  reason: string;
+  timestamp: number;
 }
 
 export interface ExportedData {
@@ -128,6 +226,15 @@ export interface ConnectorUpdateInfo {
   latestVersion: string;
   hasUpdate: boolean;
   isNew: boolean;
+  /** Registry the latest version comes from */
+  registryUrl: string;
+}
+
+/** Result of download_connector */
+export interface ConnectorInstall {
+  platformId: string;
+  /** The connector won't run until these are approved */
+  pendingPermissions: ConnectorGrant | null;
 }
 
 export interface AuthState {
@@ -156,8 +263,34 @@ export interface ConnectedApp {
   connectedAt: string;
 }
 
+/** A connector registry to install from (`AppConfig.registries`) */
+export interface RegistrySource {
+  /** URL of the registry JSON: http(s), or file:// for a local registry */
+  url: string;
+  /** Minisign public key the registry and its packages are signed with; defaults to the app's pinned keys */
+  trustKey?: string;
+  /** When registries offer the same connector, the highest priority wins */
+  priority?: number;
+}
+
 export interface AppConfig {
+  version?: number;
   storageProvider: 'local' | 'vana' | 'gdrive' | 'dropbox';
   serverMode: 'cloud' | 'self-hosted';
   selfHostedUrl?: string;
+  keepSyncedContent?: boolean;
+  connectors?: Record<string, ConnectorSettings>;
+  /** Playwright runs allowed at once; further runs wait in the run queue */
+  maxConcurrentRuns?: number;
+  /** Extra regexes masked in the log file, on top of the built-in secret and PII patterns */
+  logRedactionPatterns?: string[];
+  /** Registries connectors are installed from; empty means the default registry */
+  registries?: RegistrySource[];
+  /** Global policy for background runs scheduled by exportFrequency */
+  scheduledRuns?: {
+    enabled?: boolean;
+    onlyWhenIdle?: boolean;
+    idleMinutes?: number;
+    onlyOnAcPower?: boolean;
+  };
 }